@@ -1,4 +1,5 @@
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     tonic_build::compile_protos("proto/sync_graph.proto")?;
+    tonic_build::compile_protos("proto/graph_api.proto")?;
     Ok(())
 }
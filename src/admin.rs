@@ -0,0 +1,109 @@
+use std::time::{Duration, Instant, SystemTime};
+
+use actix::Addr;
+
+use crate::graph::{Graph, GraphStats, GraphStatsQuery};
+use crate::mutations_log::{
+    MutationsLog, MutationsLogStats, MutationsLogStatsQuery,
+};
+use crate::remotes::{RemoteStatus, Remotes, RemotesStatusQuery};
+use crate::sync_graph::{self, StatusResponse};
+use crate::{GraphEdge, GraphNode, GraphNodeIndex, StoreError};
+
+/// Introspection of a running database, see
+/// [`crate::GraphDatabase::status`].
+#[derive(Debug, Clone)]
+pub struct DatabaseStatus {
+    pub node_count: usize,
+    pub edge_count: usize,
+    pub mutations_log_length: usize,
+    pub last_mutation_hash: Option<String>,
+    pub remotes: Vec<RemoteStatus>,
+    /// Hashes of the replicated mutations which could not be applied.
+    pub pending_mutations: Vec<String>,
+    /// Size in bytes of the saved graph.
+    pub snapshot_size: Option<u64>,
+    /// Time since the graph was last saved.
+    pub snapshot_age: Option<Duration>,
+    pub uptime: Duration,
+    pub version: String,
+}
+
+impl DatabaseStatus {
+    pub(crate) async fn collect<N, E, I>(
+        graph: &Addr<Graph<N, E, I>>,
+        mutations_log: &Addr<MutationsLog<N, E, I>>,
+        remotes: &Addr<Remotes<N, E, I>>,
+        started_at: Instant,
+    ) -> Result<Self, StoreError>
+    where
+        N: GraphNode + Unpin + 'static,
+        E: GraphEdge + Unpin + 'static,
+        I: GraphNodeIndex + From<N> + Unpin + 'static,
+    {
+        let GraphStats {
+            node_count,
+            edge_count,
+            snapshot,
+            ..
+        } = graph.send(GraphStatsQuery).await?;
+        let MutationsLogStats {
+            length,
+            last_hash,
+            pending,
+        } = mutations_log.send(MutationsLogStatsQuery).await??;
+        let remotes = remotes.send(RemotesStatusQuery).await?;
+
+        Ok(Self {
+            node_count,
+            edge_count,
+            mutations_log_length: length,
+            last_mutation_hash: last_hash,
+            remotes,
+            pending_mutations: pending,
+            snapshot_size: snapshot.map(|(size, _)| size),
+            snapshot_age: snapshot.and_then(|(_, modified)| {
+                SystemTime::now().duration_since(modified).ok()
+            }),
+            uptime: started_at.elapsed(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+        })
+    }
+}
+
+impl From<DatabaseStatus> for StatusResponse {
+    fn from(status: DatabaseStatus) -> Self {
+        let remotes = status
+            .remotes
+            .into_iter()
+            .map(|remote| match remote.remotes_log {
+                Ok(remotes_log) => sync_graph::RemoteStatus {
+                    address: remote.address,
+                    remotes_log,
+                    error: String::new(),
+                },
+                Err(err) => sync_graph::RemoteStatus {
+                    address: remote.address,
+                    remotes_log: Default::default(),
+                    error: err.to_string(),
+                },
+            })
+            .collect();
+
+        Self {
+            node_count: status.node_count as u64,
+            edge_count: status.edge_count as u64,
+            mutations_log_length: status.mutations_log_length as u64,
+            last_mutation_hash: status.last_mutation_hash.unwrap_or_default(),
+            remotes,
+            pending_mutations: status.pending_mutations,
+            snapshot_size: status.snapshot_size.unwrap_or_default(),
+            snapshot_age_secs: status
+                .snapshot_age
+                .map(|age| age.as_secs())
+                .unwrap_or_default(),
+            uptime_secs: status.uptime.as_secs(),
+            version: status.version,
+        }
+    }
+}
@@ -0,0 +1,402 @@
+use std::collections::{HashMap, VecDeque};
+
+use petgraph::stable_graph::{NodeIndex, StableGraph};
+use petgraph::Direction::{Incoming, Outgoing};
+use petgraph::{Directed, Direction};
+
+use crate::{GraphEdge, GraphNode, GraphNodeIndex};
+
+const DEFAULT_PAGE_RANK_TOLERANCE: f64 = 1.0e-6;
+
+/// PageRank of every node with the given damping factor, stopping after
+/// `max_iterations` or once the scores converged. Rank of dangling nodes is
+/// distributed evenly over all nodes. With `undirected` rank flows along
+/// edges in both directions.
+pub fn page_rank<N, E, I>(
+    graph: &StableGraph<N, E, Directed>,
+    damping_factor: f64,
+    max_iterations: usize,
+    undirected: bool,
+) -> Vec<(I, f64)>
+where
+    N: GraphNode,
+    E: GraphEdge,
+    I: GraphNodeIndex + From<N>,
+{
+    let indices: Vec<NodeIndex> = graph.node_indices().collect();
+    let positions = get_positions(&indices);
+    let count = indices.len();
+
+    if count == 0 {
+        return Vec::new();
+    }
+
+    let out_degrees: Vec<usize> = indices
+        .iter()
+        .map(|index| get_neighbors(graph, *index, Outgoing, undirected).count())
+        .collect();
+
+    let initial = 1.0 / count as f64;
+    let mut ranks = vec![initial; count];
+
+    for _ in 0..max_iterations {
+        let dangling: f64 = ranks
+            .iter()
+            .zip(out_degrees.iter())
+            .filter(|(_, degree)| **degree == 0)
+            .map(|(rank, _)| rank)
+            .sum();
+
+        let base = (1.0 - damping_factor) / count as f64
+            + damping_factor * dangling / count as f64;
+
+        let mut next = vec![base; count];
+
+        for edge in graph.edge_indices() {
+            if let Some((source, target)) = graph.edge_endpoints(edge) {
+                let source = positions[&source];
+                let target = positions[&target];
+
+                next[target] +=
+                    damping_factor * ranks[source] / out_degrees[source] as f64;
+
+                if undirected {
+                    next[source] += damping_factor * ranks[target]
+                        / out_degrees[target] as f64;
+                }
+            }
+        }
+
+        let delta: f64 = next
+            .iter()
+            .zip(ranks.iter())
+            .map(|(next, previous)| (next - previous).abs())
+            .sum();
+
+        ranks = next;
+
+        if delta < DEFAULT_PAGE_RANK_TOLERANCE * count as f64 {
+            break;
+        }
+    }
+
+    into_sorted_scores(graph, &indices, ranks)
+}
+
+/// Sum of in- and out-degree of every node, normalized by the maximum
+/// possible degree `n - 1`. The same for directed and undirected graphs.
+pub fn degree_centrality<N, E, I>(
+    graph: &StableGraph<N, E, Directed>,
+) -> Vec<(I, f64)>
+where
+    N: GraphNode,
+    E: GraphEdge,
+    I: GraphNodeIndex + From<N>,
+{
+    let indices: Vec<NodeIndex> = graph.node_indices().collect();
+    let scale = match indices.len() {
+        0 | 1 => 1.0,
+        count => 1.0 / (count - 1) as f64,
+    };
+
+    let scores = indices
+        .iter()
+        .map(|index| {
+            let degree = graph.neighbors_directed(*index, Outgoing).count()
+                + graph.neighbors_directed(*index, Incoming).count();
+            degree as f64 * scale
+        })
+        .collect();
+
+    into_sorted_scores(graph, &indices, scores)
+}
+
+/// Betweenness centrality of every node following Brandes' algorithm on
+/// the unweighted graph, directed unless `undirected`, normalized by
+/// `(n - 1)(n - 2)`.
+pub fn betweenness_centrality<N, E, I>(
+    graph: &StableGraph<N, E, Directed>,
+    undirected: bool,
+) -> Vec<(I, f64)>
+where
+    N: GraphNode,
+    E: GraphEdge,
+    I: GraphNodeIndex + From<N>,
+{
+    let indices: Vec<NodeIndex> = graph.node_indices().collect();
+    let positions = get_positions(&indices);
+    let count = indices.len();
+
+    let mut scores = vec![0.0; count];
+
+    for source in 0..count {
+        let mut stack = Vec::with_capacity(count);
+        let mut predecessors: Vec<Vec<usize>> = vec![Vec::new(); count];
+        let mut paths = vec![0.0; count];
+        let mut distances: Vec<Option<usize>> = vec![None; count];
+        let mut queue = VecDeque::new();
+
+        paths[source] = 1.0;
+        distances[source] = Some(0);
+        queue.push_back(source);
+
+        while let Some(current) = queue.pop_front() {
+            stack.push(current);
+            let distance = distances[current].unwrap_or_default();
+
+            for neighbor in
+                get_neighbors(graph, indices[current], Outgoing, undirected)
+            {
+                let neighbor = positions[&neighbor];
+
+                if distances[neighbor].is_none() {
+                    distances[neighbor] = Some(distance + 1);
+                    queue.push_back(neighbor);
+                }
+
+                if distances[neighbor] == Some(distance + 1) {
+                    paths[neighbor] += paths[current];
+                    predecessors[neighbor].push(current);
+                }
+            }
+        }
+
+        let mut dependencies = vec![0.0; count];
+
+        while let Some(current) = stack.pop() {
+            for predecessor in predecessors[current].iter() {
+                dependencies[*predecessor] += paths[*predecessor]
+                    / paths[current]
+                    * (1.0 + dependencies[current]);
+            }
+
+            if current != source {
+                scores[current] += dependencies[current];
+            }
+        }
+    }
+
+    if count > 2 {
+        let scale = 1.0 / ((count - 1) * (count - 2)) as f64;
+        scores.iter_mut().for_each(|score| *score *= scale);
+    }
+
+    into_sorted_scores(graph, &indices, scores)
+}
+
+/// Closeness centrality of every node computed from the distances of the
+/// nodes that can reach it, following edges in both directions with
+/// `undirected`. Scores of nodes reachable only from a part of the graph are
+/// scaled by the size of that part (Wasserman and Faust).
+pub fn closeness_centrality<N, E, I>(
+    graph: &StableGraph<N, E, Directed>,
+    undirected: bool,
+) -> Vec<(I, f64)>
+where
+    N: GraphNode,
+    E: GraphEdge,
+    I: GraphNodeIndex + From<N>,
+{
+    let indices: Vec<NodeIndex> = graph.node_indices().collect();
+    let count = indices.len();
+
+    let scores = indices
+        .iter()
+        .map(|index| {
+            let distances = get_distances(graph, *index, Incoming, undirected);
+            let reachable = distances.len();
+            let total: usize = distances.values().sum();
+
+            if total == 0 || count <= 1 {
+                return 0.0;
+            }
+
+            let closeness = (reachable - 1) as f64 / total as f64;
+            closeness * (reachable - 1) as f64 / (count - 1) as f64
+        })
+        .collect();
+
+    into_sorted_scores(graph, &indices, scores)
+}
+
+//
+// private methods
+//
+
+fn get_positions(indices: &[NodeIndex]) -> HashMap<NodeIndex, usize> {
+    indices
+        .iter()
+        .enumerate()
+        .map(|(position, index)| (*index, position))
+        .collect()
+}
+
+/// Neighbors of `index` along edges in direction `dir`, along edges in both
+/// directions if `undirected`.
+fn get_neighbors<N, E>(
+    graph: &StableGraph<N, E, Directed>,
+    index: NodeIndex,
+    dir: Direction,
+    undirected: bool,
+) -> impl Iterator<Item = NodeIndex> + '_ {
+    let directions = if undirected {
+        vec![Outgoing, Incoming]
+    } else {
+        vec![dir]
+    };
+
+    directions
+        .into_iter()
+        .flat_map(move |dir| graph.neighbors_directed(index, dir))
+}
+
+fn get_distances<N, E>(
+    graph: &StableGraph<N, E, Directed>,
+    start: NodeIndex,
+    dir: Direction,
+    undirected: bool,
+) -> HashMap<NodeIndex, usize> {
+    let mut distances = HashMap::from([(start, 0)]);
+    let mut queue = VecDeque::from([start]);
+
+    while let Some(current) = queue.pop_front() {
+        let distance = distances[&current];
+
+        for neighbor in get_neighbors(graph, current, dir, undirected) {
+            if !distances.contains_key(&neighbor) {
+                distances.insert(neighbor, distance + 1);
+                queue.push_back(neighbor);
+            }
+        }
+    }
+
+    distances
+}
+
+fn into_sorted_scores<N, E, I>(
+    graph: &StableGraph<N, E, Directed>,
+    indices: &[NodeIndex],
+    scores: Vec<f64>,
+) -> Vec<(I, f64)>
+where
+    N: GraphNode,
+    E: GraphEdge,
+    I: GraphNodeIndex + From<N>,
+{
+    let mut scores: Vec<(I, f64)> = indices
+        .iter()
+        .zip(scores)
+        .filter_map(|(index, score)| {
+            graph
+                .node_weight(*index)
+                .map(|node| (node.clone().into(), score))
+        })
+        .collect();
+
+    scores.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    scores
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+
+    #[derive(
+        Debug, Default, Clone, PartialEq, PartialOrd, Serialize, Deserialize,
+    )]
+    struct Edge;
+    impl GraphEdge for Edge {}
+
+    #[derive(
+        Debug,
+        Default,
+        Clone,
+        Hash,
+        PartialEq,
+        Eq,
+        PartialOrd,
+        Ord,
+        Serialize,
+        Deserialize,
+    )]
+    struct Node(usize);
+    impl GraphNode for Node {}
+    impl GraphNodeIndex for Node {}
+
+    // 0 -> 1 -> 2 -> 3, 0 -> 2
+    fn get_graph() -> StableGraph<Node, Edge, Directed> {
+        let mut graph = StableGraph::default();
+        let nodes: Vec<_> = (0..4).map(|i| graph.add_node(Node(i))).collect();
+
+        graph.add_edge(nodes[0], nodes[1], Edge);
+        graph.add_edge(nodes[1], nodes[2], Edge);
+        graph.add_edge(nodes[2], nodes[3], Edge);
+        graph.add_edge(nodes[0], nodes[2], Edge);
+
+        graph
+    }
+
+    #[test]
+    fn test_page_rank_ok() {
+        let scores: Vec<(Node, f64)> =
+            page_rank(&get_graph(), 0.85, 100, false);
+
+        let total: f64 = scores.iter().map(|(_, score)| score).sum();
+        assert!((total - 1.0).abs() < 1.0e-3);
+        assert_eq!(scores[0].0, Node(3));
+        assert_eq!(scores[3].0, Node(0));
+    }
+
+    #[test]
+    fn test_degree_centrality_ok() {
+        let scores: Vec<(Node, f64)> = degree_centrality(&get_graph());
+
+        assert_eq!(scores[0], (Node(2), 1.0));
+        assert_eq!(scores[3], (Node(3), 1.0 / 3.0));
+    }
+
+    #[test]
+    fn test_betweenness_centrality_ok() {
+        let scores: Vec<(Node, f64)> =
+            betweenness_centrality(&get_graph(), false);
+
+        // 2 lies on the paths 0->3 and 1->3
+        assert_eq!(scores[0], (Node(2), 2.0 / 6.0));
+        assert_eq!(scores[1].1, 0.0);
+    }
+
+    #[test]
+    fn test_betweenness_centrality_undirected_ok() {
+        let scores: Vec<(Node, f64)> =
+            betweenness_centrality(&get_graph(), true);
+
+        // 2 lies on the paths 0-3 and 1-3 in both directions
+        assert_eq!(scores[0], (Node(2), 4.0 / 6.0));
+        assert_eq!(scores[1].1, 0.0);
+    }
+
+    #[test]
+    fn test_closeness_centrality_ok() {
+        let scores: Vec<(Node, f64)> =
+            closeness_centrality(&get_graph(), false);
+
+        assert_eq!(scores.last(), Some(&(Node(0), 0.0)));
+        assert_eq!(scores[0].0, Node(2));
+    }
+
+    #[test]
+    fn test_undirected_ok() {
+        // 2 is adjacent to every other node
+        let scores: Vec<(Node, f64)> = closeness_centrality(&get_graph(), true);
+        assert_eq!(scores[0], (Node(2), 1.0));
+        assert!(scores.iter().all(|(_, score)| *score > 0.0));
+
+        let scores: Vec<(Node, f64)> = page_rank(&get_graph(), 0.85, 100, true);
+        let total: f64 = scores.iter().map(|(_, score)| score).sum();
+        assert!((total - 1.0).abs() < 1.0e-3);
+        assert_eq!(scores[0].0, Node(2));
+    }
+}
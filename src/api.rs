@@ -0,0 +1,634 @@
+use actix::Addr;
+
+use petgraph::Direction;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tonic::{Request, Response, Status};
+
+use crate::auth::ServerAuth;
+use crate::graph::Graph;
+use crate::graph_api::graph_api_server::GraphApi;
+use crate::graph_api::{
+    self, EdgeMessage, EdgesMessage, EmptyMessage, EndpointsMessage,
+    FilterGraphRequest, GraphMessage, KeyMessage, NeighborsRequest,
+    NodeMessage, NodesMessage, RemoveNodeRequest, RemoveNodeResponse,
+};
+use crate::metrics;
+use crate::mutations_log::MutationsLog;
+use crate::{
+    DeletionPolicy, GraphEdge, GraphMutation, GraphNode, GraphNodeIndex,
+    GraphQuery, GraphResponse, StoreError,
+};
+
+/// Client facing gRPC API of the graph, nodes, edges and keys are passed as
+/// JSON so clients don't need to know the bincode encoding of the crate.
+#[derive(Debug)]
+pub struct ApiServer<N, E, I>
+where
+    N: GraphNode + Unpin + 'static,
+    E: GraphEdge + Unpin + 'static,
+    I: GraphNodeIndex + From<N> + Unpin + 'static,
+{
+    graph: Addr<Graph<N, E, I>>,
+    mutations_log: Addr<MutationsLog<N, E, I>>,
+    /// Url the database is served on, labeling its metrics.
+    server_url: String,
+}
+
+impl<N, E, I> ApiServer<N, E, I>
+where
+    N: GraphNode + Unpin + 'static,
+    E: GraphEdge + Unpin + 'static,
+    I: GraphNodeIndex + From<N> + Unpin + 'static,
+{
+    pub fn new(
+        graph: Addr<Graph<N, E, I>>,
+        mutations_log: Addr<MutationsLog<N, E, I>>,
+        server_url: String,
+    ) -> Self {
+        Self {
+            graph,
+            mutations_log,
+            server_url,
+        }
+    }
+
+    async fn mutate(
+        &self,
+        mutation: GraphMutation<N, E, I>,
+    ) -> Result<GraphResponse<N, E, I>, Status> {
+        Ok(metrics::send(
+            &self.server_url,
+            "mutations_log",
+            &self.mutations_log,
+            mutation,
+        )
+        .await
+        .map_err(StoreError::from)??)
+    }
+
+    async fn query(
+        &self,
+        query: GraphQuery<N, E, I>,
+    ) -> Result<GraphResponse<N, E, I>, Status> {
+        Ok(metrics::send(&self.server_url, "graph", &self.graph, query)
+            .await
+            .map_err(StoreError::from)??)
+    }
+}
+
+#[tonic::async_trait]
+impl<N, E, I> GraphApi for ApiServer<N, E, I>
+where
+    N: GraphNode + Unpin,
+    E: GraphEdge + Unpin,
+    I: GraphNodeIndex + From<N> + Unpin,
+{
+    async fn add_node(
+        &self,
+        request: Request<NodeMessage>,
+    ) -> Result<Response<NodeMessage>, Status> {
+        ServerAuth::require_write(&request)?;
+
+        let node: N = decode(&request.into_inner().node, "node")?;
+
+        match self.mutate(GraphMutation::AddNode(node)).await? {
+            GraphResponse::Node(node) => Ok(Response::new(NodeMessage {
+                node: encode(&node)?,
+            })),
+            response => Err(unexpected(response)),
+        }
+    }
+
+    async fn remove_node(
+        &self,
+        request: Request<RemoveNodeRequest>,
+    ) -> Result<Response<RemoveNodeResponse>, Status> {
+        ServerAuth::require_write(&request)?;
+
+        let request = request.into_inner();
+        let key: I = decode(&request.key, "key")?;
+        let policy = match request.policy() {
+            graph_api::DeletionPolicy::Restrict => DeletionPolicy::Restrict,
+            graph_api::DeletionPolicy::Detach => DeletionPolicy::Detach,
+            graph_api::DeletionPolicy::Cascade => DeletionPolicy::Cascade,
+        };
+
+        match self
+            .mutate(GraphMutation::RemoveNodeWithPolicy((key, policy)))
+            .await?
+        {
+            GraphResponse::RemovedNode((node, edges)) => {
+                Ok(Response::new(RemoveNodeResponse {
+                    node: encode(&node)?,
+                    edges: encode_edges(edges)?,
+                }))
+            }
+            response => Err(unexpected(response)),
+        }
+    }
+
+    async fn get_node(
+        &self,
+        request: Request<KeyMessage>,
+    ) -> Result<Response<NodeMessage>, Status> {
+        let key: I = decode(&request.into_inner().key, "key")?;
+
+        match self.query(GraphQuery::GetNode(key)).await? {
+            GraphResponse::Node(node) => Ok(Response::new(NodeMessage {
+                node: encode(&node)?,
+            })),
+            response => Err(unexpected(response)),
+        }
+    }
+
+    async fn get_nodes(
+        &self,
+        _request: Request<EmptyMessage>,
+    ) -> Result<Response<NodesMessage>, Status> {
+        match self.query(GraphQuery::GetNodes).await? {
+            GraphResponse::Nodes(nodes) => Ok(Response::new(NodesMessage {
+                nodes: nodes.iter().map(encode).collect::<Result<_, _>>()?,
+            })),
+            response => Err(unexpected(response)),
+        }
+    }
+
+    async fn add_edge(
+        &self,
+        request: Request<EdgeMessage>,
+    ) -> Result<Response<EmptyMessage>, Status> {
+        ServerAuth::require_write(&request)?;
+
+        let EdgeMessage { from, to, edge } = request.into_inner();
+        let from: I = decode(&from, "from")?;
+        let to: I = decode(&to, "to")?;
+        let edge: E = decode(&edge, "edge")?;
+
+        self.mutate(GraphMutation::AddEdge((from, to, edge)))
+            .await?;
+
+        Ok(Response::new(EmptyMessage {}))
+    }
+
+    async fn remove_edge(
+        &self,
+        request: Request<EndpointsMessage>,
+    ) -> Result<Response<EdgeMessage>, Status> {
+        ServerAuth::require_write(&request)?;
+
+        let EndpointsMessage { from, to } = request.into_inner();
+        let (from_key, to_key): (I, I) =
+            (decode(&from, "from")?, decode(&to, "to")?);
+
+        match self
+            .mutate(GraphMutation::RemoveEdge((from_key, to_key)))
+            .await?
+        {
+            GraphResponse::Edge(edge) => Ok(Response::new(EdgeMessage {
+                from,
+                to,
+                edge: encode(&edge)?,
+            })),
+            response => Err(unexpected(response)),
+        }
+    }
+
+    async fn get_edge(
+        &self,
+        request: Request<EndpointsMessage>,
+    ) -> Result<Response<EdgeMessage>, Status> {
+        let EndpointsMessage { from, to } = request.into_inner();
+        let (from_key, to_key): (I, I) =
+            (decode(&from, "from")?, decode(&to, "to")?);
+
+        match self.query(GraphQuery::GetEdge((from_key, to_key))).await? {
+            GraphResponse::Edge(edge) => Ok(Response::new(EdgeMessage {
+                from,
+                to,
+                edge: encode(&edge)?,
+            })),
+            response => Err(unexpected(response)),
+        }
+    }
+
+    async fn get_edges(
+        &self,
+        _request: Request<EmptyMessage>,
+    ) -> Result<Response<EdgesMessage>, Status> {
+        match self.query(GraphQuery::GetEdgesWithEndpoints).await? {
+            GraphResponse::Triples(triples) => {
+                Ok(Response::new(EdgesMessage {
+                    edges: encode_edges(triples)?,
+                }))
+            }
+            response => Err(unexpected(response)),
+        }
+    }
+
+    async fn get_neighbors(
+        &self,
+        request: Request<NeighborsRequest>,
+    ) -> Result<Response<NodesMessage>, Status> {
+        let request = request.into_inner();
+        let key: I = decode(&request.key, "key")?;
+        let query = match request.direction() {
+            graph_api::Direction::Both => GraphQuery::GetNeighborsUnd(key),
+            graph_api::Direction::Outgoing => {
+                GraphQuery::GetNeighborsDir((key, Direction::Outgoing))
+            }
+            graph_api::Direction::Incoming => {
+                GraphQuery::GetNeighborsDir((key, Direction::Incoming))
+            }
+        };
+
+        match self.query(query).await? {
+            GraphResponse::Nodes(nodes) => Ok(Response::new(NodesMessage {
+                nodes: nodes.iter().map(encode).collect::<Result<_, _>>()?,
+            })),
+            response => Err(unexpected(response)),
+        }
+    }
+
+    async fn filter_graph(
+        &self,
+        request: Request<FilterGraphRequest>,
+    ) -> Result<Response<GraphMessage>, Status> {
+        let FilterGraphRequest {
+            include_nodes,
+            include_edges,
+        } = request.into_inner();
+
+        let include_nodes: Option<Vec<N>> = if include_nodes.is_empty() {
+            None
+        } else {
+            Some(
+                include_nodes
+                    .iter()
+                    .map(|node| decode(node, "include_nodes"))
+                    .collect::<Result<_, _>>()?,
+            )
+        };
+        let include_edges: Option<Vec<E>> = if include_edges.is_empty() {
+            None
+        } else {
+            Some(
+                include_edges
+                    .iter()
+                    .map(|edge| decode(edge, "include_edges"))
+                    .collect::<Result<_, _>>()?,
+            )
+        };
+
+        match self
+            .query(GraphQuery::FilterGraph((include_nodes, include_edges)))
+            .await?
+        {
+            GraphResponse::Graph(graph) => Ok(Response::new(GraphMessage {
+                graph: encode(&graph)?,
+            })),
+            response => Err(unexpected(response)),
+        }
+    }
+}
+
+impl From<StoreError> for Status {
+    fn from(err: StoreError) -> Self {
+        let message = err.to_string();
+
+        match err {
+            StoreError::GraphNotFound
+            | StoreError::EdgeNotFound
+            | StoreError::NodeNotFound => Status::not_found(message),
+            StoreError::ConflictDuplicateNode
+            | StoreError::ConflictDuplicateEdge
+            | StoreError::ConflictUniqueKey(_) => {
+                Status::already_exists(message)
+            }
+            StoreError::NodeHasEdges
+            | StoreError::AmbiguousEdge
+            | StoreError::ConstraintViolation(_) => {
+                Status::failed_precondition(message)
+            }
+            StoreError::ParseError | StoreError::QueryError(_) => {
+                Status::invalid_argument(message)
+            }
+            _ => Status::internal(message),
+        }
+    }
+}
+
+fn decode<T: DeserializeOwned>(json: &str, field: &str) -> Result<T, Status> {
+    serde_json::from_str(json).map_err(|err| {
+        Status::invalid_argument(format!(
+            "Could not decode `{field}` from JSON. Error: '{err}'"
+        ))
+    })
+}
+
+fn encode<T: Serialize>(value: &T) -> Result<String, Status> {
+    serde_json::to_string(value)
+        .map_err(|err| Status::internal(err.to_string()))
+}
+
+fn encode_edges<I, E>(
+    triples: Vec<(I, I, E)>,
+) -> Result<Vec<EdgeMessage>, Status>
+where
+    I: Serialize,
+    E: Serialize,
+{
+    triples
+        .iter()
+        .map(|(from, to, edge)| {
+            Ok(EdgeMessage {
+                from: encode(from)?,
+                to: encode(to)?,
+                edge: encode(edge)?,
+            })
+        })
+        .collect()
+}
+
+fn unexpected<N, E, I>(response: GraphResponse<N, E, I>) -> Status
+where
+    N: GraphNode + 'static,
+    E: GraphEdge + 'static,
+    I: GraphNodeIndex + From<N> + 'static,
+{
+    log::error!("[ApiServer] Unexpected response {:?}", response);
+    Status::internal("Unexpected response from the graph")
+}
+
+#[cfg(test)]
+mod tests {
+    use actix::Actor;
+    use serde::{Deserialize, Serialize};
+    use tokio::sync::broadcast;
+    use tonic::Code;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::auth::PeerRole;
+    use crate::graph::GraphMode;
+    use crate::remotes::Remotes;
+
+    #[derive(
+        Debug,
+        Default,
+        Clone,
+        Copy,
+        Hash,
+        PartialEq,
+        Eq,
+        PartialOrd,
+        Ord,
+        Serialize,
+        Deserialize,
+    )]
+    struct Edge(usize);
+    impl GraphEdge for Edge {}
+
+    #[derive(
+        Debug,
+        Default,
+        Clone,
+        Copy,
+        Hash,
+        PartialEq,
+        Eq,
+        PartialOrd,
+        Ord,
+        Serialize,
+        Deserialize,
+    )]
+    struct Node(Uuid);
+    impl GraphNode for Node {}
+
+    #[derive(
+        Debug,
+        Default,
+        Clone,
+        Copy,
+        Hash,
+        PartialEq,
+        Eq,
+        PartialOrd,
+        Ord,
+        Serialize,
+        Deserialize,
+    )]
+    struct NodeId(Uuid);
+
+    impl GraphNodeIndex for NodeId {}
+
+    impl From<Node> for NodeId {
+        fn from(node: Node) -> Self {
+            Self(node.0)
+        }
+    }
+
+    async fn setup(test_dir: &str) -> ApiServer<Node, Edge, NodeId> {
+        if std::path::Path::new(test_dir).is_dir() {
+            std::fs::remove_dir_all(test_dir).unwrap();
+        }
+
+        let graph = Graph::new(Some(test_dir.into())).unwrap().start();
+        let remotes = Remotes::new(1, None, None, GraphMode::default()).start();
+        let (events, _) = broadcast::channel(16);
+        let server_url = "http://127.0.0.1:0".to_string();
+        let mutations_log = MutationsLog::new(
+            graph.clone(),
+            remotes,
+            Some(test_dir.into()),
+            server_url.clone(),
+            events,
+        )
+        .await
+        .unwrap()
+        .start();
+
+        ApiServer::new(graph, mutations_log, server_url)
+    }
+
+    /// Request of a peer allowed to push mutations, as granted by the
+    /// `ServerAuth` interceptor.
+    fn write_request<T>(message: T) -> Request<T> {
+        let mut request = Request::new(message);
+        request.extensions_mut().insert(PeerRole::ReadWrite);
+        request
+    }
+
+    async fn add_node(api: &ApiServer<Node, Edge, NodeId>) -> String {
+        let node = serde_json::to_string(&Node(Uuid::new_v4())).unwrap();
+        api.add_node(write_request(NodeMessage { node }))
+            .await
+            .unwrap()
+            .into_inner()
+            .node
+    }
+
+    async fn add_edge(
+        api: &ApiServer<Node, Edge, NodeId>,
+        from: &str,
+        to: &str,
+        edge: usize,
+    ) {
+        api.add_edge(write_request(EdgeMessage {
+            from: from.to_string(),
+            to: to.to_string(),
+            edge: edge.to_string(),
+        }))
+        .await
+        .unwrap();
+    }
+
+    async fn remove_node(
+        api: &ApiServer<Node, Edge, NodeId>,
+        key: &str,
+        policy: graph_api::DeletionPolicy,
+    ) -> Result<RemoveNodeResponse, Status> {
+        let mut request = RemoveNodeRequest {
+            key: key.to_string(),
+            ..Default::default()
+        };
+        request.set_policy(policy);
+
+        api.remove_node(write_request(request))
+            .await
+            .map(Response::into_inner)
+    }
+
+    #[test]
+    fn test_status_from_store_error_ok() {
+        let cases = [
+            (StoreError::NodeNotFound, Code::NotFound),
+            (StoreError::EdgeNotFound, Code::NotFound),
+            (StoreError::ConflictDuplicateNode, Code::AlreadyExists),
+            (
+                StoreError::ConflictUniqueKey("email".into()),
+                Code::AlreadyExists,
+            ),
+            (StoreError::NodeHasEdges, Code::FailedPrecondition),
+            (StoreError::AmbiguousEdge, Code::FailedPrecondition),
+            (
+                StoreError::ConstraintViolation("self loop".into()),
+                Code::FailedPrecondition,
+            ),
+            (StoreError::ParseError, Code::InvalidArgument),
+            (
+                StoreError::QueryError("syntax".into()),
+                Code::InvalidArgument,
+            ),
+            (StoreError::StoreError, Code::Internal),
+        ];
+
+        for (err, code) in cases {
+            let message = err.to_string();
+            let status = Status::from(err);
+
+            assert_eq!(status.code(), code);
+            assert_eq!(status.message(), message);
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_decode_invalid_json_nok() {
+        let test_dir = "test-data/test_api_decode_invalid_json_nok";
+        let api = setup(test_dir).await;
+
+        let status = api
+            .add_node(write_request(NodeMessage {
+                node: "{not json".to_string(),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::InvalidArgument);
+        assert!(status.message().contains("`node`"));
+
+        let from = add_node(&api).await;
+        let status = api
+            .get_edge(Request::new(EndpointsMessage {
+                from,
+                to: "42".to_string(),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::InvalidArgument);
+        assert!(status.message().contains("`to`"));
+
+        if std::path::Path::new(test_dir).is_dir() {
+            std::fs::remove_dir_all(test_dir).unwrap();
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_remove_node_policies_ok() {
+        let test_dir = "test-data/test_api_remove_node_policies_ok";
+        let api = setup(test_dir).await;
+
+        let node1 = add_node(&api).await;
+        let node2 = add_node(&api).await;
+        let node3 = add_node(&api).await;
+        add_edge(&api, &node1, &node2, 1).await;
+        add_edge(&api, &node3, &node1, 2).await;
+
+        let status =
+            remove_node(&api, &node1, graph_api::DeletionPolicy::Restrict)
+                .await
+                .unwrap_err();
+        assert_eq!(status.code(), Code::FailedPrecondition);
+
+        // the removed edges are only reported when cascading
+        let response =
+            remove_node(&api, &node1, graph_api::DeletionPolicy::Cascade)
+                .await
+                .unwrap();
+        assert_eq!(response.node, node1);
+        let mut edges: Vec<_> = response
+            .edges
+            .into_iter()
+            .map(|edge| (edge.from, edge.to, edge.edge))
+            .collect();
+        edges.sort_by(|a, b| a.2.cmp(&b.2));
+        assert_eq!(
+            edges,
+            vec![
+                (node1.clone(), node2.clone(), "1".to_string()),
+                (node3.clone(), node1.clone(), "2".to_string()),
+            ]
+        );
+
+        add_edge(&api, &node2, &node3, 3).await;
+        let response =
+            remove_node(&api, &node2, graph_api::DeletionPolicy::Detach)
+                .await
+                .unwrap();
+        assert_eq!(response.node, node2);
+        assert!(response.edges.is_empty());
+
+        let edges = api
+            .get_edges(Request::new(EmptyMessage {}))
+            .await
+            .unwrap()
+            .into_inner()
+            .edges;
+        assert!(edges.is_empty());
+
+        let response =
+            remove_node(&api, &node3, graph_api::DeletionPolicy::Restrict)
+                .await
+                .unwrap();
+        assert_eq!(response.node, node3);
+
+        let status =
+            remove_node(&api, &node3, graph_api::DeletionPolicy::Detach)
+                .await
+                .unwrap_err();
+        assert_eq!(status.code(), Code::NotFound);
+
+        if std::path::Path::new(test_dir).is_dir() {
+            std::fs::remove_dir_all(test_dir).unwrap();
+        }
+    }
+}
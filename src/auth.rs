@@ -0,0 +1,156 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use tonic::metadata::AsciiMetadataValue;
+use tonic::service::Interceptor;
+use tonic::{Request, Status};
+
+use crate::StoreError;
+
+const AUTHORIZATION: &str = "authorization";
+
+/// What a peer may do on the `SyncGraph` and `GraphApi` services.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PeerRole {
+    /// Reads the mutations log and joins the remotes, without pushing
+    /// mutations.
+    ReadOnly,
+    ReadWrite,
+}
+
+/// Authentication of the peers of a cluster. Peers present `token` as
+/// bearer token on every `SyncGraph` and `GraphApi` call and are granted the
+/// role of their token in `peer_tokens`, peers of another cluster are
+/// rejected by `SyncRemotes`.
+#[derive(Debug, Clone)]
+pub struct AuthConfig {
+    pub cluster_id: String,
+    pub token: String,
+    pub peer_tokens: HashMap<String, PeerRole>,
+}
+
+impl AuthConfig {
+    /// Cluster whose peers share the secret `token`, granting them
+    /// [`PeerRole::ReadWrite`].
+    pub fn new(cluster_id: String, token: String) -> Self {
+        let peer_tokens = HashMap::from([(token.clone(), PeerRole::ReadWrite)]);
+
+        Self {
+            cluster_id,
+            token,
+            peer_tokens,
+        }
+    }
+
+    /// Accepts peers presenting `token` with `role`.
+    pub fn add_peer_token(&mut self, token: String, role: PeerRole) {
+        self.peer_tokens.insert(token, role);
+    }
+}
+
+/// Grants the role of the bearer token of a request, any request is granted
+/// [`PeerRole::ReadWrite`] without authentication configured.
+#[derive(Debug, Clone)]
+pub struct ServerAuth(Option<Arc<Vec<(String, PeerRole)>>>);
+
+impl ServerAuth {
+    pub fn new(auth: Option<&AuthConfig>) -> Self {
+        Self(auth.map(|auth| {
+            Arc::new(auth.peer_tokens.clone().into_iter().collect())
+        }))
+    }
+
+    /// Role of `token`, compared against every peer token in constant time
+    /// so the timing doesn't tell how much of a token was guessed.
+    fn role(
+        peer_tokens: &[(String, PeerRole)],
+        token: &str,
+    ) -> Option<PeerRole> {
+        peer_tokens
+            .iter()
+            .fold(None, |role, (peer_token, peer_role)| {
+                if constant_time_eq(peer_token.as_bytes(), token.as_bytes()) {
+                    Some(*peer_role)
+                } else {
+                    role
+                }
+            })
+    }
+
+    /// Fails with `PERMISSION_DENIED` unless the peer may push mutations.
+    pub fn require_write<T>(request: &Request<T>) -> Result<(), Status> {
+        match request.extensions().get::<PeerRole>() {
+            Some(PeerRole::ReadWrite) => Ok(()),
+            _ => Err(Status::permission_denied(
+                "Peer is not allowed to push mutations",
+            )),
+        }
+    }
+}
+
+impl Interceptor for ServerAuth {
+    fn call(
+        &mut self,
+        mut request: Request<()>,
+    ) -> Result<Request<()>, Status> {
+        let role = match &self.0 {
+            Some(peer_tokens) => {
+                let token = request
+                    .metadata()
+                    .get(AUTHORIZATION)
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(|value| value.strip_prefix("Bearer "))
+                    .ok_or_else(|| Status::unauthenticated("Missing token"))?;
+
+                Self::role(peer_tokens, token)
+                    .ok_or_else(|| Status::unauthenticated("Invalid token"))?
+            }
+            None => PeerRole::ReadWrite,
+        };
+
+        request.extensions_mut().insert(role);
+        Ok(request)
+    }
+}
+
+/// Compares the bytes of `a` and `b` without short-circuiting on the first
+/// difference, only their lengths leak.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len()
+        && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// Presents the token of the cluster on every request to a remote.
+#[derive(Debug, Clone)]
+pub struct ClientAuth(Option<AsciiMetadataValue>);
+
+impl ClientAuth {
+    pub fn new(auth: Option<&AuthConfig>) -> Result<Self, StoreError> {
+        let authorization = match auth {
+            Some(auth) => Some(
+                format!("Bearer {}", auth.token).parse().map_err(|_| {
+                    log::error!("Cluster token is not valid gRPC metadata.");
+                    StoreError::ParseError
+                })?,
+            ),
+            None => None,
+        };
+
+        Ok(Self(authorization))
+    }
+}
+
+impl Interceptor for ClientAuth {
+    fn call(
+        &mut self,
+        mut request: Request<()>,
+    ) -> Result<Request<()>, Status> {
+        if let Some(authorization) = &self.0 {
+            request
+                .metadata_mut()
+                .insert(AUTHORIZATION, authorization.clone());
+        }
+
+        Ok(request)
+    }
+}
@@ -5,15 +5,20 @@ use std::marker::PhantomData;
 use actix::{Actor, Context, Handler, ResponseActFuture};
 use actix_interop::FutureInterop;
 use futures_util::TryFutureExt;
-use tonic::transport::{Channel, Endpoint};
+use tonic::codegen::InterceptedService;
+use tonic::transport::{Channel, ClientTlsConfig, Endpoint};
 use tonic::Request;
+use tracing::{info_span, Instrument};
 
+use crate::auth::{AuthConfig, ClientAuth};
+use crate::graph::GraphMode;
 use crate::mutations_log::{MutationsLogMutation, MutationsLogQuery};
 use crate::sync_graph::sync_graph_client::SyncGraphClient;
 use crate::sync_graph::{
     GraphMutationRequest, MutationsLogRequest, MutationsLogResponse,
     RemotesLogRequest,
 };
+use crate::telemetry;
 use crate::{
     GraphEdge, GraphNode, GraphNodeIndex, StoreError, SyncRemotesMessage,
 };
@@ -25,7 +30,11 @@ where
     E: GraphEdge + Unpin + 'static,
     I: GraphNodeIndex + From<N> + Unpin + 'static,
 {
-    client: SyncGraphClient<Channel>,
+    client: SyncGraphClient<InterceptedService<Channel, ClientAuth>>,
+    /// Cluster the remote has to belong to, empty without authentication.
+    cluster_id: String,
+    /// Mode the graph of the remote has to run with.
+    mode: GraphMode,
     phantom_n: PhantomData<N>,
     phantom_e: PhantomData<E>,
     phantom_i: PhantomData<I>,
@@ -46,11 +55,25 @@ where
     E: GraphEdge + Unpin + 'static,
     I: GraphNodeIndex + From<N> + Unpin + 'static,
 {
-    pub async fn new(address: String) -> Result<Self, StoreError> {
-        let endpoint = Endpoint::try_from(address)
+    pub async fn new(
+        address: String,
+        tls: Option<ClientTlsConfig>,
+        auth: Option<AuthConfig>,
+        mode: GraphMode,
+    ) -> Result<Self, StoreError> {
+        let interceptor = ClientAuth::new(auth.as_ref())?;
+        let mut endpoint = Endpoint::try_from(address)
             .map_err(|_err| StoreError::ParseError)?;
 
-        let client = SyncGraphClient::connect(endpoint.clone())
+        if let Some(tls) = tls {
+            endpoint = endpoint.tls_config(tls).map_err(|err| {
+                log::error!("Invalid client TLS configuration. Error: '{err}'");
+                StoreError::ClientError
+            })?;
+        }
+
+        let channel = endpoint
+            .connect()
             .map_err(|err| {
                 log::warn!(
                     "Could not connect client at {:?}. Error: '{}' source: '{:?}'",
@@ -61,9 +84,12 @@ where
                 StoreError::ClientError
             })
             .await?;
+        let client = SyncGraphClient::with_interceptor(channel, interceptor);
 
         Ok(Self {
             client,
+            cluster_id: auth.map(|auth| auth.cluster_id).unwrap_or_default(),
+            mode,
             phantom_n: PhantomData,
             phantom_e: PhantomData,
             phantom_i: PhantomData,
@@ -85,11 +111,18 @@ where
         _ctx: &mut Self::Context,
     ) -> Self::Result {
         let mut client = self.client.clone();
+        let span = info_span!(parent: &msg.span, "graph_mutation_request");
 
         async move {
             let request: GraphMutationRequest = msg.try_into()?;
+            let mut request = Request::new(request);
+            telemetry::inject(&span, &mut request);
 
-            if let Err(err) = client.graph_mutation(request).await {
+            if let Err(err) = client
+                .graph_mutation(request)
+                .instrument(span.clone())
+                .await
+            {
                 log::error!(
                     "Error while sending 'AddEdgeRequest' request. Error: {err}"
                 );
@@ -123,14 +156,43 @@ where
         let request = Request::new(RemotesLogRequest {
             from_server: from,
             remotes_log: flat_remotes_log,
+            cluster_id: self.cluster_id.clone(),
+            multigraph: self.mode.multigraph,
+            undirected: self.mode.undirected,
         });
 
         let mut client = self.client.clone();
+        let cluster_id = self.cluster_id.clone();
+        let mode = self.mode;
 
         async move {
             match client.sync_remotes(request).await {
                 Ok(response) => {
                     let response = response.into_inner();
+
+                    if response.cluster_id != cluster_id {
+                        log::error!(
+                            "Remote {:?} belongs to cluster {:?}.",
+                            response.from_server,
+                            response.cluster_id
+                        );
+                        return Err(StoreError::ClientError);
+                    }
+
+                    let remote_mode = GraphMode {
+                        multigraph: response.multigraph,
+                        undirected: response.undirected,
+                    };
+
+                    if remote_mode != mode {
+                        let msg = format!(
+                            "Remote {:?} runs in graph mode {:?}.",
+                            response.from_server, remote_mode
+                        );
+                        log::error!("{msg}");
+                        return Err(StoreError::GraphModeConflict(msg));
+                    }
+
                     Ok(response.remotes_log)
                 }
                 Err(err) => {
@@ -162,10 +224,15 @@ where
         _ctx: &mut Self::Context,
     ) -> Self::Result {
         let mut client = self.client.clone();
+        let span = info_span!("sync_mutations_log_request");
 
         async move {
+            let mut request = Request::new(MutationsLogRequest {});
+            telemetry::inject(&span, &mut request);
+
             match client
-                .sync_mutations_log(Request::new(MutationsLogRequest {}))
+                .sync_mutations_log(request)
+                .instrument(span.clone())
                 .await
             {
                 Ok(response) => {
@@ -1,22 +1,39 @@
+use std::collections::{HashSet, VecDeque};
 use std::net::SocketAddr;
+use std::ops::RangeBounds;
 use std::str::FromStr;
+use std::time::Instant;
 
 use actix::{Actor, Addr};
+use futures_util::{stream, Stream};
 
 use petgraph::stable_graph::{NodeIndex, StableGraph};
 use petgraph::{Directed, Direction};
+use tokio::sync::broadcast::{self, error::RecvError};
 use url::Url;
 
-use crate::graph::Graph;
-use crate::mutations_log::{InitializeMutationsLog, MutationsLog};
+use crate::admin::DatabaseStatus;
+use crate::algorithms;
+use crate::graph::{Graph, GraphMode, Schema};
+use crate::graphql::GraphqlServer;
+use crate::health;
+use crate::metrics::{self, MetricsServer};
+use crate::mutations_log::{
+    AppliedMutation, InitializeMutationsLog, MutationsLog, MutationsLogSince,
+    SubscriptionFilter,
+};
+use crate::query::{Bindings, Query};
 use crate::remotes::{InitializeRemotes, Remotes};
+use crate::rest::RestGateway;
 use crate::server::GraphServer;
+use crate::telemetry;
 use crate::{
-    DatabaseConfig, GraphEdge, GraphMutation, GraphNode, GraphNodeIndex,
-    GraphQuery, GraphResponse, StoreError,
+    DatabaseConfig, DeletionPolicy, EdgeCursor, GraphEdge, GraphMutation,
+    GraphNode, GraphNodeIndex, GraphQuery, GraphResponse, GraphSchema,
+    IndexKey, NodeRemoval, OrderedRange, Predicate, StoreError, Subgraph,
 };
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct GraphDatabase<N, E, I>
 where
     N: GraphNode + Unpin + 'static,
@@ -25,8 +42,17 @@ where
 {
     graph: Addr<Graph<N, E, I>>,
     mutations_log: Addr<MutationsLog<N, E, I>>,
+    remotes: Addr<Remotes<N, E, I>>,
+    events: broadcast::Sender<AppliedMutation<N, E, I>>,
+    started_at: Instant,
+    mode: GraphMode,
+    /// Url the database is served on, labeling its metrics.
+    server_url: String,
 }
 
+/// Applied mutations buffered per subscriber before it is lagging behind.
+const SUBSCRIPTION_CAPACITY: usize = 1024;
+
 impl<N, E, I> GraphDatabase<N, E, I>
 where
     N: GraphNode + Unpin + 'static,
@@ -37,14 +63,73 @@ where
     //
 
     pub async fn run(config: DatabaseConfig) -> Result<Self, StoreError> {
-        let graph = Graph::<N, E, I>::new(config.store_path.clone())?.start();
+        Self::start(config, None).await
+    }
+
+    /// Runs the database rejecting nodes and edges which do not satisfy
+    /// `schema` with [`StoreError::ConstraintViolation`].
+    pub async fn run_with_schema(
+        config: DatabaseConfig,
+        schema: impl GraphSchema<N, E> + 'static,
+    ) -> Result<Self, StoreError> {
+        Self::start(config, Some(Schema::new(schema))).await
+    }
+
+    async fn start(
+        config: DatabaseConfig,
+        schema: Option<Schema<N, E>>,
+    ) -> Result<Self, StoreError> {
+        let started_at = Instant::now();
+
+        if config.auth.is_some()
+            && (config.http_port.is_some() || config.graphql_port.is_some())
+        {
+            log::error!(
+                "The HTTP and GraphQL gateways are not authenticated, they \
+can't be served with authentication configured."
+            );
+            return Err(StoreError::ServerError(
+                "Unauthenticated gateways with authentication configured"
+                    .to_string(),
+            ));
+        }
+
+        if let Some(otlp_endpoint) = &config.otlp_endpoint {
+            telemetry::init(otlp_endpoint)?;
+        }
+
+        let mut graph = Graph::<N, E, I>::new(config.store_path.clone())?;
+        graph.set_multigraph(config.multigraph)?;
+        graph.set_undirected(config.undirected)?;
+        if let Some(schema) = schema {
+            graph.set_schema(schema);
+        }
+        let mode = graph.mode();
+        let graph = graph.start();
+
+        let (server_tls, client_tls) = match &config.tls {
+            Some(tls) => {
+                (Some(tls.server_config()?), Some(tls.client_config()?))
+            }
+            None => (None, None),
+        };
 
-        let remotes = Remotes::new(config.sync_with_remotes).start();
+        let remotes = Remotes::new(
+            config.sync_with_remotes,
+            client_tls,
+            config.auth.clone(),
+            mode,
+        )
+        .start();
+
+        let (events, _) = broadcast::channel(SUBSCRIPTION_CAPACITY);
 
         let mutations_log = MutationsLog::new(
             graph.clone(),
             remotes.clone(),
             config.store_path.clone(),
+            config.server_url.clone(),
+            events.clone(),
         )
         .await
         .unwrap()
@@ -75,27 +160,67 @@ Error: '{err}'"
             }
         };
 
-        if let Err(err) = GraphServer::run(
+        let health_reporter = GraphServer::run(
             server_address,
+            config.server_url.clone(),
+            graph.clone(),
             mutations_log.clone(),
             remotes.clone(),
-        ) {
-            log::error!("Error while starting GraphServer. Error: '{err:?}'");
-        };
+            started_at,
+            server_tls,
+            config.auth.clone(),
+        )
+        .await?;
 
         remotes
             .send(InitializeRemotes {
                 initial_addresses: config.initial_remote_addresses,
-                server_address: config.server_url,
+                server_address: config.server_url.clone(),
             })
             .await??;
 
         mutations_log.send(InitializeMutationsLog {}).await??;
 
-        Ok(Self {
+        // ready once the initial synchronization is done
+        health::spawn_monitor(
+            health_reporter,
+            graph.clone(),
+            mutations_log.clone(),
+            remotes.clone(),
+        );
+
+        let database = Self {
             graph,
             mutations_log,
-        })
+            remotes,
+            events,
+            started_at,
+            mode,
+            server_url: config.server_url,
+        };
+
+        if let Some(http_port) = config.http_port {
+            RestGateway::run(
+                SocketAddr::new(server_address.ip(), http_port),
+                database.clone(),
+            )?;
+        }
+
+        if let Some(graphql_port) = config.graphql_port {
+            GraphqlServer::run(
+                SocketAddr::new(server_address.ip(), graphql_port),
+                database.clone(),
+            )?;
+        }
+
+        if let Some(metrics_port) = config.metrics_port {
+            MetricsServer::run(SocketAddr::new(
+                server_address.ip(),
+                metrics_port,
+            ))?;
+        }
+
+        Ok(database)
     }
 
     //
@@ -112,11 +237,14 @@ Error: '{err}'"
     ) -> Result<(), StoreError> {
         let query = GraphMutation::AddEdge((from.into(), to.into(), edge));
 
-        self.mutations_log.send(query).await??;
+        self.send_mutation(query).await?;
 
         Ok(())
     }
 
+    /// Removes the edge from `from` to `to`. Fails with
+    /// [`StoreError::AmbiguousEdge`] if there are parallel edges, see
+    /// [`GraphDatabase::remove_edge_by_key`].
     pub async fn remove_edge(
         &self,
         from: impl Into<I>,
@@ -124,9 +252,7 @@ Error: '{err}'"
     ) -> Result<E, StoreError> {
         let query = GraphMutation::RemoveEdge((from.into(), to.into()));
 
-        if let GraphResponse::Edge(edge) =
-            self.mutations_log.send(query).await??
-        {
+        if let GraphResponse::Edge(edge) = self.send_mutation(query).await? {
             Ok(edge)
         } else {
             log::error!(
@@ -137,10 +263,35 @@ Expected mutations_log to return Edge."
         }
     }
 
+    /// Removes the edge from `from` to `to` with the [`GraphEdge::key`]
+    /// `key`, leaving parallel edges in place.
+    pub async fn remove_edge_by_key(
+        &self,
+        from: impl Into<I>,
+        to: impl Into<I>,
+        key: &str,
+    ) -> Result<E, StoreError> {
+        let query = GraphMutation::RemoveEdgeByKey((
+            from.into(),
+            to.into(),
+            key.to_string(),
+        ));
+
+        if let GraphResponse::Edge(edge) = self.send_mutation(query).await? {
+            Ok(edge)
+        } else {
+            log::error!(
+                "[GraphDatabase.remove_edge_by_key] \
+Expected mutations_log to return Edge."
+            );
+            Err(StoreError::EdgeNotDeleted)
+        }
+    }
+
     pub async fn add_node(&self, node: N) -> Result<N, StoreError> {
         let query = GraphMutation::AddNode(node);
 
-        let result = self.mutations_log.send(query).await??;
+        let result = self.send_mutation(query).await?;
 
         match result {
             GraphResponse::Node(node) => Ok(node),
@@ -155,20 +306,25 @@ Expected mutations_log to return Node got {:?}",
         }
     }
 
+    /// Removes the node with the key `node`, handling its edges according to
+    /// `policy`. Returns the node and, for [`DeletionPolicy::Cascade`], the
+    /// removed edges. The edges are logged, replicated and published to
+    /// subscribers as removals of their own ahead of the node removal.
     pub async fn remove_node(
         &self,
         node: impl Into<I>,
-    ) -> Result<N, StoreError> {
-        let query = GraphMutation::RemoveNode(node.into());
+        policy: DeletionPolicy,
+    ) -> Result<NodeRemoval<N, E, I>, StoreError> {
+        let query = GraphMutation::RemoveNodeWithPolicy((node.into(), policy));
 
-        if let GraphResponse::Node(node) =
-            self.mutations_log.send(query).await??
+        if let GraphResponse::RemovedNode(removed) =
+            self.send_mutation(query).await?
         {
-            Ok(node)
+            Ok(removed)
         } else {
             log::error!(
-                "[GraphDatabase.add_node] \
-Expected mutations_log to return Node."
+                "[GraphDatabase.remove_node] \
+Expected mutations_log to return RemovedNode."
             );
             Err(StoreError::NodeNotDeleted)
         }
@@ -183,7 +339,7 @@ Expected mutations_log to return Node."
     ) -> Result<StableGraph<N, E, Directed>, StoreError> {
         let query = GraphQuery::GetGraph;
 
-        if let GraphResponse::Graph(graph) = self.graph.send(query).await?? {
+        if let GraphResponse::Graph(graph) = self.send_query(query).await? {
             Ok(graph)
         } else {
             log::error!(
@@ -201,7 +357,7 @@ Expected mutations_log to return Graph."
     ) -> Result<StableGraph<N, E, Directed>, StoreError> {
         let query = GraphQuery::FilterGraph((include_nodes, include_edges));
 
-        if let GraphResponse::Graph(graph) = self.graph.send(query).await?? {
+        if let GraphResponse::Graph(graph) = self.send_query(query).await? {
             Ok(graph)
         } else {
             log::error!(
@@ -212,13 +368,51 @@ Expected mutations_log to return Graph."
         }
     }
 
+    pub async fn filter_graph_by(
+        &self,
+        node_predicate: impl Fn(&N) -> bool + Send + 'static,
+        edge_predicate: impl Fn(&E) -> bool + Send + 'static,
+    ) -> Result<StableGraph<N, E, Directed>, StoreError> {
+        let query = GraphQuery::FilterGraphBy((
+            Predicate::new(node_predicate),
+            Predicate::new(edge_predicate),
+        ));
+
+        if let GraphResponse::Graph(graph) = self.send_query(query).await? {
+            Ok(graph)
+        } else {
+            log::error!(
+                "[GraphDatabase.filter_graph_by] \
+Expected graph to return Graph."
+            );
+            Err(StoreError::GraphNotFound)
+        }
+    }
+
+    pub async fn filter_keys_by(
+        &self,
+        node_predicate: impl Fn(&N) -> bool + Send + 'static,
+    ) -> Result<Vec<I>, StoreError> {
+        let query = GraphQuery::FilterKeysBy(Predicate::new(node_predicate));
+
+        if let GraphResponse::Keys(keys) = self.send_query(query).await? {
+            Ok(keys)
+        } else {
+            log::error!(
+                "[GraphDatabase.filter_keys_by] \
+Expected graph to return Keys."
+            );
+            Err(StoreError::NodeNotFound)
+        }
+    }
+
     pub async fn retain_nodes(
         &self,
         nodes_to_retain: Vec<I>,
     ) -> Result<StableGraph<N, E, Directed>, StoreError> {
         let query = GraphQuery::RetainNodes(nodes_to_retain);
 
-        if let GraphResponse::Graph(graph) = self.graph.send(query).await?? {
+        if let GraphResponse::Graph(graph) = self.send_query(query).await? {
             Ok(graph)
         } else {
             Err(StoreError::GraphNotFound)
@@ -231,7 +425,7 @@ Expected mutations_log to return Graph."
     ) -> Result<Vec<N>, StoreError> {
         let query = GraphQuery::GetNeighborsUnd(key);
 
-        if let GraphResponse::Nodes(nodes) = self.graph.send(query).await?? {
+        if let GraphResponse::Nodes(nodes) = self.send_query(query).await? {
             Ok(nodes)
         } else {
             Err(StoreError::NodeNotFound)
@@ -245,37 +439,141 @@ Expected mutations_log to return Graph."
     ) -> Result<Vec<N>, StoreError> {
         let query = GraphQuery::GetNeighborsDir((key, dir));
 
-        if let GraphResponse::Nodes(nodes) = self.graph.send(query).await?? {
+        if let GraphResponse::Nodes(nodes) = self.send_query(query).await? {
             Ok(nodes)
         } else {
             Err(StoreError::NodeNotFound)
         }
     }
 
+    /// Neighbors of the node `key` connected by edges of type `edge_type`
+    /// in direction `dir`, looked up in the edge type index.
+    pub async fn get_neighbors_dir_by_type(
+        &self,
+        key: I,
+        dir: Direction,
+        edge_type: &str,
+    ) -> Result<Vec<N>, StoreError> {
+        let query = GraphQuery::GetNeighborsDirByType((
+            key,
+            dir,
+            edge_type.to_string(),
+        ));
+
+        if let GraphResponse::Nodes(nodes) = self.send_query(query).await? {
+            Ok(nodes)
+        } else {
+            Err(StoreError::NodeNotFound)
+        }
+    }
+
+    /// Edge from `from` to `to`. Fails with [`StoreError::AmbiguousEdge`] if
+    /// there are parallel edges.
     pub async fn get_edge(&self, from: I, to: I) -> Result<E, StoreError> {
         let query = GraphQuery::GetEdge((from, to));
 
-        if let GraphResponse::Edge(edge) = self.graph.send(query).await?? {
+        if let GraphResponse::Edge(edge) = self.send_query(query).await? {
+            Ok(edge)
+        } else {
+            Err(StoreError::EdgeNotFound)
+        }
+    }
+
+    pub async fn get_edge_by_key(
+        &self,
+        from: I,
+        to: I,
+        key: &str,
+    ) -> Result<E, StoreError> {
+        let query = GraphQuery::GetEdgeByKey((from, to, key.to_string()));
+
+        if let GraphResponse::Edge(edge) = self.send_query(query).await? {
             Ok(edge)
         } else {
             Err(StoreError::EdgeNotFound)
         }
     }
 
+    /// All parallel edges from `from` to `to`.
+    pub async fn get_edges_between(
+        &self,
+        from: I,
+        to: I,
+    ) -> Result<Vec<E>, StoreError> {
+        let query = GraphQuery::GetEdgesBetween((from, to));
+
+        if let GraphResponse::Edges(edges) = self.send_query(query).await? {
+            Ok(edges)
+        } else {
+            Err(StoreError::EdgeNotFound)
+        }
+    }
+
     pub async fn get_edges(&self) -> Result<Vec<E>, StoreError> {
         let query = GraphQuery::GetEdges;
 
-        if let GraphResponse::Edges(edges) = self.graph.send(query).await?? {
+        if let GraphResponse::Edges(edges) = self.send_query(query).await? {
             Ok(edges)
         } else {
             Err(StoreError::EdgeNotFound)
         }
     }
 
+    pub async fn get_edges_with_endpoints(
+        &self,
+    ) -> Result<Vec<(I, I, E)>, StoreError> {
+        let query = GraphQuery::GetEdgesWithEndpoints;
+
+        if let GraphResponse::Triples(triples) = self.send_query(query).await? {
+            Ok(triples)
+        } else {
+            Err(StoreError::EdgeNotFound)
+        }
+    }
+
+    /// Edges of the node `key` in the given direction together with the key
+    /// of the node at the other end.
+    pub async fn get_incident_edges(
+        &self,
+        key: I,
+        dir: Direction,
+    ) -> Result<Vec<(I, E)>, StoreError> {
+        let query = GraphQuery::GetIncidentEdges((key, dir));
+
+        if let GraphResponse::Triples(triples) = self.send_query(query).await? {
+            let edges = triples
+                .into_iter()
+                .map(|(from, to, edge)| match dir {
+                    Direction::Outgoing => (to, edge),
+                    Direction::Incoming => (from, edge),
+                })
+                .collect();
+
+            Ok(edges)
+        } else {
+            Err(StoreError::EdgeNotFound)
+        }
+    }
+
+    /// Edges of type `edge_type` with their endpoints, looked up in the edge
+    /// type index.
+    pub async fn get_edges_by_type(
+        &self,
+        edge_type: &str,
+    ) -> Result<Vec<(I, I, E)>, StoreError> {
+        let query = GraphQuery::GetEdgesByType(edge_type.to_string());
+
+        if let GraphResponse::Triples(triples) = self.send_query(query).await? {
+            Ok(triples)
+        } else {
+            Err(StoreError::EdgeNotFound)
+        }
+    }
+
     pub async fn has_node(&self, key: I) -> Result<bool, StoreError> {
         let query = GraphQuery::HasNode(key);
 
-        if let GraphResponse::Bool(has) = self.graph.send(query).await?? {
+        if let GraphResponse::Bool(has) = self.send_query(query).await? {
             Ok(has)
         } else {
             Err(StoreError::NodeNotFound)
@@ -285,7 +583,7 @@ Expected mutations_log to return Graph."
     pub async fn get_node(&self, key: I) -> Result<N, StoreError> {
         let query = GraphQuery::GetNode(key);
 
-        if let GraphResponse::Node(node) = self.graph.send(query).await?? {
+        if let GraphResponse::Node(node) = self.send_query(query).await? {
             Ok(node)
         } else {
             Err(StoreError::NodeNotFound)
@@ -295,7 +593,7 @@ Expected mutations_log to return Graph."
     pub async fn get_nodes(&self) -> Result<Vec<N>, StoreError> {
         let query = GraphQuery::GetNodes;
 
-        if let GraphResponse::Nodes(nodes) = self.graph.send(query).await?? {
+        if let GraphResponse::Nodes(nodes) = self.send_query(query).await? {
             Ok(nodes)
         } else {
             Err(StoreError::NodeNotFound)
@@ -309,7 +607,7 @@ Expected mutations_log to return Graph."
         let query = GraphQuery::GetNodeIndex(key);
 
         if let GraphResponse::NodeIndex(node_index) =
-            self.graph.send(query).await??
+            self.send_query(query).await?
         {
             Ok(node_index)
         } else {
@@ -317,10 +615,72 @@ Expected mutations_log to return Graph."
         }
     }
 
+    /// Keys of the nodes with `index_key` in the secondary index `name`.
+    pub async fn find_by_index(
+        &self,
+        name: &str,
+        index_key: impl Into<IndexKey>,
+    ) -> Result<Vec<I>, StoreError> {
+        let query =
+            GraphQuery::FindByIndex((name.to_string(), index_key.into()));
+
+        if let GraphResponse::Keys(keys) = self.send_query(query).await? {
+            Ok(keys)
+        } else {
+            Err(StoreError::NodeNotFound)
+        }
+    }
+
+    /// Keys of the nodes within `range` in the ordered secondary index
+    /// `name`, ordered by index key.
+    pub async fn range_by_index(
+        &self,
+        name: &str,
+        range: impl RangeBounds<IndexKey>,
+        descending: bool,
+        limit: Option<usize>,
+    ) -> Result<Vec<I>, StoreError> {
+        let range = OrderedRange {
+            start: range.start_bound().cloned(),
+            end: range.end_bound().cloned(),
+            descending,
+            limit,
+        };
+        let query = GraphQuery::RangeByIndex((name.to_string(), range));
+
+        if let GraphResponse::Keys(keys) = self.send_query(query).await? {
+            Ok(keys)
+        } else {
+            Err(StoreError::NodeNotFound)
+        }
+    }
+
+    /// Keys of the `limit` nodes with the greatest keys in the ordered
+    /// secondary index `name`.
+    pub async fn top_by_index(
+        &self,
+        name: &str,
+        limit: usize,
+    ) -> Result<Vec<I>, StoreError> {
+        self.range_by_index(name, .., true, Some(limit)).await
+    }
+
+    /// Keys of the nodes whose search text contains any word of `text`,
+    /// best matches first.
+    pub async fn search(&self, text: &str) -> Result<Vec<I>, StoreError> {
+        let query = GraphQuery::Search(text.to_string());
+
+        if let GraphResponse::Keys(keys) = self.send_query(query).await? {
+            Ok(keys)
+        } else {
+            Err(StoreError::NodeNotFound)
+        }
+    }
+
     pub async fn get_source_nodes(&self) -> Result<Vec<N>, StoreError> {
         let query = GraphQuery::GetSourceNodes;
 
-        if let GraphResponse::Nodes(nodes) = self.graph.send(query).await?? {
+        if let GraphResponse::Nodes(nodes) = self.send_query(query).await? {
             Ok(nodes)
         } else {
             Err(StoreError::NodeNotFound)
@@ -330,7 +690,345 @@ Expected mutations_log to return Graph."
     pub async fn get_sink_nodes(&self) -> Result<Vec<N>, StoreError> {
         let query = GraphQuery::GetSinkNodes;
 
-        if let GraphResponse::Nodes(nodes) = self.graph.send(query).await?? {
+        if let GraphResponse::Nodes(nodes) = self.send_query(query).await? {
+            Ok(nodes)
+        } else {
+            Err(StoreError::NodeNotFound)
+        }
+    }
+
+    /// Matches the pattern query `query`, see [`crate::query`] for the
+    /// syntax, returning the keys bound to its variables for every match.
+    pub async fn query(
+        &self,
+        query: &str,
+    ) -> Result<Vec<Bindings<I>>, StoreError> {
+        let query = GraphQuery::Match(Query::parse(query)?);
+
+        if let GraphResponse::Bindings(bindings) =
+            self.send_query(query).await?
+        {
+            Ok(bindings)
+        } else {
+            Err(StoreError::GraphNotFound)
+        }
+    }
+
+    //
+    // algorithms
+    //
+    // run on a snapshot of the graph on the blocking thread pool so they
+    // block neither the Graph actor nor the runtime
+    //
+
+    pub async fn page_rank(
+        &self,
+        damping_factor: f64,
+        max_iterations: usize,
+    ) -> Result<Vec<(I, f64)>, StoreError> {
+        self.run_algorithm(move |graph, undirected| {
+            algorithms::page_rank(
+                &graph,
+                damping_factor,
+                max_iterations,
+                undirected,
+            )
+        })
+        .await
+    }
+
+    pub async fn degree_centrality(&self) -> Result<Vec<(I, f64)>, StoreError> {
+        self.run_algorithm(|graph, _| algorithms::degree_centrality(&graph))
+            .await
+    }
+
+    pub async fn betweenness_centrality(
+        &self,
+    ) -> Result<Vec<(I, f64)>, StoreError> {
+        self.run_algorithm(|graph, undirected| {
+            algorithms::betweenness_centrality(&graph, undirected)
+        })
+        .await
+    }
+
+    pub async fn closeness_centrality(
+        &self,
+    ) -> Result<Vec<(I, f64)>, StoreError> {
+        self.run_algorithm(|graph, undirected| {
+            algorithms::closeness_centrality(&graph, undirected)
+        })
+        .await
+    }
+
+    /// Nodes within `depth` hops of the node `root`, ignoring the direction
+    /// of edges, and the edges between them.
+    pub async fn get_neighborhood(
+        &self,
+        root: I,
+        depth: usize,
+    ) -> Result<Subgraph<N, E, I>, StoreError> {
+        let query = GraphQuery::GetNeighborhood((root, depth));
+
+        if let GraphResponse::Subgraph(subgraph) =
+            self.send_query(query).await?
+        {
+            Ok(subgraph)
+        } else {
+            Err(StoreError::NodeNotFound)
+        }
+    }
+
+    //
+    // subscriptions
+    //
+
+    /// Stream of the mutations applied to the graph, local and replicated
+    /// ones, matching `filter`. With `filter.after` set, the mutations logged
+    /// after that cursor are replayed first. A subscriber lagging behind
+    /// receives a [`StoreError::SyncError`] and can resubscribe from the last
+    /// cursor it received.
+    pub async fn subscribe(
+        &self,
+        filter: SubscriptionFilter<I>,
+    ) -> Result<
+        impl Stream<Item = Result<AppliedMutation<N, E, I>, StoreError>>,
+        StoreError,
+    > {
+        // subscribe before reading the log to not miss mutations in between
+        let receiver = self.events.subscribe();
+
+        let replay: VecDeque<_> = match filter.after {
+            Some(cursor) => self
+                .mutations_log
+                .send(MutationsLogSince::new(cursor))
+                .await??
+                .into_iter()
+                .map(|(cursor, entry)| AppliedMutation::from_log(cursor, entry))
+                .collect(),
+            None => VecDeque::new(),
+        };
+
+        let replayed: HashSet<String> =
+            replay.iter().map(|event| event.hash.clone()).collect();
+
+        Ok(stream::unfold(
+            (replay, replayed, receiver, filter),
+            |(mut replay, replayed, mut receiver, filter)| async move {
+                loop {
+                    let event = match replay.pop_front() {
+                        Some(event) => event,
+                        None => match receiver.recv().await {
+                            Ok(event) if replayed.contains(&event.hash) => {
+                                continue
+                            }
+                            Ok(event) => event,
+                            Err(RecvError::Lagged(skipped)) => {
+                                let err = StoreError::SyncError(format!(
+                                    "Subscriber lagged behind \
+by {skipped} mutations"
+                                ));
+                                return Some((
+                                    Err(err),
+                                    (replay, replayed, receiver, filter),
+                                ));
+                            }
+                            Err(RecvError::Closed) => return None,
+                        },
+                    };
+
+                    if filter.matches(&event.mutation) {
+                        return Some((
+                            Ok(event),
+                            (replay, replayed, receiver, filter),
+                        ));
+                    }
+                }
+            },
+        ))
+    }
+
+    //
+    // admin
+    //
+
+    /// Node and edge counts, state of the mutations log, known remotes,
+    /// pending mutations, saved snapshot and uptime of the database.
+    pub async fn status(&self) -> Result<DatabaseStatus, StoreError> {
+        DatabaseStatus::collect(
+            &self.graph,
+            &self.mutations_log,
+            &self.remotes,
+            self.started_at,
+        )
+        .await
+    }
+
+    /// Whether the actors are running and the graph snapshot and the
+    /// mutations log can be read, the same check reported by the gRPC
+    /// health service. The initial synchronization is already done once
+    /// [`GraphDatabase::run`] returns.
+    pub async fn is_ready(&self) -> bool {
+        health::check(&self.graph, &self.mutations_log, &self.remotes)
+            .await
+            .is_ok()
+    }
+
+    //
+    // private methods
+    //
+
+    /// Runs `algorithm` on a snapshot of the graph, passing whether the
+    /// graph is undirected.
+    async fn run_algorithm<T, F>(&self, algorithm: F) -> Result<T, StoreError>
+    where
+        T: Send + 'static,
+        F: FnOnce(StableGraph<N, E, Directed>, bool) -> T + Send + 'static,
+    {
+        let graph = self.get_graph().await?;
+        let undirected = self.mode.undirected;
+
+        tokio::task::spawn_blocking(move || algorithm(graph, undirected))
+            .await
+            .map_err(|err| {
+                log::error!("Error while running algorithm. Error: '{err}'");
+                StoreError::QueryError(err.to_string())
+            })
+    }
+
+    async fn send_query(
+        &self,
+        query: GraphQuery<N, E, I>,
+    ) -> Result<GraphResponse<N, E, I>, StoreError> {
+        metrics::send(&self.server_url, "graph", &self.graph, query).await?
+    }
+
+    async fn send_mutation(
+        &self,
+        mutation: GraphMutation<N, E, I>,
+    ) -> Result<GraphResponse<N, E, I>, StoreError> {
+        metrics::send(
+            &self.server_url,
+            "mutations_log",
+            &self.mutations_log,
+            mutation,
+        )
+        .await?
+    }
+}
+
+impl<N, E, I> GraphDatabase<N, E, I>
+where
+    N: GraphNode + Unpin + 'static,
+    E: GraphEdge + Unpin + 'static,
+    I: GraphNodeIndex + From<N> + Ord + Unpin + 'static,
+{
+    /// Up to `limit` nodes following the node `after` in key order, pass the
+    /// key of the last returned node as `after` to get the next page. The
+    /// node `after` doesn't have to exist anymore.
+    pub async fn get_nodes_page(
+        &self,
+        after: Option<I>,
+        limit: usize,
+    ) -> Result<Vec<N>, StoreError> {
+        Self::send_nodes_page(&self.graph, after, limit).await
+    }
+
+    /// Up to `limit` edges following the edge `after`, ordered by the keys of
+    /// their endpoints and their [`GraphEdge::key`]. Pass the cursor of the
+    /// last returned edge as `after` to get the next page. Edges are paged
+    /// once, with the direction they were added with, also in undirected
+    /// mode.
+    pub async fn get_edges_page(
+        &self,
+        after: Option<EdgeCursor<I>>,
+        limit: usize,
+    ) -> Result<Vec<(I, I, E)>, StoreError> {
+        let query = GraphQuery::GetEdgesPage((after, limit, I::cmp));
+
+        if let GraphResponse::Triples(triples) = self.send_query(query).await? {
+            Ok(triples)
+        } else {
+            Err(StoreError::EdgeNotFound)
+        }
+    }
+
+    /// Stream of all nodes, fetched from the Graph actor in pages of
+    /// `chunk_size` so other messages get handled in between.
+    pub fn stream_nodes(
+        &self,
+        chunk_size: usize,
+    ) -> impl Stream<Item = Result<N, StoreError>> {
+        let chunk_size = chunk_size.max(1);
+        let actor = (self.server_url.clone(), self.graph.clone());
+        let state = (actor, Some(None), VecDeque::new());
+
+        stream::unfold(
+            state,
+            move |(actor, mut cursor, mut buffer)| async move {
+                loop {
+                    if let Some(node) = buffer.pop_front() {
+                        return Some((Ok(node), (actor, cursor, buffer)));
+                    }
+
+                    let after = cursor?;
+                    let (server_url, graph) = &actor;
+
+                    match Self::send_nodes_page(
+                        server_url, graph, after, chunk_size,
+                    )
+                    .await
+                    {
+                        Ok(nodes) => {
+                            cursor = match nodes.last() {
+                                Some(last) if nodes.len() == chunk_size => {
+                                    Some(Some(last.clone().into()))
+                                }
+                                _ => None,
+                            };
+                            buffer.extend(nodes);
+                        }
+                        Err(err) => {
+                            return Some((Err(err), (actor, None, buffer)))
+                        }
+                    }
+                }
+            },
+        )
+    }
+
+    /// Keys of the nodes within `range` ordered by the keys themselves.
+    pub async fn get_keys_ordered(
+        &self,
+        range: impl RangeBounds<I>,
+        descending: bool,
+        limit: Option<usize>,
+    ) -> Result<Vec<I>, StoreError> {
+        let range = OrderedRange {
+            start: range.start_bound().cloned(),
+            end: range.end_bound().cloned(),
+            descending,
+            limit,
+        };
+        let query = GraphQuery::RangeByKey((range, I::cmp));
+
+        if let GraphResponse::Keys(keys) = self.send_query(query).await? {
+            Ok(keys)
+        } else {
+            Err(StoreError::NodeNotFound)
+        }
+    }
+
+    async fn send_nodes_page(
+        server_url: &str,
+        graph: &Addr<Graph<N, E, I>>,
+        after: Option<I>,
+        limit: usize,
+    ) -> Result<Vec<N>, StoreError> {
+        let query = GraphQuery::GetNodesPage((after, limit, I::cmp));
+
+        if let GraphResponse::Nodes(nodes) =
+            metrics::send(server_url, "graph", graph, query).await??
+        {
             Ok(nodes)
         } else {
             Err(StoreError::NodeNotFound)
@@ -1,22 +1,43 @@
-use std::collections::hash_map::Entry;
-use std::collections::HashMap;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fmt::Debug;
+use std::ops::Bound;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
 
-use actix::{Actor, Context, Handler};
+use actix::{Actor, AsyncContext, Context, Handler, Message, MessageResult};
 
-use petgraph::stable_graph::{NodeIndex, StableGraph};
-use petgraph::visit::{IntoEdgeReferences, IntoNodeReferences};
+use petgraph::stable_graph::{
+    EdgeIndex, EdgeReference, NodeIndex, StableGraph,
+};
+use petgraph::visit::{EdgeRef, IntoEdgeReferences, IntoNodeReferences};
 use petgraph::Direction::{Incoming, Outgoing};
 use petgraph::{Directed, Direction};
+use serde::{Deserialize, Serialize};
 
 use crate::graph_store::GraphStore;
+use crate::indexes::{
+    EdgeTypeIndex, KeyOrdering, OrderedRange, SecondaryIndexes,
+    UniqueConstraints,
+};
+use crate::metrics;
+use crate::mutations_log::MutationsLogMutation;
+use crate::search::SearchIndex;
 use crate::{
-    GraphEdge, GraphMutation, GraphNode, GraphNodeIndex, GraphQuery,
-    GraphResponse,
+    DeletionPolicy, EdgeCursor, GraphEdge, GraphMutation, GraphNode,
+    GraphNodeIndex, GraphQuery, GraphResponse, GraphSchema, NodeRemoval,
+    Predicate, Subgraph,
 };
 
 use super::StoreError;
 
+const GRAPH_META_FILE_NAME: &str = "meta.bin";
+const SEARCH_INDEX_FILE_NAME: &str = "search.bin";
+pub(crate) const UNIQUE_CONSTRAINTS_FILE_NAME: &str = "unique.bin";
+
+/// Interval in which the search index is saved if it changed.
+const SNAPSHOT_INTERVAL: Duration = Duration::from_secs(30);
+
 #[derive(Debug, Clone, Default)]
 pub struct Graph<N, E, I>
 where
@@ -26,9 +47,62 @@ where
 {
     inner: StableGraph<N, E, Directed>,
     nodes_map: HashMap<I, NodeIndex>,
+    indexes: SecondaryIndexes<I>,
+    search_index: SearchIndex<I>,
+    /// Whether the search index changed since it was last saved.
+    search_index_dirty: bool,
+    /// Hash of the last mutation applied to the graph.
+    last_hash: Option<String>,
+    unique_constraints: UniqueConstraints<I>,
+    /// Nodes whose unique key claims were loaded without their mutation
+    /// hash, restored from the mutations log on startup.
+    unknown_unique_hashes: HashSet<I>,
+    edge_types: EdgeTypeIndex,
+    mode: GraphMode,
+    schema: Option<Schema<N, E>>,
     store: GraphStore<N, E>,
 }
 
+/// State of the graph saved next to `data.bin`, written before the graph so
+/// that snapshots of derived data taken before a crash don't match it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct GraphMeta {
+    last_hash: Option<String>,
+    mode: GraphMode,
+}
+
+/// How the graph treats its edges. Replicas apply the same mutations only
+/// to the same outcome if they agree on it, which peers check on sync.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize,
+)]
+pub struct GraphMode {
+    /// Parallel edges with distinct [`GraphEdge::key`]s are allowed.
+    pub multigraph: bool,
+    /// Edges are looked up and followed in either direction.
+    pub undirected: bool,
+}
+
+/// Shared [`GraphSchema`] of the graph.
+#[derive(Clone)]
+pub struct Schema<N, E>(Arc<dyn GraphSchema<N, E>>);
+
+impl<N, E> Schema<N, E>
+where
+    N: GraphNode,
+    E: GraphEdge,
+{
+    pub fn new(schema: impl GraphSchema<N, E> + 'static) -> Self {
+        Self(Arc::new(schema))
+    }
+}
+
+impl<N, E> Debug for Schema<N, E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Schema")
+    }
+}
+
 impl<N, E, I> Actor for Graph<N, E, I>
 where
     N: GraphNode + Unpin + 'static,
@@ -36,6 +110,20 @@ where
     I: GraphNodeIndex + From<N> + Unpin + 'static,
 {
     type Context = Context<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        ctx.run_interval(SNAPSHOT_INTERVAL, |graph, _ctx| {
+            if let Err(err) = graph.snapshot() {
+                log::warn!("Error saving graph snapshot. Error: '{err:?}'");
+            }
+        });
+    }
+
+    fn stopped(&mut self, _ctx: &mut Self::Context) {
+        if let Err(err) = self.snapshot() {
+            log::warn!("Error saving graph snapshot. Error: '{err:?}'");
+        }
+    }
 }
 
 impl<N, E, I> Handler<GraphMutation<N, E, I>> for Graph<N, E, I>
@@ -51,27 +139,260 @@ where
         msg: GraphMutation<N, E, I>,
         _: &mut Self::Context,
     ) -> Self::Result {
-        match msg {
-            GraphMutation::AddEdge((from, to, edge)) => {
-                self.add_edge(from, to, edge)?;
-                Ok(GraphResponse::Empty)
-            }
-            GraphMutation::RemoveEdge((from, to)) => {
-                let edge = self.remove_edge(from, to)?;
-                Ok(GraphResponse::Edge(edge))
-            }
+        self.apply_mutation(msg, None)
+    }
+}
+
+/// Mutation committed to the mutations log under `hash`, which decides
+/// between nodes concurrently claiming the same unique key.
+pub struct ApplyMutation<N, E, I>(pub MutationsLogMutation<N, E, I>)
+where
+    N: GraphNode + 'static,
+    E: GraphEdge + 'static,
+    I: GraphNodeIndex + From<N> + 'static;
+
+impl<N, E, I> Message for ApplyMutation<N, E, I>
+where
+    N: GraphNode + 'static,
+    E: GraphEdge + 'static,
+    I: GraphNodeIndex + From<N> + 'static,
+{
+    type Result = Result<GraphResponse<N, E, I>, StoreError>;
+}
+
+impl<N, E, I> Handler<ApplyMutation<N, E, I>> for Graph<N, E, I>
+where
+    N: GraphNode + Unpin + 'static,
+    E: GraphEdge + Unpin + 'static,
+    I: GraphNodeIndex + From<N> + Unpin + 'static,
+{
+    type Result = Result<GraphResponse<N, E, I>, StoreError>;
+
+    fn handle(
+        &mut self,
+        msg: ApplyMutation<N, E, I>,
+        _: &mut Self::Context,
+    ) -> Self::Result {
+        let ApplyMutation(MutationsLogMutation { hash, mutation, .. }) = msg;
+        self.apply_mutation(mutation, Some(&hash))
+    }
+}
+
+/// Fails if a mutation can't succeed before anything is logged, see
+/// [`Graph::check_mutation`].
+pub struct CheckMutation<N, E, I>(pub GraphMutation<N, E, I>)
+where
+    N: GraphNode + 'static,
+    E: GraphEdge + 'static,
+    I: GraphNodeIndex + From<N> + 'static;
+
+impl<N, E, I> Message for CheckMutation<N, E, I>
+where
+    N: GraphNode + 'static,
+    E: GraphEdge + 'static,
+    I: GraphNodeIndex + From<N> + 'static,
+{
+    type Result = Result<(), StoreError>;
+}
+
+impl<N, E, I> Handler<CheckMutation<N, E, I>> for Graph<N, E, I>
+where
+    N: GraphNode + Unpin + 'static,
+    E: GraphEdge + Unpin + 'static,
+    I: GraphNodeIndex + From<N> + Unpin + 'static,
+{
+    type Result = Result<(), StoreError>;
+
+    fn handle(
+        &mut self,
+        msg: CheckMutation<N, E, I>,
+        _: &mut Self::Context,
+    ) -> Self::Result {
+        self.check_mutation(&msg.0)
+    }
+}
+
+/// Hash, mutation and response of a removal applied by
+/// [`Graph::remove_node_with_edges`].
+pub type AppliedRemoval<N, E, I> =
+    (String, GraphMutation<N, E, I>, GraphResponse<N, E, I>);
+
+/// Applies a node removal along with the removals of its edges, to log
+/// afterwards, see [`Graph::remove_node_with_edges`].
+pub struct RemoveNodeWithEdges<N, E, I>(pub GraphMutation<N, E, I>)
+where
+    N: GraphNode + 'static,
+    E: GraphEdge + 'static,
+    I: GraphNodeIndex + From<N> + 'static;
+
+impl<N, E, I> Message for RemoveNodeWithEdges<N, E, I>
+where
+    N: GraphNode + 'static,
+    E: GraphEdge + 'static,
+    I: GraphNodeIndex + From<N> + 'static,
+{
+    type Result = Result<Vec<AppliedRemoval<N, E, I>>, StoreError>;
+}
+
+impl<N, E, I> Handler<RemoveNodeWithEdges<N, E, I>> for Graph<N, E, I>
+where
+    N: GraphNode + Unpin + 'static,
+    E: GraphEdge + Unpin + 'static,
+    I: GraphNodeIndex + From<N> + Unpin + 'static,
+{
+    type Result = Result<Vec<AppliedRemoval<N, E, I>>, StoreError>;
+
+    fn handle(
+        &mut self,
+        msg: RemoveNodeWithEdges<N, E, I>,
+        _: &mut Self::Context,
+    ) -> Self::Result {
+        let (key, policy) =
+            msg.0.node_removal().ok_or(StoreError::NodeNotDeleted)?;
+        self.remove_node_with_edges(key, policy)
+    }
+}
+
+/// Removals of the nodes to evict before committing a mutation, see
+/// [`Graph::unique_key_evictions`].
+pub struct UniqueKeyEvictions<N, E, I>(pub MutationsLogMutation<N, E, I>)
+where
+    N: GraphNode + 'static,
+    E: GraphEdge + 'static,
+    I: GraphNodeIndex + From<N> + 'static;
+
+impl<N, E, I> Message for UniqueKeyEvictions<N, E, I>
+where
+    N: GraphNode + 'static,
+    E: GraphEdge + 'static,
+    I: GraphNodeIndex + From<N> + 'static,
+{
+    type Result = Vec<GraphMutation<N, E, I>>;
+}
+
+impl<N, E, I> Handler<UniqueKeyEvictions<N, E, I>> for Graph<N, E, I>
+where
+    N: GraphNode + Unpin + 'static,
+    E: GraphEdge + Unpin + 'static,
+    I: GraphNodeIndex + From<N> + Unpin + 'static,
+{
+    type Result = MessageResult<UniqueKeyEvictions<N, E, I>>;
+
+    fn handle(
+        &mut self,
+        msg: UniqueKeyEvictions<N, E, I>,
+        _: &mut Self::Context,
+    ) -> Self::Result {
+        let UniqueKeyEvictions(MutationsLogMutation { hash, mutation, .. }) =
+            msg;
+
+        match mutation {
             GraphMutation::AddNode(node) => {
-                let node = self.add_node(node)?;
-                Ok(GraphResponse::Node(node))
-            }
-            GraphMutation::RemoveNode(key) => {
-                let node = self.remove_node(key)?;
-                Ok(GraphResponse::Node(node))
+                MessageResult(self.unique_key_evictions(&node, &hash))
             }
+            _ => MessageResult(Vec::new()),
         }
     }
 }
 
+/// Whether unique key claims were loaded without their mutation hashes.
+pub struct HasUnknownUniqueKeyHashes;
+
+impl Message for HasUnknownUniqueKeyHashes {
+    type Result = bool;
+}
+
+impl<N, E, I> Handler<HasUnknownUniqueKeyHashes> for Graph<N, E, I>
+where
+    N: GraphNode + Unpin + 'static,
+    E: GraphEdge + Unpin + 'static,
+    I: GraphNodeIndex + From<N> + Unpin + 'static,
+{
+    type Result = bool;
+
+    fn handle(
+        &mut self,
+        _msg: HasUnknownUniqueKeyHashes,
+        _: &mut Self::Context,
+    ) -> Self::Result {
+        !self.unknown_unique_hashes.is_empty()
+    }
+}
+
+/// Local mutations log to restore unknown unique key hashes from, see
+/// [`Graph::restore_unique_key_hashes`].
+pub struct RestoreUniqueKeyHashes<N, E, I>(
+    pub Vec<MutationsLogMutation<N, E, I>>,
+)
+where
+    N: GraphNode + 'static,
+    E: GraphEdge + 'static,
+    I: GraphNodeIndex + From<N> + 'static;
+
+impl<N, E, I> Message for RestoreUniqueKeyHashes<N, E, I>
+where
+    N: GraphNode + 'static,
+    E: GraphEdge + 'static,
+    I: GraphNodeIndex + From<N> + 'static,
+{
+    type Result = Result<(), StoreError>;
+}
+
+impl<N, E, I> Handler<RestoreUniqueKeyHashes<N, E, I>> for Graph<N, E, I>
+where
+    N: GraphNode + Unpin + 'static,
+    E: GraphEdge + Unpin + 'static,
+    I: GraphNodeIndex + From<N> + Unpin + 'static,
+{
+    type Result = Result<(), StoreError>;
+
+    fn handle(
+        &mut self,
+        msg: RestoreUniqueKeyHashes<N, E, I>,
+        _: &mut Self::Context,
+    ) -> Self::Result {
+        self.restore_unique_key_hashes(msg.0)
+    }
+}
+
+/// Node and edge counts of the graph and metadata of its saved snapshot.
+#[derive(Debug, Clone)]
+pub struct GraphStats {
+    pub node_count: usize,
+    pub edge_count: usize,
+    pub mode: GraphMode,
+    /// Size in bytes and modification time of `data.bin`.
+    pub snapshot: Option<(u64, SystemTime)>,
+}
+
+pub struct GraphStatsQuery;
+
+impl Message for GraphStatsQuery {
+    type Result = GraphStats;
+}
+
+impl<N, E, I> Handler<GraphStatsQuery> for Graph<N, E, I>
+where
+    N: GraphNode + Unpin + 'static,
+    E: GraphEdge + Unpin + 'static,
+    I: GraphNodeIndex + From<N> + Unpin + 'static,
+{
+    type Result = MessageResult<GraphStatsQuery>;
+
+    fn handle(
+        &mut self,
+        _msg: GraphStatsQuery,
+        _: &mut Self::Context,
+    ) -> Self::Result {
+        MessageResult(GraphStats {
+            node_count: self.inner.node_count(),
+            edge_count: self.inner.edge_count(),
+            mode: self.mode,
+            snapshot: self.store.snapshot_metadata(),
+        })
+    }
+}
+
 impl<N, E, I> Handler<GraphQuery<N, E, I>> for Graph<N, E, I>
 where
     N: GraphNode + Unpin + 'static,
@@ -85,6 +406,10 @@ where
         msg: GraphQuery<N, E, I>,
         _: &mut Self::Context,
     ) -> Self::Result {
+        let _timer = metrics::QUERY_DURATION
+            .with_label_values(&[msg.name()])
+            .start_timer();
+
         match msg {
             GraphQuery::GetGraph => {
                 let graph = self.get_graph()?;
@@ -94,6 +419,15 @@ where
                 let graph = self.filter_graph(include_nodes, include_edges)?;
                 Ok(GraphResponse::Graph(graph))
             }
+            GraphQuery::FilterGraphBy((node_predicate, edge_predicate)) => {
+                let graph =
+                    self.filter_graph_by(node_predicate, edge_predicate)?;
+                Ok(GraphResponse::Graph(graph))
+            }
+            GraphQuery::FilterKeysBy(node_predicate) => {
+                let keys = self.filter_keys_by(node_predicate)?;
+                Ok(GraphResponse::Keys(keys))
+            }
             GraphQuery::RetainNodes(nodes_to_retain) => {
                 let graph = self.retain_nodes(nodes_to_retain)?;
                 Ok(GraphResponse::Graph(graph))
@@ -106,14 +440,43 @@ where
                 let nodes = self.get_neighbors_dir(&key, dir)?;
                 Ok(GraphResponse::Nodes(nodes))
             }
+            GraphQuery::GetNeighborsDirByType((key, dir, edge_type)) => {
+                let nodes =
+                    self.get_neighbors_dir_by_type(&key, dir, &edge_type)?;
+                Ok(GraphResponse::Nodes(nodes))
+            }
+            GraphQuery::GetNeighborhood((root, depth)) => {
+                let subgraph = self.get_neighborhood(&root, depth)?;
+                Ok(GraphResponse::Subgraph(subgraph))
+            }
             GraphQuery::GetEdge((from, to)) => {
                 let edge = self.get_edge(&from, &to)?;
                 Ok(GraphResponse::Edge(edge))
             }
+            GraphQuery::GetEdgeByKey((from, to, key)) => {
+                let edge = self.get_edge_by_key(&from, &to, &key)?;
+                Ok(GraphResponse::Edge(edge))
+            }
+            GraphQuery::GetEdgesBetween((from, to)) => {
+                let edges = self.get_edges_between(&from, &to)?;
+                Ok(GraphResponse::Edges(edges))
+            }
             GraphQuery::GetEdges => {
                 let edges = self.get_edges()?;
                 Ok(GraphResponse::Edges(edges))
             }
+            GraphQuery::GetEdgesWithEndpoints => {
+                let triples = self.get_edges_with_endpoints()?;
+                Ok(GraphResponse::Triples(triples))
+            }
+            GraphQuery::GetIncidentEdges((key, dir)) => {
+                let triples = self.get_incident_edges(&key, dir)?;
+                Ok(GraphResponse::Triples(triples))
+            }
+            GraphQuery::GetEdgesByType(edge_type) => {
+                let triples = self.get_edges_by_type(&edge_type)?;
+                Ok(GraphResponse::Triples(triples))
+            }
             GraphQuery::HasNode(key) => {
                 let has = self.has_node(&key)?;
                 Ok(GraphResponse::Bool(has))
@@ -126,10 +489,34 @@ where
                 let nodes = self.get_nodes()?;
                 Ok(GraphResponse::Nodes(nodes))
             }
+            GraphQuery::GetNodesPage((after, limit, cmp)) => {
+                let nodes = self.get_nodes_page(after, limit, cmp)?;
+                Ok(GraphResponse::Nodes(nodes))
+            }
+            GraphQuery::GetEdgesPage((after, limit, cmp)) => {
+                let triples = self.get_edges_page(after, limit, cmp)?;
+                Ok(GraphResponse::Triples(triples))
+            }
             GraphQuery::GetNodeIndex(key) => {
                 let node_index = self.get_node_index(&key)?;
                 Ok(GraphResponse::NodeIndex(node_index))
             }
+            GraphQuery::FindByIndex((name, index_key)) => {
+                let keys = self.indexes.find(&name, &index_key);
+                Ok(GraphResponse::Keys(keys))
+            }
+            GraphQuery::RangeByIndex((name, range)) => {
+                let keys = self.indexes.find_range(&name, range);
+                Ok(GraphResponse::Keys(keys))
+            }
+            GraphQuery::RangeByKey((range, cmp)) => {
+                let keys = self.get_keys_ordered(range, cmp)?;
+                Ok(GraphResponse::Keys(keys))
+            }
+            GraphQuery::Search(text) => {
+                let keys = self.search_index.search(&text);
+                Ok(GraphResponse::Keys(keys))
+            }
             GraphQuery::GetSourceNodes => {
                 let nodes = self.get_source_nodes()?;
                 Ok(GraphResponse::Nodes(nodes))
@@ -138,6 +525,10 @@ where
                 let nodes = self.get_sink_nodes()?;
                 Ok(GraphResponse::Nodes(nodes))
             }
+            GraphQuery::Match(query) => {
+                let bindings = query.execute(&self.inner, self.mode.undirected);
+                Ok(GraphResponse::Bindings(bindings))
+            }
         }
     }
 }
@@ -154,89 +545,517 @@ where
     pub fn new(store_path: Option<String>) -> Result<Self, StoreError> {
         let store = GraphStore::new(store_path)?;
         let inner = store.load_from_file()?;
-        let nodes_map = Self::get_nodes_map_from_graph(&inner);
+        let (nodes_map, indexes) = Self::get_nodes_map_from_graph(&inner);
+        let meta = store
+            .load_derived_from_file::<GraphMeta>(GRAPH_META_FILE_NAME)
+            .unwrap_or_else(|err| {
+                log::warn!("Error loading graph metadata. Error: '{err:?}'");
+                None
+            })
+            .unwrap_or_default();
+        let search_index =
+            Self::load_search_index(&store, &inner, &meta.last_hash)?;
+        let (unique_constraints, unknown_unique_hashes) =
+            Self::load_unique_constraints(&store, &inner)?;
+        let edge_types = Self::get_edge_types_from_graph(&inner);
 
         log::info!("Initialized graph");
 
         Ok(Self {
             inner,
             nodes_map,
+            indexes,
+            search_index,
+            search_index_dirty: false,
+            last_hash: meta.last_hash,
+            unique_constraints,
+            unknown_unique_hashes,
+            edge_types,
+            mode: meta.mode,
+            schema: None,
             store,
         })
     }
 
+    /// Allows parallel edges between the same nodes with distinct
+    /// [`GraphEdge::key`]s. The mode is saved with the graph and can't be
+    /// turned off while it holds parallel edges.
+    pub fn set_multigraph(
+        &mut self,
+        multigraph: bool,
+    ) -> Result<(), StoreError> {
+        if !multigraph && self.has_indistinct_edges(false, self.mode.undirected)
+        {
+            return Err(StoreError::GraphModeConflict(
+                "the graph holds parallel edges".to_string(),
+            ));
+        }
+
+        self.mode.multigraph = multigraph;
+        self.save_meta()
+    }
+
+    /// Mode the graph runs with, as saved with it.
+    pub fn mode(&self) -> GraphMode {
+        self.mode
+    }
+
+    /// Makes edge lookups, neighbor queries, pattern matching and the graph
+    /// algorithms ignore edge directions. The mode is saved with the graph
+    /// and can't be turned on while it holds edges in opposite directions
+    /// which lookups couldn't tell apart.
+    pub fn set_undirected(
+        &mut self,
+        undirected: bool,
+    ) -> Result<(), StoreError> {
+        if undirected && self.has_indistinct_edges(self.mode.multigraph, true) {
+            return Err(StoreError::GraphModeConflict(
+                "the graph holds edges in opposite directions".to_string(),
+            ));
+        }
+
+        self.mode.undirected = undirected;
+        self.save_meta()
+    }
+
+    /// Validates nodes and edges against `schema` before adding them.
+    pub fn set_schema(&mut self, schema: Schema<N, E>) {
+        self.schema = Some(schema);
+    }
+
     //
     // public interface
     //
 
-    fn add_edge(&mut self, from: I, to: I, edge: E) -> Result<(), StoreError> {
-        if let Ok(_) = self.get_edge(&from, &to) {
-            return Err(StoreError::ConflictDuplicateEdge);
-        };
-
-        let from_idx = self.get_node_index(&from)?;
-        let to_idx = self.get_node_index(&to)?;
-
-        let mut graph = self.inner.clone();
+    /// Saves the derived data which isn't saved on every mutation.
+    pub fn snapshot(&mut self) -> Result<(), StoreError> {
+        if self.search_index_dirty {
+            self.store.save_derived_to_file(
+                SEARCH_INDEX_FILE_NAME,
+                &(&self.last_hash, &self.search_index),
+            )?;
+            self.search_index_dirty = false;
+        }
 
-        graph.add_edge(from_idx, to_idx, edge);
+        Ok(())
+    }
 
-        self.inner = self.store.save_to_file(graph)?;
+    /// Fails before `mutation` is logged if it can't succeed on any
+    /// replica, i.e. an added node claims unique keys claimed locally.
+    pub fn check_mutation(
+        &self,
+        mutation: &GraphMutation<N, E, I>,
+    ) -> Result<(), StoreError> {
+        // keys claimed locally are claimed under an earlier mutation
+        if let GraphMutation::AddNode(node) = mutation {
+            let key: I = node.clone().into();
+
+            if let Some((name, _, _)) =
+                self.unique_constraints.get_owners(&key, node).pop()
+            {
+                return Err(StoreError::ConflictUniqueKey(name));
+            }
+        }
 
         Ok(())
     }
 
-    fn remove_edge(&mut self, from: I, to: I) -> Result<E, StoreError> {
-        if let (Some(from_idx), Some(to_idx)) =
-            (self.nodes_map.get(&from), self.nodes_map.get(&to))
+    /// Removes the node `key` as the removals of its edges followed by the
+    /// removal of the node, to log in that order so that every replica
+    /// removes the same edges and subscribers learn about each of them.
+    /// [`DeletionPolicy::Restrict`] is checked and all removals are applied
+    /// within the same message, so no edge can be added to the node in
+    /// between. Removals applied before one fails are still returned to be
+    /// logged.
+    pub fn remove_node_with_edges(
+        &mut self,
+        key: I,
+        policy: DeletionPolicy,
+    ) -> Result<Vec<AppliedRemoval<N, E, I>>, StoreError> {
+        let node_index = self.get_node_index(&key)?;
+
+        if policy == DeletionPolicy::Restrict
+            && !self.get_incident_edge_refs(node_index).is_empty()
         {
-            let mut graph = self.inner.clone();
+            return Err(StoreError::NodeHasEdges);
+        }
 
-            let edge_index = self
-                .inner
-                .find_edge(*from_idx, *to_idx)
-                .ok_or(StoreError::EdgeNotFound)?;
+        let mut applied = Vec::new();
 
-            let edge = graph
-                .remove_edge(edge_index)
-                .ok_or(StoreError::EdgeNotFound)?;
+        for mutation in self.get_node_removals(&[key]) {
+            let hash = mutation.get_hash();
 
-            self.inner = self.store.save_to_file(graph)?;
+            match self.apply_mutation(mutation.clone(), Some(&hash)) {
+                Ok(response) => applied.push((hash, mutation, response)),
+                Err(err) => {
+                    log::error!(
+                        "Error while removing node. Error: '{err:?}' \
+mutation: '{mutation:?}'"
+                    );
+                    break;
+                }
+            }
+        }
+
+        Ok(applied)
+    }
+
+    /// Mutations removing the nodes `keys` after explicitly removing their
+    /// edges.
+    fn get_node_removals(&self, keys: &[I]) -> Vec<GraphMutation<N, E, I>> {
+        let mut edge_indices = HashSet::new();
+        let incident_edges: Vec<_> = keys
+            .iter()
+            .filter_map(|key| self.nodes_map.get(key))
+            .flat_map(|node_index| self.get_incident_edge_refs(*node_index))
+            .filter(|e| edge_indices.insert(e.id()))
+            .collect();
+
+        // keyed edges are removed first, a key-less edge is only found by
+        // its endpoints once its keyed parallel edges are gone
+        let mut removals: Vec<(bool, GraphMutation<N, E, I>)> = incident_edges
+            .iter()
+            .filter_map(|e| {
+                let (from, to, edge) =
+                    self.get_triple(e.source(), e.target(), e.weight())?;
+
+                Some(match edge.key() {
+                    Some(key) => {
+                        (false, GraphMutation::RemoveEdgeByKey((from, to, key)))
+                    }
+                    None => (true, GraphMutation::RemoveEdge((from, to))),
+                })
+            })
+            .collect();
+        removals.sort_by_key(|(keyless, _)| *keyless);
+
+        let mut mutations: Vec<_> =
+            removals.into_iter().map(|(_, removal)| removal).collect();
+        mutations.extend(keys.iter().cloned().map(GraphMutation::RemoveNode));
+
+        mutations
+    }
+
+    /// Mutations evicting the nodes which claim unique keys of `node` under a
+    /// greater mutation hash than `hash`, to commit ahead of adding `node`.
+    /// Replicas thereby converge on the node of the earliest mutation for
+    /// concurrently claimed keys and log its evictions. Empty if `node`
+    /// loses to any of the current owners, so that adding it fails with
+    /// [`StoreError::ConflictUniqueKey`].
+    pub fn unique_key_evictions(
+        &self,
+        node: &N,
+        hash: &str,
+    ) -> Vec<GraphMutation<N, E, I>> {
+        let key: I = node.clone().into();
+        if self.nodes_map.contains_key(&key) {
+            return Vec::new();
+        }
+
+        let owners = self.unique_constraints.get_owners(&key, node);
+        if owners
+            .iter()
+            .any(|(_, _, owner_hash)| owner_hash.as_str() <= hash)
+        {
+            return Vec::new();
+        }
+
+        // ordered by the hashes of their claims for the replicas to log the
+        // evictions alike
+        let mut owners: Vec<(I, String)> = owners
+            .into_iter()
+            .filter(|(_, owner, _)| self.nodes_map.contains_key(owner))
+            .map(|(_, owner, owner_hash)| (owner, owner_hash))
+            .collect();
+        owners.sort_by(|(_, hash1), (_, hash2)| hash1.cmp(hash2));
+
+        let mut seen = HashSet::new();
+        let evicted: Vec<I> = owners
+            .into_iter()
+            .map(|(owner, _)| owner)
+            .filter(|owner| seen.insert(owner.clone()))
+            .collect();
+
+        for owner in evicted.iter() {
+            log::warn!(
+                "Evicting node {owner:?} in favor of {key:?} \
+claiming the same unique key"
+            );
+        }
+
+        self.get_node_removals(&evicted)
+    }
+
+    /// Restores the mutation hashes of the unique key claims loaded without
+    /// them from the local mutations log `log`: the hash of the first
+    /// addition of the node since its key was last removed.
+    pub fn restore_unique_key_hashes(
+        &mut self,
+        log: Vec<MutationsLogMutation<N, E, I>>,
+    ) -> Result<(), StoreError> {
+        let mut hashes: HashMap<I, String> = HashMap::new();
+
+        for entry in log {
+            match entry.mutation {
+                GraphMutation::AddNode(node) => {
+                    let key: I = node.clone().into();
+
+                    if self.unknown_unique_hashes.contains(&key) {
+                        hashes.entry(key).or_insert(entry.hash);
+                    }
+                }
+                GraphMutation::RemoveNode(key)
+                | GraphMutation::RemoveNodeWithPolicy((key, _)) => {
+                    hashes.remove(&key);
+                }
+                _ => {}
+            }
+        }
+
+        for (key, hash) in hashes {
+            let node = self.get_node(&key)?;
+            self.unique_constraints.insert(&key, &node, &hash);
+            self.unknown_unique_hashes.remove(&key);
+        }
+
+        if !self.unknown_unique_hashes.is_empty() {
+            log::warn!(
+                "Mutation hashes of the unique keys of {:?} not found in the \
+mutations log",
+                self.unknown_unique_hashes
+            );
+        }
+
+        self.save_unique_constraints()
+    }
+
+    fn apply_mutation(
+        &mut self,
+        msg: GraphMutation<N, E, I>,
+        hash: Option<&str>,
+    ) -> Result<GraphResponse<N, E, I>, StoreError> {
+        let hash = match hash {
+            Some(hash) => hash.to_string(),
+            None => msg.get_hash(),
+        };
+
+        let previous_hash = self.last_hash.replace(hash.clone());
+        self.save_meta()?;
+
+        let response = self.apply_mutation_with_hash(msg, &hash);
+
+        if response.is_err() {
+            self.last_hash = previous_hash;
+            self.save_meta()?;
+        }
+
+        response
+    }
+
+    fn apply_mutation_with_hash(
+        &mut self,
+        msg: GraphMutation<N, E, I>,
+        hash: &str,
+    ) -> Result<GraphResponse<N, E, I>, StoreError> {
+        match msg {
+            GraphMutation::AddEdge((from, to, edge)) => {
+                self.add_edge(from, to, edge)?;
+                Ok(GraphResponse::Empty)
+            }
+            GraphMutation::RemoveEdge((from, to)) => {
+                let edge = self.remove_edge(from, to)?;
+                Ok(GraphResponse::Edge(edge))
+            }
+            GraphMutation::AddNode(node) => {
+                let node = self.add_node_with_hash(node, hash)?;
+                Ok(GraphResponse::Node(node))
+            }
+            GraphMutation::RemoveNode(key) => {
+                let (node, _) =
+                    self.remove_node(key, DeletionPolicy::Detach)?;
+                Ok(GraphResponse::Node(node))
+            }
+            GraphMutation::RemoveEdgeByKey((from, to, key)) => {
+                let edge = self.remove_edge_by_key(from, to, key)?;
+                Ok(GraphResponse::Edge(edge))
+            }
+            GraphMutation::RemoveNodeWithPolicy((key, policy)) => {
+                let removed = self.remove_node(key, policy)?;
+                Ok(GraphResponse::RemovedNode(removed))
+            }
+        }
+    }
 
-            Ok(edge)
+    fn add_edge(&mut self, from: I, to: I, edge: E) -> Result<(), StoreError> {
+        let key = if self.mode.multigraph {
+            edge.key()
         } else {
-            Err(StoreError::EdgeNotFound)
+            None
+        };
+
+        match self.get_edge_index(&from, &to, key.as_deref()) {
+            Err(StoreError::EdgeNotFound) => {}
+            _ => return Err(StoreError::ConflictDuplicateEdge),
+        };
+
+        let from_idx = self.get_node_index(&from)?;
+        let to_idx = self.get_node_index(&to)?;
+
+        if let Some(Schema(schema)) = &self.schema {
+            if let (Some(from_node), Some(to_node)) = (
+                self.inner.node_weight(from_idx),
+                self.inner.node_weight(to_idx),
+            ) {
+                schema
+                    .validate_edge(from_node, to_node, &edge)
+                    .map_err(StoreError::ConstraintViolation)?;
+            }
+        }
+
+        let mut graph = self.inner.clone();
+
+        let edge_type = edge.edge_type();
+        let edge_index = graph.add_edge(from_idx, to_idx, edge);
+
+        self.inner = self.store.save_to_file(graph)?;
+
+        if let Some(edge_type) = edge_type {
+            self.edge_types
+                .insert(&edge_type, edge_index, from_idx, to_idx);
         }
+
+        Ok(())
+    }
+
+    fn remove_edge(&mut self, from: I, to: I) -> Result<E, StoreError> {
+        let edge_index = self.get_edge_index(&from, &to, None)?;
+
+        self.remove_edge_at(edge_index)
+    }
+
+    fn remove_edge_by_key(
+        &mut self,
+        from: I,
+        to: I,
+        key: String,
+    ) -> Result<E, StoreError> {
+        let edge_index = self.get_edge_index(&from, &to, Some(&key))?;
+
+        self.remove_edge_at(edge_index)
     }
 
     fn add_node(&mut self, node: N) -> Result<N, StoreError> {
-        match self.nodes_map.entry(node.clone().into()) {
-            Entry::Vacant(vacant_entry) => {
-                let mut graph = self.inner.clone();
+        let hash = GraphMutation::<N, E, I>::AddNode(node.clone()).get_hash();
+
+        self.add_node_with_hash(node, &hash)
+    }
 
-                let new_node_index = graph.add_node(node.clone());
+    /// Adds the node committed under `hash`. Nodes holding one of its unique
+    /// keys are evicted if their mutation hash is greater, so that replicas
+    /// converge on the earliest node for concurrently claimed keys.
+    fn add_node_with_hash(
+        &mut self,
+        node: N,
+        hash: &str,
+    ) -> Result<N, StoreError> {
+        if let Some(Schema(schema)) = &self.schema {
+            schema
+                .validate_node(&node)
+                .map_err(StoreError::ConstraintViolation)?;
+        }
 
-                self.inner = self.store.save_to_file(graph)?;
-                vacant_entry.insert(new_node_index);
+        let key: I = node.clone().into();
 
-                Ok(node)
-            }
-            Entry::Occupied(_) => Err(StoreError::ConflictDuplicateNode),
+        if self.nodes_map.contains_key(&key) {
+            return Err(StoreError::ConflictDuplicateNode);
+        }
+
+        // owners claiming under a greater hash are evicted by mutations
+        // committed ahead of this one, see `unique_key_evictions`
+        if let Some((name, _, _)) =
+            self.unique_constraints.get_owners(&key, &node).pop()
+        {
+            return Err(StoreError::ConflictUniqueKey(name));
+        }
+
+        let mut graph = self.inner.clone();
+
+        let new_node_index = graph.add_node(node.clone());
+
+        self.inner = self.store.save_to_file(graph)?;
+        self.nodes_map.insert(key.clone(), new_node_index);
+        self.indexes.insert(&key, &node);
+
+        if !node.unique_keys().is_empty() {
+            self.unique_constraints.insert(&key, &node, hash);
+            self.save_unique_constraints()?;
+        }
+
+        if let Some(text) = node.search_text() {
+            self.search_index.insert(&key, &text);
+            self.search_index_dirty = true;
         }
+
+        Ok(node)
     }
 
-    fn remove_node(&mut self, key: I) -> Result<N, StoreError> {
-        if let Entry::Occupied(map_entry) = self.nodes_map.entry(key) {
+    fn remove_node(
+        &mut self,
+        key: I,
+        policy: DeletionPolicy,
+    ) -> Result<NodeRemoval<N, E, I>, StoreError> {
+        if let Some(node_index) = self.nodes_map.get(&key).copied() {
+            let incident_edges = self.get_incident_edge_refs(node_index);
+
+            if policy == DeletionPolicy::Restrict && !incident_edges.is_empty()
+            {
+                return Err(StoreError::NodeHasEdges);
+            }
+
+            let removed_edges = match policy {
+                DeletionPolicy::Cascade => incident_edges
+                    .iter()
+                    .filter_map(|e| {
+                        self.get_triple(e.source(), e.target(), e.weight())
+                    })
+                    .collect(),
+                _ => Vec::new(),
+            };
+
+            let removed_edge_types: Vec<_> = incident_edges
+                .iter()
+                .filter_map(|e| {
+                    let edge_type = e.weight().edge_type()?;
+                    Some((edge_type, e.id(), e.source(), e.target()))
+                })
+                .collect();
+
             let mut graph = self.inner.clone();
 
             let removed_node = graph
-                .remove_node(*map_entry.get())
+                .remove_node(node_index)
                 .ok_or(StoreError::NodeNotDeleted)?;
 
             self.inner = self.store.save_to_file(graph)?;
-            map_entry.remove();
+            self.nodes_map.remove(&key);
+
+            for (edge_type, edge_index, source, target) in removed_edge_types {
+                self.edge_types
+                    .remove(&edge_type, edge_index, source, target);
+            }
+            self.indexes.remove(&key, &removed_node);
 
-            Ok(removed_node)
+            if !removed_node.unique_keys().is_empty() {
+                self.unique_constraints.remove(&key, &removed_node);
+                self.save_unique_constraints()?;
+            }
+
+            if let Some(text) = removed_node.search_text() {
+                self.search_index.remove(&key, &text);
+                self.search_index_dirty = true;
+            }
+
+            Ok((removed_node, removed_edges))
         } else {
             Err(StoreError::NodeNotFound)
         }
@@ -251,47 +1070,47 @@ where
         include_nodes: Option<Vec<N>>,
         include_edges: Option<Vec<E>>,
     ) -> Result<StableGraph<N, E, Directed>, StoreError> {
-        let filtered = match (include_nodes, include_edges) {
-            (Some(nodes), Some(edges)) => {
-                let mut nodes = nodes.into_iter();
-                let node_filter = |_, node: &N| {
-                    nodes.find(|n| {
-                        log::info!("n: {:?} == node: {:?}", n, node);
-                        n == node
-                    })
-                };
-                let mut edges = edges.into_iter();
-                let edge_filter = |_, edge: &E| {
-                    edges.find(|e| {
-                        log::info!("e: {:?} == edge: {:?}", e, edge);
-                        e == edge
-                    })
-                };
-
-                self.inner.filter_map(node_filter, edge_filter)
-            }
-            (Some(nodes), None) => {
-                let mut nodes = nodes.into_iter();
-                let node_filter = |_, node: &N| nodes.find(|n| n == node);
-                let edge_filter = |_, edge: &E| Some((*edge).clone());
+        let node_filter = |_, node: &N| match &include_nodes {
+            Some(nodes) if !nodes.contains(node) => None,
+            _ => Some(node.clone()),
+        };
+        let edge_filter = |_, edge: &E| match &include_edges {
+            Some(edges) if !edges.contains(edge) => None,
+            _ => Some(edge.clone()),
+        };
 
-                log::info!("(Some(nodes), None)");
+        let filtered = self.inner.filter_map(node_filter, edge_filter);
 
-                self.inner.filter_map(node_filter, edge_filter)
-            }
-            (None, Some(edges)) => {
-                let node_filter = |_, node: &N| Some((*node).clone());
-                let mut edges = edges.into_iter();
-                let edge_filter = |_, edge: &E| edges.find(|e| e == edge);
+        Ok(filtered)
+    }
 
-                self.inner.filter_map(node_filter, edge_filter)
-            }
-            (None, None) => self.inner.clone(),
-        };
+    fn filter_graph_by(
+        &self,
+        node_predicate: Predicate<N>,
+        edge_predicate: Predicate<E>,
+    ) -> Result<StableGraph<N, E, Directed>, StoreError> {
+        let filtered = self.inner.filter_map(
+            |_, node| node_predicate.matches(node).then(|| node.clone()),
+            |_, edge| edge_predicate.matches(edge).then(|| edge.clone()),
+        );
 
         Ok(filtered)
     }
 
+    fn filter_keys_by(
+        &self,
+        node_predicate: Predicate<N>,
+    ) -> Result<Vec<I>, StoreError> {
+        let keys = self
+            .inner
+            .node_references()
+            .filter(|(_, node)| node_predicate.matches(node))
+            .map(|(_, node)| node.clone().into())
+            .collect();
+
+        Ok(keys)
+    }
+
     fn retain_nodes(
         &self,
         nodes_to_retain: Vec<I>,
@@ -325,6 +1144,10 @@ where
         key: &I,
         dir: Direction,
     ) -> Result<Vec<N>, StoreError> {
+        if self.mode.undirected {
+            return self.get_neighbors_und(key);
+        }
+
         let found_node_index =
             self.nodes_map.get(key).ok_or(StoreError::NodeNotFound)?;
 
@@ -339,22 +1162,135 @@ where
         Ok(neighbors)
     }
 
+    fn get_neighbors_dir_by_type(
+        &self,
+        key: &I,
+        dir: Direction,
+        edge_type: &str,
+    ) -> Result<Vec<N>, StoreError> {
+        let node_index = self.get_node_index(key)?;
+
+        let neighbors = self
+            .get_directions(dir)
+            .into_iter()
+            .flat_map(|dir| {
+                self.edge_types
+                    .get_by_node(node_index, dir, edge_type)
+                    .into_iter()
+                    .map(move |edge_index| (dir, edge_index))
+            })
+            .filter_map(|(dir, edge_index)| {
+                let (source, target) = self.inner.edge_endpoints(edge_index)?;
+                match dir {
+                    Outgoing => self.inner.node_weight(target),
+                    Incoming => self.inner.node_weight(source),
+                }
+            })
+            .cloned()
+            .collect();
+
+        Ok(neighbors)
+    }
+
+    /// Nodes within `depth` hops of the node `root` in breadth-first order,
+    /// ignoring the direction of edges, and the edges between them.
+    fn get_neighborhood(
+        &self,
+        root: &I,
+        depth: usize,
+    ) -> Result<Subgraph<N, E, I>, StoreError> {
+        let start = self.get_node_index(root)?;
+
+        let mut distances = HashMap::from([(start, 0)]);
+        let mut queue = VecDeque::from([start]);
+        let mut indices = vec![start];
+
+        while let Some(current) = queue.pop_front() {
+            let distance = distances[&current];
+            if distance == depth {
+                continue;
+            }
+
+            for neighbor in self.inner.neighbors_undirected(current) {
+                if !distances.contains_key(&neighbor) {
+                    distances.insert(neighbor, distance + 1);
+                    queue.push_back(neighbor);
+                    indices.push(neighbor);
+                }
+            }
+        }
+
+        let nodes = indices
+            .iter()
+            .map(|index| self.inner[*index].clone())
+            .collect();
+        let edges = self
+            .inner
+            .edge_references()
+            .filter(|e| {
+                distances.contains_key(&e.source())
+                    && distances.contains_key(&e.target())
+            })
+            .map(|e| {
+                (
+                    self.inner[e.source()].clone().into(),
+                    self.inner[e.target()].clone().into(),
+                    e.weight().clone(),
+                )
+            })
+            .collect();
+
+        Ok((nodes, edges))
+    }
+
     fn get_edge(&self, from: &I, to: &I) -> Result<E, StoreError> {
-        if let (Some(from_idx), Some(to_idx)) =
-            (self.nodes_map.get(from), self.nodes_map.get(to))
-        {
-            let edge_index = self
-                .inner
-                .find_edge(*from_idx, *to_idx)
-                .ok_or(StoreError::EdgeNotFound)?;
+        let edge_index = self.get_edge_index(from, to, None)?;
 
-            self.inner
-                .edge_weight(edge_index)
-                .cloned()
-                .ok_or(StoreError::EdgeNotFound)
-        } else {
-            Err(StoreError::EdgeNotFound)
+        self.inner
+            .edge_weight(edge_index)
+            .cloned()
+            .ok_or(StoreError::EdgeNotFound)
+    }
+
+    fn get_edge_by_key(
+        &self,
+        from: &I,
+        to: &I,
+        key: &str,
+    ) -> Result<E, StoreError> {
+        let edge_index = self.get_edge_index(from, to, Some(key))?;
+
+        self.inner
+            .edge_weight(edge_index)
+            .cloned()
+            .ok_or(StoreError::EdgeNotFound)
+    }
+
+    fn get_edges_between(
+        &self,
+        from: &I,
+        to: &I,
+    ) -> Result<Vec<E>, StoreError> {
+        let from_idx = self.get_node_index(from)?;
+        let to_idx = self.get_node_index(to)?;
+
+        let mut edges: Vec<E> = self
+            .inner
+            .edges_connecting(from_idx, to_idx)
+            .map(|e| e.weight())
+            .cloned()
+            .collect();
+
+        if self.mode.undirected && from_idx != to_idx {
+            edges.extend(
+                self.inner
+                    .edges_connecting(to_idx, from_idx)
+                    .map(|e| e.weight())
+                    .cloned(),
+            );
         }
+
+        Ok(edges)
     }
 
     fn get_edges(&self) -> Result<Vec<E>, StoreError> {
@@ -365,6 +1301,63 @@ where
         Ok(edges)
     }
 
+    fn get_edges_with_endpoints(&self) -> Result<Vec<(I, I, E)>, StoreError> {
+        let triples = self
+            .inner
+            .edge_references()
+            .filter_map(|e| self.get_triple(e.source(), e.target(), e.weight()))
+            .collect();
+
+        Ok(triples)
+    }
+
+    fn get_incident_edges(
+        &self,
+        key: &I,
+        dir: Direction,
+    ) -> Result<Vec<(I, I, E)>, StoreError> {
+        let node_index = self.get_node_index(key)?;
+
+        // in undirected mode edges are oriented away from or towards the node
+        // as requested by `dir`
+        let triples = self
+            .get_directions(dir)
+            .into_iter()
+            .flat_map(|edge_dir| {
+                self.inner
+                    .edges_directed(node_index, edge_dir)
+                    .map(move |e| match edge_dir {
+                        Outgoing => (e.target(), e.weight()),
+                        Incoming => (e.source(), e.weight()),
+                    })
+            })
+            .filter_map(|(neighbor, edge)| match dir {
+                Outgoing => self.get_triple(node_index, neighbor, edge),
+                Incoming => self.get_triple(neighbor, node_index, edge),
+            })
+            .collect();
+
+        Ok(triples)
+    }
+
+    fn get_edges_by_type(
+        &self,
+        edge_type: &str,
+    ) -> Result<Vec<(I, I, E)>, StoreError> {
+        let triples = self
+            .edge_types
+            .get_by_type(edge_type)
+            .into_iter()
+            .filter_map(|edge_index| {
+                let (source, target) = self.inner.edge_endpoints(edge_index)?;
+                let edge = self.inner.edge_weight(edge_index)?;
+                self.get_triple(source, target, edge)
+            })
+            .collect();
+
+        Ok(triples)
+    }
+
     fn has_node(&self, key: &I) -> Result<bool, StoreError> {
         Ok(self.nodes_map.contains_key(key))
     }
@@ -388,6 +1381,86 @@ where
         Ok(nodes)
     }
 
+    /// Up to `limit` nodes following the key `after` in the key order `cmp`.
+    fn get_nodes_page(
+        &self,
+        after: Option<I>,
+        limit: usize,
+        cmp: KeyOrdering<I>,
+    ) -> Result<Vec<N>, StoreError> {
+        let range = OrderedRange {
+            start: after.map_or(Bound::Unbounded, Bound::Excluded),
+            end: Bound::Unbounded,
+            descending: false,
+            limit: Some(limit),
+        };
+
+        let nodes = self
+            .get_keys_ordered(range, cmp)?
+            .iter()
+            .filter_map(|key| self.nodes_map.get(key))
+            .filter_map(|index| self.inner.node_weight(*index))
+            .cloned()
+            .collect();
+
+        Ok(nodes)
+    }
+
+    /// Up to `limit` edges following the cursor `after`, ordered by source
+    /// key and target key in the key order `cmp`, then by edge key.
+    fn get_edges_page(
+        &self,
+        after: Option<EdgeCursor<I>>,
+        limit: usize,
+        cmp: KeyOrdering<I>,
+    ) -> Result<Vec<(I, I, E)>, StoreError> {
+        let range = OrderedRange {
+            start: match &after {
+                Some((from, _, _)) => Bound::Included(from.clone()),
+                None => Bound::Unbounded,
+            },
+            end: Bound::Unbounded,
+            descending: false,
+            limit: None,
+        };
+
+        let mut triples = Vec::new();
+
+        for from in self.get_keys_ordered(range, cmp)? {
+            let index = self.get_node_index(&from)?;
+            let mut edges: Vec<(I, Option<String>, &E)> = self
+                .inner
+                .edges_directed(index, Outgoing)
+                .filter_map(|e| {
+                    let to = self.inner.node_weight(e.target())?.clone().into();
+                    Some((to, e.weight().key(), e.weight()))
+                })
+                .collect();
+            edges.sort_by(|(to1, key1, _), (to2, key2, _)| {
+                cmp(to1, to2).then_with(|| key1.cmp(key2))
+            });
+
+            for (to, key, edge) in edges {
+                if let Some((after_from, after_to, after_key)) = &after {
+                    if &from == after_from
+                        && cmp(&to, after_to).then_with(|| key.cmp(after_key))
+                            != Ordering::Greater
+                    {
+                        continue;
+                    }
+                }
+
+                if triples.len() == limit {
+                    return Ok(triples);
+                }
+
+                triples.push((from.clone(), to, edge.clone()));
+            }
+        }
+
+        Ok(triples)
+    }
+
     fn get_node_index(&self, key: &I) -> Result<NodeIndex, StoreError> {
         let node_index =
             self.nodes_map.get(key).ok_or(StoreError::NodeNotFound)?;
@@ -395,6 +1468,36 @@ where
         Ok(*node_index)
     }
 
+    /// Keys within `range` in the key order `cmp`, only the first `limit`
+    /// keys are sorted.
+    fn get_keys_ordered(
+        &self,
+        range: OrderedRange<I>,
+        cmp: KeyOrdering<I>,
+    ) -> Result<Vec<I>, StoreError> {
+        let order = |a: &&I, b: &&I| {
+            if range.descending {
+                cmp(b, a)
+            } else {
+                cmp(a, b)
+            }
+        };
+
+        let mut keys: Vec<&I> = self
+            .nodes_map
+            .keys()
+            .filter(|key| range.contains_by(key, cmp))
+            .collect();
+
+        if let Some(limit) = range.limit.filter(|limit| *limit < keys.len()) {
+            keys.select_nth_unstable_by(limit, order);
+            keys.truncate(limit);
+        }
+        keys.sort_by(order);
+
+        Ok(keys.into_iter().cloned().collect())
+    }
+
     fn get_node_indices(
         &self,
         keys: Vec<I>,
@@ -402,7 +1505,12 @@ where
         Ok(keys.iter().filter_map(|k| self.nodes_map.get(k)).collect())
     }
 
+    /// Nodes without incoming edges, in undirected mode without any edges.
     fn get_source_nodes(&self) -> Result<Vec<N>, StoreError> {
+        if self.mode.undirected {
+            return self.get_isolated_nodes();
+        }
+
         let externals = self.inner.externals(Incoming);
         let externals = externals
             .filter_map(|n| self.inner.node_weight(n))
@@ -412,7 +1520,12 @@ where
         Ok(externals)
     }
 
+    /// Nodes without outgoing edges, in undirected mode without any edges.
     fn get_sink_nodes(&self) -> Result<Vec<N>, StoreError> {
+        if self.mode.undirected {
+            return self.get_isolated_nodes();
+        }
+
         let externals = self.inner.externals(Outgoing);
         let externals = externals
             .filter_map(|n| self.inner.node_weight(n))
@@ -422,20 +1535,272 @@ where
         Ok(externals)
     }
 
+    fn get_isolated_nodes(&self) -> Result<Vec<N>, StoreError> {
+        let isolated = self
+            .inner
+            .node_indices()
+            .filter(|n| self.inner.neighbors_undirected(*n).next().is_none())
+            .filter_map(|n| self.inner.node_weight(n))
+            .cloned()
+            .collect();
+
+        Ok(isolated)
+    }
+
     //
     // private methods
     //
 
-    fn get_nodes_map_from_graph(
-        data: &StableGraph<N, E, Directed>,
-    ) -> HashMap<I, NodeIndex<u32>> {
-        let mut nodes_map = HashMap::new();
-
+    /// Index of the edge from `from` to `to`, with the given
+    /// [`GraphEdge::key`] if any. Fails with [`StoreError::AmbiguousEdge`]
+    /// if several edges match.
+    fn get_edge_index(
+        &self,
+        from: &I,
+        to: &I,
+        key: Option<&str>,
+    ) -> Result<EdgeIndex, StoreError> {
+        let (Some(from_idx), Some(to_idx)) =
+            (self.nodes_map.get(from), self.nodes_map.get(to))
+        else {
+            return Err(StoreError::EdgeNotFound);
+        };
+
+        let find_edges = |from_idx: NodeIndex, to_idx: NodeIndex| {
+            self.inner
+                .edges_connecting(from_idx, to_idx)
+                .filter(move |e| {
+                    key.is_none() || e.weight().key().as_deref() == key
+                })
+                .map(|e| e.id())
+        };
+
+        let mut edges: Vec<EdgeIndex> =
+            find_edges(*from_idx, *to_idx).collect();
+        if self.mode.undirected && from_idx != to_idx {
+            edges.extend(find_edges(*to_idx, *from_idx));
+        }
+
+        match edges.as_slice() {
+            [] => Err(StoreError::EdgeNotFound),
+            [edge_index] => Ok(*edge_index),
+            _ => Err(StoreError::AmbiguousEdge),
+        }
+    }
+
+    /// Whether the graph holds edges between the same nodes which lookups
+    /// in the given mode couldn't tell apart.
+    fn has_indistinct_edges(&self, multigraph: bool, undirected: bool) -> bool {
+        let mut edges = HashSet::new();
+
+        self.inner.edge_references().any(|e| {
+            let (mut source, mut target) = (e.source(), e.target());
+            if undirected && source > target {
+                std::mem::swap(&mut source, &mut target);
+            }
+            let key = if multigraph { e.weight().key() } else { None };
+
+            !edges.insert((source, target, key))
+        })
+    }
+
+    /// Edges from and to the node, self loops are listed once.
+    fn get_incident_edge_refs(
+        &self,
+        node_index: NodeIndex,
+    ) -> Vec<EdgeReference<'_, E>> {
+        self.inner
+            .edges_directed(node_index, Outgoing)
+            .chain(
+                self.inner
+                    .edges_directed(node_index, Incoming)
+                    .filter(move |e| e.source() != node_index),
+            )
+            .collect()
+    }
+
+    /// Directions of the edges to follow for `dir`, both in undirected mode.
+    fn get_directions(&self, dir: Direction) -> Vec<Direction> {
+        if self.mode.undirected {
+            vec![Outgoing, Incoming]
+        } else {
+            vec![dir]
+        }
+    }
+
+    fn remove_edge_at(
+        &mut self,
+        edge_index: EdgeIndex,
+    ) -> Result<E, StoreError> {
+        let (source, target) = self
+            .inner
+            .edge_endpoints(edge_index)
+            .ok_or(StoreError::EdgeNotFound)?;
+
+        let mut graph = self.inner.clone();
+
+        let edge = graph
+            .remove_edge(edge_index)
+            .ok_or(StoreError::EdgeNotFound)?;
+
+        self.inner = self.store.save_to_file(graph)?;
+
+        if let Some(edge_type) = edge.edge_type() {
+            self.edge_types
+                .remove(&edge_type, edge_index, source, target);
+        }
+
+        Ok(edge)
+    }
+
+    fn get_triple(
+        &self,
+        source: NodeIndex,
+        target: NodeIndex,
+        edge: &E,
+    ) -> Option<(I, I, E)> {
+        let source = self.inner.node_weight(source)?.clone().into();
+        let target = self.inner.node_weight(target)?.clone().into();
+
+        Some((source, target, edge.clone()))
+    }
+
+    fn save_meta(&self) -> Result<(), StoreError> {
+        let meta = GraphMeta {
+            last_hash: self.last_hash.clone(),
+            mode: self.mode,
+        };
+
+        self.store.save_derived_to_file(GRAPH_META_FILE_NAME, &meta)
+    }
+
+    fn save_unique_constraints(&self) -> Result<(), StoreError> {
+        self.store.save_derived_to_file(
+            UNIQUE_CONSTRAINTS_FILE_NAME,
+            &self.unique_constraints,
+        )
+    }
+
+    /// Loads the persisted unique keys and rebuilds them from the graph,
+    /// keeping the mutation hashes of the claims which are still valid.
+    /// Also returns the keys of the nodes whose hashes are missing, to
+    /// restore with [`Graph::restore_unique_key_hashes`].
+    fn load_unique_constraints(
+        store: &GraphStore<N, E>,
+        data: &StableGraph<N, E, Directed>,
+    ) -> Result<(UniqueConstraints<I>, HashSet<I>), StoreError> {
+        let loaded = store
+            .load_derived_from_file::<UniqueConstraints<I>>(
+                UNIQUE_CONSTRAINTS_FILE_NAME,
+            )
+            .unwrap_or_else(|err| {
+                log::warn!(
+                    "Error loading unique constraints. Error: '{err:?}'"
+                );
+                None
+            })
+            .unwrap_or_default();
+
+        let mut unique_constraints = UniqueConstraints::default();
+        let mut unknown_hashes = HashSet::new();
+
+        for node in data.node_weights() {
+            if node.unique_keys().is_empty() {
+                continue;
+            }
+
+            let key: I = node.clone().into();
+
+            // until restored, the empty hash keeps the node from being
+            // evicted by concurrently added nodes
+            let hash = match loaded.get_hash(&key, node) {
+                Some(hash) => hash,
+                None => {
+                    unknown_hashes.insert(key.clone());
+                    String::new()
+                }
+            };
+            unique_constraints.insert(&key, node, &hash);
+        }
+
+        Ok((unique_constraints, unknown_hashes))
+    }
+
+    /// Loads the persisted search index, rebuilding it from the graph if it
+    /// is missing or was saved before the last mutation `last_hash`.
+    fn load_search_index(
+        store: &GraphStore<N, E>,
+        data: &StableGraph<N, E, Directed>,
+        last_hash: &Option<String>,
+    ) -> Result<SearchIndex<I>, StoreError> {
+        let texts: Vec<(I, String)> = data
+            .node_weights()
+            .filter_map(|node| Some((node.clone().into(), node.search_text()?)))
+            .collect();
+
+        let loaded = store
+            .load_derived_from_file::<(Option<String>, SearchIndex<I>)>(
+                SEARCH_INDEX_FILE_NAME,
+            )
+            .unwrap_or_else(|err| {
+                log::warn!("Error loading search index. Error: '{err:?}'");
+                None
+            });
+
+        if let Some((hash, search_index)) = loaded {
+            if &hash == last_hash && search_index.len() == texts.len() {
+                return Ok(search_index);
+            }
+        }
+
+        let mut search_index = SearchIndex::default();
+
+        for (key, text) in texts.iter() {
+            search_index.insert(key, text);
+        }
+
+        if !texts.is_empty() {
+            store.save_derived_to_file(
+                SEARCH_INDEX_FILE_NAME,
+                &(last_hash, &search_index),
+            )?;
+        }
+
+        Ok(search_index)
+    }
+
+    fn get_edge_types_from_graph(
+        data: &StableGraph<N, E, Directed>,
+    ) -> EdgeTypeIndex {
+        let mut edge_types = EdgeTypeIndex::default();
+
+        for edge in data.edge_references() {
+            if let Some(edge_type) = edge.weight().edge_type() {
+                edge_types.insert(
+                    &edge_type,
+                    edge.id(),
+                    edge.source(),
+                    edge.target(),
+                );
+            }
+        }
+
+        edge_types
+    }
+
+    fn get_nodes_map_from_graph(
+        data: &StableGraph<N, E, Directed>,
+    ) -> (HashMap<I, NodeIndex<u32>>, SecondaryIndexes<I>) {
+        let mut nodes_map = HashMap::new();
+        let mut indexes = SecondaryIndexes::default();
+
         for (index, node) in data.node_references() {
-            nodes_map.insert(node.clone().into(), index);
+            let key: I = node.clone().into();
+            indexes.insert(&key, node);
+            nodes_map.insert(key, index);
         }
 
-        nodes_map
+        (nodes_map, indexes)
     }
 }
 
@@ -444,7 +1809,10 @@ mod tests {
     use serde::{Deserialize, Serialize};
     use uuid::Uuid;
 
+    use std::ops::Bound;
+
     use super::*;
+    use crate::IndexKey;
 
     #[derive(
         Debug,
@@ -628,7 +1996,9 @@ mod tests {
         graph.add_node(node2).unwrap();
         graph.add_node(node3).unwrap();
         graph.add_edge(node1.into(), node3.into(), Edge(1)).unwrap();
-        graph.remove_node(node1.into()).unwrap();
+        graph
+            .remove_node(node1.into(), DeletionPolicy::Detach)
+            .unwrap();
 
         let graph_inner = graph.get_graph().unwrap();
         let graph_inner_json = serde_json::to_string(&graph_inner).unwrap();
@@ -644,4 +2014,1128 @@ mod tests {
             std::fs::remove_dir_all(test_dir).unwrap();
         }
     }
+
+    #[tokio::test]
+    async fn test_filter_graph_by_ok() {
+        let test_dir = "test-data/test_filter_graph_by_ok";
+        if std::path::Path::new(test_dir).is_dir() {
+            std::fs::remove_dir_all(test_dir).unwrap();
+        }
+
+        let mut graph =
+            Graph::<Node, Edge, NodeId>::new(Some(test_dir.into())).unwrap();
+
+        let node1 = Node(Uuid::new_v4());
+        let node2 = Node(Uuid::new_v4());
+        let node3 = Node(Uuid::new_v4());
+
+        graph.add_node(node1).unwrap();
+        graph.add_node(node2).unwrap();
+        graph.add_node(node3).unwrap();
+        graph.add_edge(node1.into(), node2.into(), Edge(1)).unwrap();
+        graph.add_edge(node2.into(), node3.into(), Edge(1)).unwrap();
+        graph.add_edge(node1.into(), node3.into(), Edge(2)).unwrap();
+
+        let filtered = graph.filter_graph(None, Some(vec![Edge(1)])).unwrap();
+        assert_eq!(filtered.node_count(), 3);
+        assert_eq!(filtered.edge_count(), 2);
+
+        let filtered = graph
+            .filter_graph_by(
+                Predicate::new(move |node| *node != node2),
+                Predicate::new(|edge: &Edge| edge.0 > 1),
+            )
+            .unwrap();
+        assert_eq!(filtered.node_count(), 2);
+        assert_eq!(filtered.edge_count(), 1);
+
+        let keys = graph
+            .filter_keys_by(Predicate::new(move |node| *node == node3))
+            .unwrap();
+        assert_eq!(keys, vec![NodeId::from(node3)]);
+
+        // predicates only have to be Send, e.g. to keep state in a Cell
+        let calls = std::cell::Cell::new(0);
+        let keys = graph
+            .filter_keys_by(Predicate::new(move |node| {
+                calls.set(calls.get() + 1);
+                *node == node1
+            }))
+            .unwrap();
+        assert_eq!(keys, vec![NodeId::from(node1)]);
+
+        if std::path::Path::new(test_dir).is_dir() {
+            std::fs::remove_dir_all(test_dir).unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_incident_edges_ok() {
+        let test_dir = "test-data/test_get_incident_edges_ok";
+        if std::path::Path::new(test_dir).is_dir() {
+            std::fs::remove_dir_all(test_dir).unwrap();
+        }
+
+        let mut graph =
+            Graph::<Node, Edge, NodeId>::new(Some(test_dir.into())).unwrap();
+
+        let node1 = Node(Uuid::new_v4());
+        let node2 = Node(Uuid::new_v4());
+
+        graph.add_node(node1).unwrap();
+        graph.add_node(node2).unwrap();
+        graph.add_edge(node1.into(), node2.into(), Edge(1)).unwrap();
+
+        let expected = vec![(node1.into(), node2.into(), Edge(1))];
+        assert_eq!(graph.get_edges_with_endpoints().unwrap(), expected);
+        assert_eq!(
+            graph.get_incident_edges(&node2.into(), Incoming).unwrap(),
+            expected
+        );
+        assert!(graph
+            .get_incident_edges(&node2.into(), Outgoing)
+            .unwrap()
+            .is_empty());
+
+        if std::path::Path::new(test_dir).is_dir() {
+            std::fs::remove_dir_all(test_dir).unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_neighborhood_ok() {
+        let test_dir = "test-data/test_get_neighborhood_ok";
+        if std::path::Path::new(test_dir).is_dir() {
+            std::fs::remove_dir_all(test_dir).unwrap();
+        }
+
+        let mut graph =
+            Graph::<Node, Edge, NodeId>::new(Some(test_dir.into())).unwrap();
+
+        // 0 -> 1 -> 2 -> 3, 0 -> 2
+        let nodes: Vec<_> = (0..4).map(|_| Node(Uuid::new_v4())).collect();
+        for node in nodes.iter() {
+            graph.add_node(*node).unwrap();
+        }
+        for (edge, (from, to)) in
+            [(0, 1), (1, 2), (2, 3), (0, 2)].into_iter().enumerate()
+        {
+            graph
+                .add_edge(nodes[from].into(), nodes[to].into(), Edge(edge))
+                .unwrap();
+        }
+
+        let (neighborhood, edges) =
+            graph.get_neighborhood(&nodes[1].into(), 1).unwrap();
+        assert_eq!(neighborhood[0], nodes[1]);
+        assert_eq!(neighborhood.len(), 3);
+        assert!(!neighborhood.contains(&nodes[3]));
+        // 2 -> 3 leaves the neighborhood
+        assert_eq!(edges.len(), 3);
+        assert!(!edges.contains(&(nodes[2].into(), nodes[3].into(), Edge(2))));
+
+        let (neighborhood, _) =
+            graph.get_neighborhood(&nodes[1].into(), 0).unwrap();
+        assert_eq!(neighborhood, vec![nodes[1]]);
+        assert_eq!(
+            graph.get_neighborhood(&Node(Uuid::new_v4()).into(), 1),
+            Err(StoreError::NodeNotFound)
+        );
+
+        if std::path::Path::new(test_dir).is_dir() {
+            std::fs::remove_dir_all(test_dir).unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_nodes_page_ok() {
+        let test_dir = "test-data/test_get_nodes_page_ok";
+        if std::path::Path::new(test_dir).is_dir() {
+            std::fs::remove_dir_all(test_dir).unwrap();
+        }
+
+        let mut graph =
+            Graph::<Node, Edge, NodeId>::new(Some(test_dir.into())).unwrap();
+
+        let nodes: Vec<Node> =
+            (1..=5).map(|id| Node(Uuid::from_u128(id))).collect();
+
+        for node in nodes[..4].iter() {
+            graph.add_node(*node).unwrap();
+        }
+
+        let page = graph.get_nodes_page(None, 2, NodeId::cmp).unwrap();
+        assert_eq!(page, nodes[..2]);
+
+        // the cursor is removed and the new node reuses a slot in front of
+        // the nodes of the next page
+        graph
+            .remove_node(nodes[0].into(), DeletionPolicy::Detach)
+            .unwrap();
+        graph
+            .remove_node(nodes[1].into(), DeletionPolicy::Detach)
+            .unwrap();
+        graph.add_node(nodes[4]).unwrap();
+        assert!(graph.get_node_index(&nodes[4].into()).unwrap().index() < 2);
+
+        let page = graph
+            .get_nodes_page(Some(nodes[1].into()), 10, NodeId::cmp)
+            .unwrap();
+        assert_eq!(page, nodes[2..]);
+
+        if std::path::Path::new(test_dir).is_dir() {
+            std::fs::remove_dir_all(test_dir).unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_edges_page_ok() {
+        let test_dir = "test-data/test_get_edges_page_ok";
+        if std::path::Path::new(test_dir).is_dir() {
+            std::fs::remove_dir_all(test_dir).unwrap();
+        }
+
+        #[derive(
+            Debug, Default, Clone, PartialEq, PartialOrd, Serialize, Deserialize,
+        )]
+        struct KeyedEdge(usize);
+
+        impl GraphEdge for KeyedEdge {
+            fn key(&self) -> Option<String> {
+                Some(self.0.to_string())
+            }
+        }
+
+        let mut graph =
+            Graph::<Node, KeyedEdge, NodeId>::new(Some(test_dir.into()))
+                .unwrap();
+        graph.set_multigraph(true).unwrap();
+
+        let nodes: Vec<Node> =
+            (1..=3).map(|id| Node(Uuid::from_u128(id))).collect();
+        let keys: Vec<NodeId> =
+            nodes.iter().map(|node| (*node).into()).collect();
+
+        for node in nodes.iter() {
+            graph.add_node(*node).unwrap();
+        }
+
+        let expected = vec![
+            (keys[0], keys[1], KeyedEdge(1)),
+            (keys[0], keys[1], KeyedEdge(2)),
+            (keys[0], keys[2], KeyedEdge(1)),
+            (keys[1], keys[2], KeyedEdge(1)),
+        ];
+
+        for (from, to, edge) in expected.iter().rev() {
+            graph.add_edge(*from, *to, edge.clone()).unwrap();
+        }
+
+        // pages end between parallel edges
+        let mut paged = Vec::new();
+        let mut after = None;
+
+        loop {
+            let page = graph.get_edges_page(after, 1, NodeId::cmp).unwrap();
+            let Some((from, to, edge)) = page.last().cloned() else {
+                break;
+            };
+            after = Some((from, to, edge.key()));
+            paged.extend(page);
+        }
+        assert_eq!(paged, expected);
+
+        // the cursor doesn't have to exist anymore
+        graph
+            .remove_edge_by_key(keys[0], keys[1], "2".into())
+            .unwrap();
+        assert_eq!(
+            graph
+                .get_edges_page(
+                    Some((keys[0], keys[1], Some("2".into()))),
+                    10,
+                    NodeId::cmp,
+                )
+                .unwrap(),
+            expected[2..]
+        );
+
+        if std::path::Path::new(test_dir).is_dir() {
+            std::fs::remove_dir_all(test_dir).unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_find_by_index_ok() {
+        let test_dir = "test-data/test_find_by_index_ok";
+        if std::path::Path::new(test_dir).is_dir() {
+            std::fs::remove_dir_all(test_dir).unwrap();
+        }
+
+        #[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+        struct LabeledNode {
+            label: String,
+            id: Uuid,
+        }
+
+        impl GraphNode for LabeledNode {
+            fn index_keys(&self) -> Vec<(&'static str, IndexKey)> {
+                vec![("label", self.label.as_str().into())]
+            }
+        }
+
+        impl From<LabeledNode> for NodeId {
+            fn from(node: LabeledNode) -> Self {
+                Self(node.id)
+            }
+        }
+
+        let mut graph =
+            Graph::<LabeledNode, Edge, NodeId>::new(Some(test_dir.into()))
+                .unwrap();
+
+        let node1 = LabeledNode {
+            label: "a".to_string(),
+            id: Uuid::new_v4(),
+        };
+        let node2 = LabeledNode {
+            label: "b".to_string(),
+            id: Uuid::new_v4(),
+        };
+
+        graph.add_node(node1.clone()).unwrap();
+        graph.add_node(node2.clone()).unwrap();
+
+        let found = graph.indexes.find("label", &"a".into());
+        assert_eq!(found, vec![NodeId::from(node1.clone())]);
+
+        graph
+            .remove_node(node1.into(), DeletionPolicy::Detach)
+            .unwrap();
+        assert!(graph.indexes.find("label", &"a".into()).is_empty());
+
+        // indexes are rebuilt when loading the graph from file
+        let graph =
+            Graph::<LabeledNode, Edge, NodeId>::new(Some(test_dir.into()))
+                .unwrap();
+        let found = graph.indexes.find("label", &"b".into());
+        assert_eq!(found, vec![NodeId::from(node2)]);
+
+        if std::path::Path::new(test_dir).is_dir() {
+            std::fs::remove_dir_all(test_dir).unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_range_by_index_ok() {
+        let test_dir = "test-data/test_range_by_index_ok";
+        if std::path::Path::new(test_dir).is_dir() {
+            std::fs::remove_dir_all(test_dir).unwrap();
+        }
+
+        #[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+        struct ScoredNode {
+            score: f64,
+            id: Uuid,
+        }
+
+        impl GraphNode for ScoredNode {
+            fn ordered_index_keys(&self) -> Vec<(&'static str, IndexKey)> {
+                vec![("score", self.score.into())]
+            }
+        }
+
+        impl From<ScoredNode> for NodeId {
+            fn from(node: ScoredNode) -> Self {
+                Self(node.id)
+            }
+        }
+
+        let mut graph =
+            Graph::<ScoredNode, Edge, NodeId>::new(Some(test_dir.into()))
+                .unwrap();
+
+        let nodes: Vec<ScoredNode> = [0.5, 2.5, 1.5, -1.0]
+            .into_iter()
+            .map(|score| ScoredNode {
+                score,
+                id: Uuid::new_v4(),
+            })
+            .collect();
+
+        for node in nodes.iter() {
+            graph.add_node(node.clone()).unwrap();
+        }
+
+        let keys = graph.indexes.find_range(
+            "score",
+            OrderedRange {
+                start: Bound::Included(0.5.into()),
+                end: Bound::Excluded(2.5.into()),
+                descending: false,
+                limit: None,
+            },
+        );
+        assert_eq!(
+            keys,
+            vec![nodes[0].clone().into(), nodes[2].clone().into()]
+        );
+
+        let keys = graph.indexes.find_range(
+            "score",
+            OrderedRange {
+                start: Bound::Unbounded,
+                end: Bound::Unbounded,
+                descending: true,
+                limit: Some(2),
+            },
+        );
+        assert_eq!(
+            keys,
+            vec![nodes[1].clone().into(), nodes[2].clone().into()]
+        );
+
+        let mut expected: Vec<NodeId> =
+            nodes.iter().cloned().map(NodeId::from).collect();
+        expected.sort();
+
+        let keys = graph
+            .get_keys_ordered(
+                OrderedRange {
+                    start: Bound::Excluded(expected[0]),
+                    end: Bound::Unbounded,
+                    descending: false,
+                    limit: None,
+                },
+                NodeId::cmp,
+            )
+            .unwrap();
+        assert_eq!(keys, expected[1..]);
+
+        let keys = graph
+            .get_keys_ordered(
+                OrderedRange {
+                    start: Bound::Unbounded,
+                    end: Bound::Excluded(expected[3]),
+                    descending: true,
+                    limit: Some(2),
+                },
+                NodeId::cmp,
+            )
+            .unwrap();
+        assert_eq!(keys, vec![expected[2], expected[1]]);
+
+        // start after end
+        let keys = graph
+            .get_keys_ordered(
+                OrderedRange {
+                    start: Bound::Included(expected[3]),
+                    end: Bound::Excluded(expected[0]),
+                    descending: false,
+                    limit: None,
+                },
+                NodeId::cmp,
+            )
+            .unwrap();
+        assert!(keys.is_empty());
+
+        if std::path::Path::new(test_dir).is_dir() {
+            std::fs::remove_dir_all(test_dir).unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_search_ok() {
+        let test_dir = "test-data/test_search_ok";
+        if std::path::Path::new(test_dir).is_dir() {
+            std::fs::remove_dir_all(test_dir).unwrap();
+        }
+
+        #[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+        struct NamedNode {
+            name: String,
+            id: Uuid,
+        }
+
+        impl GraphNode for NamedNode {
+            fn search_text(&self) -> Option<String> {
+                Some(self.name.clone())
+            }
+        }
+
+        impl From<NamedNode> for NodeId {
+            fn from(node: NamedNode) -> Self {
+                Self(node.id)
+            }
+        }
+
+        let mut graph =
+            Graph::<NamedNode, Edge, NodeId>::new(Some(test_dir.into()))
+                .unwrap();
+
+        let node1 = NamedNode {
+            name: "Ada Lovelace".to_string(),
+            id: Uuid::new_v4(),
+        };
+        let node2 = NamedNode {
+            name: "Ada, Countess of Lovelace, Ada".to_string(),
+            id: Uuid::new_v4(),
+        };
+
+        graph.add_node(node1.clone()).unwrap();
+        graph.add_node(node2.clone()).unwrap();
+
+        let found = graph.search_index.search("countess");
+        assert_eq!(found, vec![NodeId::from(node2.clone())]);
+
+        let found = graph.search_index.search("ADA");
+        assert_eq!(found.len(), 2);
+
+        graph
+            .remove_node(node2.into(), DeletionPolicy::Detach)
+            .unwrap();
+        graph.snapshot().unwrap();
+
+        let search_index_path = format!("{test_dir}/{SEARCH_INDEX_FILE_NAME}");
+        let saved_at = std::fs::metadata(&search_index_path)
+            .unwrap()
+            .modified()
+            .unwrap();
+        let (saved_hash, _) = graph
+            .store
+            .load_derived_from_file::<(Option<String>, SearchIndex<NodeId>)>(
+                SEARCH_INDEX_FILE_NAME,
+            )
+            .unwrap()
+            .unwrap();
+        assert!(saved_hash.is_some());
+        assert_eq!(saved_hash, graph.last_hash);
+
+        // the saved search index is loaded rather than rebuilt and saved again
+        let graph =
+            Graph::<NamedNode, Edge, NodeId>::new(Some(test_dir.into()))
+                .unwrap();
+        assert_eq!(graph.last_hash, saved_hash);
+        assert_eq!(
+            std::fs::metadata(&search_index_path)
+                .unwrap()
+                .modified()
+                .unwrap(),
+            saved_at
+        );
+        assert!(graph.search_index.search("countess").is_empty());
+        assert_eq!(
+            graph.search_index.search("lovelace"),
+            vec![NodeId::from(node1)]
+        );
+
+        if std::path::Path::new(test_dir).is_dir() {
+            std::fs::remove_dir_all(test_dir).unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_search_index_stale_with_same_count_rebuilt_ok() {
+        let test_dir = "test-data/test_search_index_stale_rebuilt_ok";
+        if std::path::Path::new(test_dir).is_dir() {
+            std::fs::remove_dir_all(test_dir).unwrap();
+        }
+
+        #[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+        struct NamedNode {
+            name: String,
+            id: Uuid,
+        }
+
+        impl GraphNode for NamedNode {
+            fn search_text(&self) -> Option<String> {
+                Some(self.name.clone())
+            }
+        }
+
+        impl From<NamedNode> for NodeId {
+            fn from(node: NamedNode) -> Self {
+                Self(node.id)
+            }
+        }
+
+        let node1 = NamedNode {
+            name: "Ada Lovelace".to_string(),
+            id: Uuid::new_v4(),
+        };
+        let node2 = NamedNode {
+            name: "Alan Turing".to_string(),
+            id: Uuid::new_v4(),
+        };
+
+        let mut graph =
+            Graph::<NamedNode, Edge, NodeId>::new(Some(test_dir.into()))
+                .unwrap();
+        graph
+            .apply_mutation(GraphMutation::AddNode(node1.clone()), None)
+            .unwrap();
+        graph.snapshot().unwrap();
+
+        // replaced without a snapshot, the saved index has the same count
+        graph
+            .apply_mutation(GraphMutation::RemoveNode(node1.into()), None)
+            .unwrap();
+        graph
+            .apply_mutation(GraphMutation::AddNode(node2.clone()), None)
+            .unwrap();
+
+        let graph =
+            Graph::<NamedNode, Edge, NodeId>::new(Some(test_dir.into()))
+                .unwrap();
+        assert!(graph.search_index.search("lovelace").is_empty());
+        assert_eq!(
+            graph.search_index.search("turing"),
+            vec![NodeId::from(node2)]
+        );
+
+        if std::path::Path::new(test_dir).is_dir() {
+            std::fs::remove_dir_all(test_dir).unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_edges_by_type_ok() {
+        let test_dir = "test-data/test_get_edges_by_type_ok";
+        if std::path::Path::new(test_dir).is_dir() {
+            std::fs::remove_dir_all(test_dir).unwrap();
+        }
+
+        #[derive(
+            Debug, Default, Clone, PartialEq, PartialOrd, Serialize, Deserialize,
+        )]
+        struct TypedEdge(usize);
+
+        impl GraphEdge for TypedEdge {
+            fn edge_type(&self) -> Option<String> {
+                Some(format!("type-{}", self.0))
+            }
+        }
+
+        let mut graph =
+            Graph::<Node, TypedEdge, NodeId>::new(Some(test_dir.into()))
+                .unwrap();
+
+        let node1 = Node(Uuid::new_v4());
+        let node2 = Node(Uuid::new_v4());
+        let node3 = Node(Uuid::new_v4());
+
+        graph.add_node(node1).unwrap();
+        graph.add_node(node2).unwrap();
+        graph.add_node(node3).unwrap();
+        graph
+            .add_edge(node1.into(), node2.into(), TypedEdge(1))
+            .unwrap();
+        graph
+            .add_edge(node1.into(), node3.into(), TypedEdge(2))
+            .unwrap();
+        graph
+            .add_edge(node3.into(), node2.into(), TypedEdge(1))
+            .unwrap();
+
+        assert_eq!(graph.get_edges_by_type("type-1").unwrap().len(), 2);
+        assert_eq!(
+            graph
+                .get_neighbors_dir_by_type(&node1.into(), Outgoing, "type-1")
+                .unwrap(),
+            vec![node2]
+        );
+        assert!(graph
+            .get_neighbors_dir_by_type(&node2.into(), Outgoing, "type-1")
+            .unwrap()
+            .is_empty());
+
+        graph
+            .remove_node(node3.into(), DeletionPolicy::Detach)
+            .unwrap();
+        assert_eq!(
+            graph.get_edges_by_type("type-1").unwrap(),
+            vec![(node1.into(), node2.into(), TypedEdge(1))]
+        );
+        assert!(graph.get_edges_by_type("type-2").unwrap().is_empty());
+
+        let graph =
+            Graph::<Node, TypedEdge, NodeId>::new(Some(test_dir.into()))
+                .unwrap();
+        assert_eq!(graph.get_edges_by_type("type-1").unwrap().len(), 1);
+
+        if std::path::Path::new(test_dir).is_dir() {
+            std::fs::remove_dir_all(test_dir).unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_multigraph_parallel_edges_ok() {
+        let test_dir = "test-data/test_multigraph_parallel_edges_ok";
+        if std::path::Path::new(test_dir).is_dir() {
+            std::fs::remove_dir_all(test_dir).unwrap();
+        }
+
+        #[derive(
+            Debug, Default, Clone, PartialEq, PartialOrd, Serialize, Deserialize,
+        )]
+        struct KeyedEdge(usize);
+
+        impl GraphEdge for KeyedEdge {
+            fn key(&self) -> Option<String> {
+                Some(self.0.to_string())
+            }
+        }
+
+        let mut graph =
+            Graph::<Node, KeyedEdge, NodeId>::new(Some(test_dir.into()))
+                .unwrap();
+
+        let node1 = Node(Uuid::new_v4());
+        let node2 = Node(Uuid::new_v4());
+
+        graph.add_node(node1).unwrap();
+        graph.add_node(node2).unwrap();
+        graph
+            .add_edge(node1.into(), node2.into(), KeyedEdge(1))
+            .unwrap();
+        assert_eq!(
+            graph.add_edge(node1.into(), node2.into(), KeyedEdge(2)),
+            Err(StoreError::ConflictDuplicateEdge)
+        );
+
+        graph.set_multigraph(true).unwrap();
+        graph
+            .add_edge(node1.into(), node2.into(), KeyedEdge(2))
+            .unwrap();
+        assert_eq!(
+            graph.add_edge(node1.into(), node2.into(), KeyedEdge(2)),
+            Err(StoreError::ConflictDuplicateEdge)
+        );
+
+        // key-less lookups don't pick one of the parallel edges
+        assert_eq!(
+            graph.remove_edge(node1.into(), node2.into()),
+            Err(StoreError::AmbiguousEdge)
+        );
+        assert_eq!(
+            graph.get_edge(&node1.into(), &node2.into()),
+            Err(StoreError::AmbiguousEdge)
+        );
+        assert!(matches!(
+            graph.set_multigraph(false),
+            Err(StoreError::GraphModeConflict(_))
+        ));
+
+        // the mode is loaded with the graph
+        let mut graph =
+            Graph::<Node, KeyedEdge, NodeId>::new(Some(test_dir.into()))
+                .unwrap();
+        assert!(graph.mode().multigraph);
+        assert_eq!(
+            graph
+                .get_edges_between(&node1.into(), &node2.into())
+                .unwrap()
+                .len(),
+            2
+        );
+
+        assert_eq!(
+            graph
+                .remove_edge_by_key(node1.into(), node2.into(), "1".into())
+                .unwrap(),
+            KeyedEdge(1)
+        );
+        assert_eq!(
+            graph
+                .get_edge_by_key(&node1.into(), &node2.into(), "2")
+                .unwrap(),
+            KeyedEdge(2)
+        );
+        assert_eq!(
+            graph.get_edge_by_key(&node1.into(), &node2.into(), "1"),
+            Err(StoreError::EdgeNotFound)
+        );
+
+        if std::path::Path::new(test_dir).is_dir() {
+            std::fs::remove_dir_all(test_dir).unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_undirected_ok() {
+        let test_dir = "test-data/test_undirected_ok";
+        if std::path::Path::new(test_dir).is_dir() {
+            std::fs::remove_dir_all(test_dir).unwrap();
+        }
+
+        let mut graph =
+            Graph::<Node, Edge, NodeId>::new(Some(test_dir.into())).unwrap();
+        graph.set_undirected(true).unwrap();
+
+        let node1 = Node(Uuid::new_v4());
+        let node2 = Node(Uuid::new_v4());
+
+        graph.add_node(node1).unwrap();
+        graph.add_node(node2).unwrap();
+        graph.add_edge(node1.into(), node2.into(), Edge(1)).unwrap();
+
+        assert_eq!(
+            graph.add_edge(node2.into(), node1.into(), Edge(2)),
+            Err(StoreError::ConflictDuplicateEdge)
+        );
+        assert_eq!(graph.get_edge(&node2.into(), &node1.into()), Ok(Edge(1)));
+        assert_eq!(
+            graph.get_neighbors_dir(&node2.into(), Outgoing).unwrap(),
+            vec![node1]
+        );
+        assert_eq!(
+            graph.get_incident_edges(&node2.into(), Outgoing).unwrap(),
+            vec![(node2.into(), node1.into(), Edge(1))]
+        );
+
+        // only nodes without edges are sources and sinks
+        let node3 = Node(Uuid::new_v4());
+        graph.add_node(node3).unwrap();
+        assert_eq!(graph.get_source_nodes().unwrap(), vec![node3]);
+        assert_eq!(graph.get_sink_nodes().unwrap(), vec![node3]);
+
+        // edges are paged once with the direction they were added with
+        assert_eq!(
+            graph.get_edges_page(None, 10, NodeId::cmp).unwrap(),
+            vec![(node1.into(), node2.into(), Edge(1))]
+        );
+
+        // the mode is loaded with the graph
+        let mut graph =
+            Graph::<Node, Edge, NodeId>::new(Some(test_dir.into())).unwrap();
+        assert!(graph.mode().undirected);
+
+        assert_eq!(graph.remove_edge(node2.into(), node1.into()), Ok(Edge(1)));
+        assert!(graph.get_edges().unwrap().is_empty());
+
+        // edges in both directions can't be told apart once undirected
+        graph.set_undirected(false).unwrap();
+        graph.add_edge(node1.into(), node2.into(), Edge(1)).unwrap();
+        graph.add_edge(node2.into(), node1.into(), Edge(2)).unwrap();
+        assert!(matches!(
+            graph.set_undirected(true),
+            Err(StoreError::GraphModeConflict(_))
+        ));
+        assert!(!graph.mode().undirected);
+
+        if std::path::Path::new(test_dir).is_dir() {
+            std::fs::remove_dir_all(test_dir).unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_remove_node_with_edges_ok() {
+        let test_dir = "test-data/test_remove_node_with_edges_ok/node";
+        let replica_dir = "test-data/test_remove_node_with_edges_ok/replica";
+        let root_test_path = "test-data/test_remove_node_with_edges_ok";
+        if std::path::Path::new(root_test_path).is_dir() {
+            std::fs::remove_dir_all(root_test_path).unwrap();
+        }
+
+        #[derive(
+            Debug, Default, Clone, PartialEq, PartialOrd, Serialize, Deserialize,
+        )]
+        struct OptionalKeyEdge(Option<usize>);
+
+        impl GraphEdge for OptionalKeyEdge {
+            fn key(&self) -> Option<String> {
+                self.0.map(|key| key.to_string())
+            }
+        }
+
+        let mut graph =
+            Graph::<Node, OptionalKeyEdge, NodeId>::new(Some(test_dir.into()))
+                .unwrap();
+        graph.set_multigraph(true).unwrap();
+
+        let node1 = Node(Uuid::new_v4());
+        let node2 = Node(Uuid::new_v4());
+
+        graph.add_node(node1).unwrap();
+        graph.add_node(node2).unwrap();
+        graph
+            .add_edge(node1.into(), node2.into(), OptionalKeyEdge(None))
+            .unwrap();
+        graph
+            .add_edge(node1.into(), node2.into(), OptionalKeyEdge(Some(1)))
+            .unwrap();
+        graph
+            .add_edge(node2.into(), node1.into(), OptionalKeyEdge(Some(2)))
+            .unwrap();
+
+        assert!(matches!(
+            graph
+                .remove_node_with_edges(node1.into(), DeletionPolicy::Restrict),
+            Err(StoreError::NodeHasEdges)
+        ));
+        assert!(graph.has_node(&node1.into()).unwrap());
+
+        // the key-less edge is unambiguous once its parallel edge is gone
+        let applied = graph
+            .remove_node_with_edges(node1.into(), DeletionPolicy::Cascade)
+            .unwrap();
+        let kinds: Vec<&str> =
+            applied.iter().map(|(_, m, _)| m.name()).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                "RemoveEdgeByKey",
+                "RemoveEdgeByKey",
+                "RemoveEdge",
+                "RemoveNode"
+            ]
+        );
+        assert!(matches!(
+            applied.last(),
+            Some((_, _, GraphResponse::Node(node))) if *node == node1
+        ));
+        assert!(!graph.has_node(&node1.into()).unwrap());
+        assert!(graph.get_edges().unwrap().is_empty());
+
+        // the logged removals apply alike on replicas
+        let mut replica = Graph::<Node, OptionalKeyEdge, NodeId>::new(Some(
+            replica_dir.into(),
+        ))
+        .unwrap();
+        replica.set_multigraph(true).unwrap();
+        replica.add_node(node1).unwrap();
+        replica.add_node(node2).unwrap();
+        replica
+            .add_edge(node1.into(), node2.into(), OptionalKeyEdge(None))
+            .unwrap();
+        replica
+            .add_edge(node1.into(), node2.into(), OptionalKeyEdge(Some(1)))
+            .unwrap();
+        replica
+            .add_edge(node2.into(), node1.into(), OptionalKeyEdge(Some(2)))
+            .unwrap();
+        for (hash, mutation, _) in applied {
+            replica.apply_mutation(mutation, Some(&hash)).unwrap();
+        }
+        assert!(!replica.has_node(&node1.into()).unwrap());
+        assert!(replica.get_edges().unwrap().is_empty());
+
+        if std::path::Path::new(root_test_path).is_dir() {
+            std::fs::remove_dir_all(root_test_path).unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_remove_node_policies_ok() {
+        let test_dir = "test-data/test_remove_node_policies_ok";
+        if std::path::Path::new(test_dir).is_dir() {
+            std::fs::remove_dir_all(test_dir).unwrap();
+        }
+
+        let mut graph =
+            Graph::<Node, Edge, NodeId>::new(Some(test_dir.into())).unwrap();
+
+        let node1 = Node(Uuid::new_v4());
+        let node2 = Node(Uuid::new_v4());
+        let node3 = Node(Uuid::new_v4());
+
+        graph.add_node(node1).unwrap();
+        graph.add_node(node2).unwrap();
+        graph.add_node(node3).unwrap();
+        graph.add_edge(node1.into(), node2.into(), Edge(1)).unwrap();
+        graph.add_edge(node3.into(), node1.into(), Edge(2)).unwrap();
+
+        assert_eq!(
+            graph.remove_node(node1.into(), DeletionPolicy::Restrict),
+            Err(StoreError::NodeHasEdges)
+        );
+        assert!(graph.has_node(&node1.into()).unwrap());
+
+        let (node, mut edges) = graph
+            .remove_node(node1.into(), DeletionPolicy::Cascade)
+            .unwrap();
+        edges.sort_by_key(|(_, _, edge)| edge.0);
+        assert_eq!(node, node1);
+        assert_eq!(
+            edges,
+            vec![
+                (node1.into(), node2.into(), Edge(1)),
+                (node3.into(), node1.into(), Edge(2)),
+            ]
+        );
+        assert!(graph.get_edges().unwrap().is_empty());
+
+        assert_eq!(
+            graph.remove_node(node2.into(), DeletionPolicy::Restrict),
+            Ok((node2, Vec::new()))
+        );
+
+        if std::path::Path::new(test_dir).is_dir() {
+            std::fs::remove_dir_all(test_dir).unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_schema_constraint_violation_nok() {
+        let test_dir = "test-data/test_schema_constraint_violation_nok";
+        if std::path::Path::new(test_dir).is_dir() {
+            std::fs::remove_dir_all(test_dir).unwrap();
+        }
+
+        struct NoSelfLoops;
+
+        impl GraphSchema<Node, Edge> for NoSelfLoops {
+            fn validate_node(&self, node: &Node) -> Result<(), String> {
+                match node.0.is_nil() {
+                    true => Err("nil node".to_string()),
+                    false => Ok(()),
+                }
+            }
+
+            fn validate_edge(
+                &self,
+                from: &Node,
+                to: &Node,
+                _: &Edge,
+            ) -> Result<(), String> {
+                match from == to {
+                    true => Err("self loop".to_string()),
+                    false => Ok(()),
+                }
+            }
+        }
+
+        let mut graph =
+            Graph::<Node, Edge, NodeId>::new(Some(test_dir.into())).unwrap();
+        graph.set_schema(Schema::new(NoSelfLoops));
+
+        let node1 = Node(Uuid::new_v4());
+        let node2 = Node(Uuid::new_v4());
+
+        assert_eq!(
+            graph.add_node(Node(Uuid::nil())),
+            Err(StoreError::ConstraintViolation("nil node".to_string()))
+        );
+        graph.add_node(node1).unwrap();
+        graph.add_node(node2).unwrap();
+
+        assert_eq!(
+            graph.add_edge(node1.into(), node1.into(), Edge(1)),
+            Err(StoreError::ConstraintViolation("self loop".to_string()))
+        );
+        graph.add_edge(node1.into(), node2.into(), Edge(1)).unwrap();
+        assert_eq!(graph.get_nodes().unwrap().len(), 2);
+
+        if std::path::Path::new(test_dir).is_dir() {
+            std::fs::remove_dir_all(test_dir).unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_unique_keys_resolved_by_hash_ok() {
+        let test_dir = "test-data/test_unique_keys_resolved_by_hash_ok";
+        if std::path::Path::new(test_dir).is_dir() {
+            std::fs::remove_dir_all(test_dir).unwrap();
+        }
+
+        #[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+        struct User {
+            email: String,
+            id: Uuid,
+        }
+
+        impl GraphNode for User {
+            fn unique_keys(&self) -> Vec<(&'static str, IndexKey)> {
+                vec![("email", self.email.as_str().into())]
+            }
+        }
+
+        impl From<User> for NodeId {
+            fn from(node: User) -> Self {
+                Self(node.id)
+            }
+        }
+
+        let user = |email: &str| User {
+            email: email.to_string(),
+            id: Uuid::new_v4(),
+        };
+
+        let mut graph =
+            Graph::<User, Edge, NodeId>::new(Some(test_dir.into())).unwrap();
+
+        let user1 = user("a@example.com");
+        let user2 = user("a@example.com");
+        let user3 = user("a@example.com");
+
+        graph.add_node_with_hash(user1.clone(), "2").unwrap();
+        assert_eq!(
+            graph.add_node_with_hash(user2.clone(), "3"),
+            Err(StoreError::ConflictUniqueKey("email".to_string()))
+        );
+
+        assert!(graph.unique_key_evictions(&user2, "3").is_empty());
+
+        // an earlier mutation evicts the current holder of the key by
+        // mutations committed ahead of it
+        assert_eq!(
+            graph.add_node_with_hash(user3.clone(), "1"),
+            Err(StoreError::ConflictUniqueKey("email".to_string()))
+        );
+        let evictions = graph.unique_key_evictions(&user3, "1");
+        assert_eq!(evictions.len(), 1);
+        assert!(matches!(
+            &evictions[0],
+            GraphMutation::RemoveNode(key) if key == &user1.clone().into()
+        ));
+        for eviction in evictions {
+            graph.apply_mutation(eviction, None).unwrap();
+        }
+        graph.add_node_with_hash(user3.clone(), "1").unwrap();
+        assert!(!graph.has_node(&user1.clone().into()).unwrap());
+        assert!(graph.has_node(&user3.clone().into()).unwrap());
+
+        let mut graph =
+            Graph::<User, Edge, NodeId>::new(Some(test_dir.into())).unwrap();
+        assert_eq!(
+            graph.add_node_with_hash(user1.clone(), "2"),
+            Err(StoreError::ConflictUniqueKey("email".to_string()))
+        );
+        graph.add_node(user("b@example.com")).unwrap();
+
+        // claims loaded without their hashes are never evicted until
+        // restored from the mutations log
+        std::fs::remove_file(format!(
+            "{test_dir}/{UNIQUE_CONSTRAINTS_FILE_NAME}"
+        ))
+        .unwrap();
+        let mut graph =
+            Graph::<User, Edge, NodeId>::new(Some(test_dir.into())).unwrap();
+        let user4 = user("a@example.com");
+        assert!(graph.unique_key_evictions(&user4, "0").is_empty());
+
+        let log_entry = |hash: &str, mutation| MutationsLogMutation {
+            hash: hash.to_string(),
+            origin: String::new(),
+            mutation,
+            span: tracing::Span::none(),
+        };
+        graph
+            .restore_unique_key_hashes(vec![
+                log_entry("2", GraphMutation::AddNode(user1.clone())),
+                log_entry("1-0", GraphMutation::RemoveNode(user1.into())),
+                log_entry("1", GraphMutation::AddNode(user3.clone())),
+            ])
+            .unwrap();
+        assert!(graph.unknown_unique_hashes.is_empty());
+        assert!(matches!(
+            graph.unique_key_evictions(&user4, "0").as_slice(),
+            [GraphMutation::RemoveNode(key)] if key == &user3.clone().into()
+        ));
+        assert!(graph.unique_key_evictions(&user4, "4").is_empty());
+
+        if std::path::Path::new(test_dir).is_dir() {
+            std::fs::remove_dir_all(test_dir).unwrap();
+        }
+    }
 }
@@ -2,10 +2,14 @@ use std::fmt::{Debug, Display};
 use std::fs;
 use std::marker::PhantomData;
 use std::path::Path;
+use std::time::SystemTime;
 
 use petgraph::stable_graph::StableGraph;
 use petgraph::Directed;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 
+use crate::metrics;
 use crate::{GraphEdge, GraphNode, StoreError};
 
 const DEFAULT_STORE_PATH: &str = "data/graph_store/graph";
@@ -54,6 +58,7 @@ where
         &self,
         data: StableGraph<N, E, Directed>,
     ) -> Result<StableGraph<N, E, Directed>, StoreError> {
+        let _timer = metrics::SNAPSHOT_WRITE_DURATION.start_timer();
         let store_filepath = Self::get_filepath(self.store_path.clone());
 
         let encoded = bincode::serialize(&data)
@@ -86,6 +91,49 @@ where
         Ok(decoded)
     }
 
+    /// Saves data derived from the graph, like indexes, next to `data.bin`.
+    pub fn save_derived_to_file<T: Serialize>(
+        &self,
+        file_name: &str,
+        data: &T,
+    ) -> Result<(), StoreError> {
+        let filepath = format!("{}/{}", self.store_path, file_name);
+
+        let encoded = bincode::serialize(data)
+            .map_err(|err| StoreError::Serde(err.to_string()))?;
+
+        savefile::save_file(filepath, 0, &encoded)
+            .map_err(|err| StoreError::FileSaveError(err.to_string()))
+    }
+
+    pub fn load_derived_from_file<T: DeserializeOwned>(
+        &self,
+        file_name: &str,
+    ) -> Result<Option<T>, StoreError> {
+        let filepath = format!("{}/{}", self.store_path, file_name);
+
+        if !Path::new(&filepath).exists() {
+            return Ok(None);
+        }
+
+        let loaded_bincode: Vec<u8> = savefile::load_file(filepath, 0)
+            .map_err(|err| StoreError::FileLoadError(err.to_string()))?;
+
+        let decoded = bincode::deserialize(&loaded_bincode[..])
+            .map_err(|err| StoreError::FileDecodeError(err.to_string()))?;
+
+        Ok(Some(decoded))
+    }
+
+    /// Size in bytes and modification time of the saved graph, `None` if
+    /// it was not saved yet.
+    pub fn snapshot_metadata(&self) -> Option<(u64, SystemTime)> {
+        let store_filepath = Self::get_filepath(self.store_path.clone());
+        let metadata = fs::metadata(store_filepath).ok()?;
+
+        Some((metadata.len(), metadata.modified().ok()?))
+    }
+
     //
     // private methods
     //
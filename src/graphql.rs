@@ -0,0 +1,940 @@
+use std::net::SocketAddr;
+
+use actix_web::{guard, web, App, HttpRequest, HttpResponse, HttpServer};
+use async_graphql::dynamic::{
+    Enum, Field, FieldFuture, FieldValue, InputValue, Object, ObjectAccessor,
+    ResolverContext, Scalar, Schema, SchemaError, Subscription,
+    SubscriptionField, SubscriptionFieldFuture, TypeRef,
+};
+use async_graphql::{Error, Name, Value};
+use async_graphql_actix_web::{
+    GraphQLRequest, GraphQLResponse, GraphQLSubscription,
+};
+use futures_util::StreamExt;
+
+use petgraph::Direction;
+use serde::Serialize;
+
+use crate::{
+    AppliedMutation, DeletionPolicy, GraphDatabase, GraphEdge, GraphNode,
+    GraphNodeIndex, MutationKind, StoreError, SubscriptionFilter,
+};
+
+/// GraphQL server over a [`GraphDatabase`], served on `/graphql` with
+/// subscriptions over websockets.
+///
+/// The `Node` and `Edge` types get a field for every field of the serde
+/// representation of the defaults of `N` and `E`. Fields the crate adds are
+/// prefixed with `_` to not collide with them: `_key`, `_json`,
+/// `_neighbors(dir)` and `_edges(dir)` on nodes and `_from`, `_to` and
+/// `_json` on edges. Keys and payloads in arguments are `JSON` scalars.
+pub struct GraphqlServer;
+
+impl GraphqlServer {
+    pub fn run<N, E, I>(
+        address: SocketAddr,
+        database: GraphDatabase<N, E, I>,
+    ) -> Result<(), StoreError>
+    where
+        N: GraphNode + Unpin + 'static,
+        E: GraphEdge + Unpin + 'static,
+        I: GraphNodeIndex + From<N> + Unpin + 'static,
+    {
+        let schema = web::Data::new(
+            build_schema(database)
+                .map_err(|err| StoreError::ServerError(err.to_string()))?,
+        );
+
+        let server = HttpServer::new(move || {
+            App::new()
+                .app_data(schema.clone())
+                .route("/graphql", web::post().to(execute))
+                .route(
+                    "/graphql",
+                    web::get()
+                        .guard(guard::Header("upgrade", "websocket"))
+                        .to(subscribe),
+                )
+        })
+        .disable_signals()
+        .bind(address)
+        .map_err(|err| StoreError::ServerError(err.to_string()))?
+        .run();
+
+        tokio::spawn(async move {
+            if let Err(err) = server.await {
+                log::error!(
+                    "Error while running GraphqlServer. Error: '{err}'"
+                );
+            }
+        });
+
+        Ok(())
+    }
+}
+
+async fn execute(
+    schema: web::Data<Schema>,
+    request: GraphQLRequest,
+) -> GraphQLResponse {
+    schema.execute(request.into_inner()).await.into()
+}
+
+async fn subscribe(
+    schema: web::Data<Schema>,
+    request: HttpRequest,
+    payload: web::Payload,
+) -> actix_web::Result<HttpResponse> {
+    GraphQLSubscription::new(Schema::clone(&schema)).start(&request, payload)
+}
+
+/// Node resolved by the `Node` type.
+struct NodeValue<I> {
+    key: I,
+    json: serde_json::Value,
+}
+
+/// Edge resolved by the `Edge` type.
+struct EdgeValue<I> {
+    from: I,
+    to: I,
+    json: serde_json::Value,
+}
+
+fn build_schema<N, E, I>(
+    database: GraphDatabase<N, E, I>,
+) -> Result<Schema, SchemaError>
+where
+    N: GraphNode + Unpin + 'static,
+    E: GraphEdge + Unpin + 'static,
+    I: GraphNodeIndex + From<N> + Unpin + 'static,
+{
+    let json = Scalar::new("JSON");
+    let direction =
+        Enum::new("Direction").items(["OUTGOING", "INCOMING", "BOTH"]);
+    let policy =
+        Enum::new("DeletionPolicy").items(["RESTRICT", "DETACH", "CASCADE"]);
+    let mutation_kind = Enum::new("MutationKind").items([
+        "ADD_EDGE",
+        "REMOVE_EDGE",
+        "ADD_NODE",
+        "REMOVE_NODE",
+    ]);
+
+    Schema::build("Query", Some("Mutation"), Some("Subscription"))
+        .register(json)
+        .register(direction)
+        .register(policy)
+        .register(mutation_kind)
+        .register(node_type::<N, E, I>())
+        .register(edge_type::<N, E, I>())
+        .register(applied_mutation_type::<N, E, I>())
+        .register(query_type::<N, E, I>())
+        .register(mutation_type::<N, E, I>())
+        .register(subscription_type::<N, E, I>())
+        .data(database)
+        .finish()
+}
+
+//
+// types
+//
+
+fn node_type<N, E, I>() -> Object
+where
+    N: GraphNode + Unpin + 'static,
+    E: GraphEdge + Unpin + 'static,
+    I: GraphNodeIndex + From<N> + Unpin + 'static,
+{
+    let node = serde_fields(&N::default()).into_iter().fold(
+        Object::new("Node"),
+        |object, (name, ty)| {
+            object.field(Field::new(name.clone(), ty, move |ctx| {
+                let name = name.clone();
+                FieldFuture::new(async move {
+                    let node =
+                        ctx.parent_value.try_downcast_ref::<NodeValue<I>>()?;
+                    Ok(node.json.get(&name).cloned().map(json_field))
+                })
+            }))
+        },
+    );
+
+    node.field(Field::new("_key", TypeRef::named_nn("JSON"), |ctx| {
+        FieldFuture::new(async move {
+            let node = ctx.parent_value.try_downcast_ref::<NodeValue<I>>()?;
+            Ok(Some(json_field(to_json(&node.key)?)))
+        })
+    }))
+    .field(Field::new("_json", TypeRef::named_nn("JSON"), |ctx| {
+        FieldFuture::new(async move {
+            let node = ctx.parent_value.try_downcast_ref::<NodeValue<I>>()?;
+            Ok(Some(json_field(node.json.clone())))
+        })
+    }))
+    .field(
+        Field::new("_neighbors", TypeRef::named_nn_list_nn("Node"), |ctx| {
+            FieldFuture::new(async move {
+                let database = database::<N, E, I>(&ctx)?;
+                let node =
+                    ctx.parent_value.try_downcast_ref::<NodeValue<I>>()?;
+                let key = node.key.clone();
+
+                let nodes = match direction_arg(&ctx.args, None)? {
+                    Some(dir) => database.get_neighbors_dir(key, dir).await,
+                    None => database.get_neighbors_und(key).await,
+                }
+                .map_err(store_error)?;
+
+                Ok(Some(FieldValue::list(
+                    nodes
+                        .into_iter()
+                        .map(node_value::<N, I>)
+                        .collect::<Result<Vec<_>, _>>()?,
+                )))
+            })
+        })
+        .argument(
+            InputValue::new("dir", TypeRef::named("Direction"))
+                .default_value(Value::Enum(Name::new("BOTH"))),
+        ),
+    )
+    .field(
+        Field::new("_edges", TypeRef::named_nn_list_nn("Edge"), |ctx| {
+            FieldFuture::new(async move {
+                let database = database::<N, E, I>(&ctx)?;
+                let node =
+                    ctx.parent_value.try_downcast_ref::<NodeValue<I>>()?;
+                let key = node.key.clone();
+
+                let dirs = match direction_arg(
+                    &ctx.args,
+                    Some(Direction::Outgoing),
+                )? {
+                    Some(dir) => vec![dir],
+                    None => vec![Direction::Outgoing, Direction::Incoming],
+                };
+
+                let mut edges = Vec::new();
+                for dir in dirs {
+                    for (other, edge) in database
+                        .get_incident_edges(key.clone(), dir)
+                        .await
+                        .map_err(store_error)?
+                    {
+                        let (from, to) = match dir {
+                            Direction::Outgoing => (key.clone(), other),
+                            Direction::Incoming => (other, key.clone()),
+                        };
+                        edges.push(edge_value(from, to, &edge)?);
+                    }
+                }
+
+                Ok(Some(FieldValue::list(edges)))
+            })
+        })
+        .argument(
+            InputValue::new("dir", TypeRef::named("Direction"))
+                .default_value(Value::Enum(Name::new("OUTGOING"))),
+        ),
+    )
+}
+
+fn edge_type<N, E, I>() -> Object
+where
+    N: GraphNode + Unpin + 'static,
+    E: GraphEdge + Unpin + 'static,
+    I: GraphNodeIndex + From<N> + Unpin + 'static,
+{
+    let edge = serde_fields(&E::default()).into_iter().fold(
+        Object::new("Edge"),
+        |object, (name, ty)| {
+            object.field(Field::new(name.clone(), ty, move |ctx| {
+                let name = name.clone();
+                FieldFuture::new(async move {
+                    let edge =
+                        ctx.parent_value.try_downcast_ref::<EdgeValue<I>>()?;
+                    Ok(edge.json.get(&name).cloned().map(json_field))
+                })
+            }))
+        },
+    );
+
+    edge.field(Field::new("_from", TypeRef::named_nn("Node"), |ctx| {
+        FieldFuture::new(async move {
+            let database = database::<N, E, I>(&ctx)?;
+            let edge = ctx.parent_value.try_downcast_ref::<EdgeValue<I>>()?;
+            let node = database
+                .get_node(edge.from.clone())
+                .await
+                .map_err(store_error)?;
+
+            Ok(Some(node_value::<N, I>(node)?))
+        })
+    }))
+    .field(Field::new("_to", TypeRef::named_nn("Node"), |ctx| {
+        FieldFuture::new(async move {
+            let database = database::<N, E, I>(&ctx)?;
+            let edge = ctx.parent_value.try_downcast_ref::<EdgeValue<I>>()?;
+            let node = database
+                .get_node(edge.to.clone())
+                .await
+                .map_err(store_error)?;
+
+            Ok(Some(node_value::<N, I>(node)?))
+        })
+    }))
+    .field(Field::new("_json", TypeRef::named_nn("JSON"), |ctx| {
+        FieldFuture::new(async move {
+            let edge = ctx.parent_value.try_downcast_ref::<EdgeValue<I>>()?;
+            Ok(Some(json_field(edge.json.clone())))
+        })
+    }))
+}
+
+fn applied_mutation_type<N, E, I>() -> Object
+where
+    N: GraphNode + Unpin + 'static,
+    E: GraphEdge + Unpin + 'static,
+    I: GraphNodeIndex + From<N> + Unpin + 'static,
+{
+    fn applied<'a, N, E, I>(
+        ctx: &ResolverContext<'a>,
+    ) -> Result<&'a AppliedMutation<N, E, I>, Error>
+    where
+        N: GraphNode + Unpin + 'static,
+        E: GraphEdge + Unpin + 'static,
+        I: GraphNodeIndex + From<N> + Unpin + 'static,
+    {
+        ctx.parent_value
+            .try_downcast_ref::<AppliedMutation<N, E, I>>()
+    }
+
+    Object::new("AppliedMutation")
+        .field(Field::new(
+            "cursor",
+            TypeRef::named_nn(TypeRef::INT),
+            |ctx| {
+                FieldFuture::new(async move {
+                    let applied = applied::<N, E, I>(&ctx)?;
+                    Ok(Some(FieldValue::value(applied.cursor)))
+                })
+            },
+        ))
+        .field(Field::new(
+            "hash",
+            TypeRef::named_nn(TypeRef::STRING),
+            |ctx| {
+                FieldFuture::new(async move {
+                    let applied = applied::<N, E, I>(&ctx)?;
+                    Ok(Some(FieldValue::value(applied.hash.clone())))
+                })
+            },
+        ))
+        .field(Field::new(
+            "origin",
+            TypeRef::named_nn(TypeRef::STRING),
+            |ctx| {
+                FieldFuture::new(async move {
+                    let applied = applied::<N, E, I>(&ctx)?;
+                    Ok(Some(FieldValue::value(applied.origin.clone())))
+                })
+            },
+        ))
+        .field(Field::new(
+            "kind",
+            TypeRef::named_nn("MutationKind"),
+            |ctx| {
+                FieldFuture::new(async move {
+                    let applied = applied::<N, E, I>(&ctx)?;
+                    let kind = match applied.mutation.kind() {
+                        MutationKind::AddEdge => "ADD_EDGE",
+                        MutationKind::RemoveEdge => "REMOVE_EDGE",
+                        MutationKind::AddNode => "ADD_NODE",
+                        MutationKind::RemoveNode => "REMOVE_NODE",
+                    };
+                    Ok(Some(FieldValue::value(Value::Enum(Name::new(kind)))))
+                })
+            },
+        ))
+        .field(Field::new(
+            "keys",
+            TypeRef::named_nn_list_nn("JSON"),
+            |ctx| {
+                FieldFuture::new(async move {
+                    let applied = applied::<N, E, I>(&ctx)?;
+                    Ok(Some(json_field(to_json(&applied.mutation.keys())?)))
+                })
+            },
+        ))
+        .field(Field::new("mutation", TypeRef::named_nn("JSON"), |ctx| {
+            FieldFuture::new(async move {
+                let applied = applied::<N, E, I>(&ctx)?;
+                Ok(Some(json_field(to_json(&applied.mutation)?)))
+            })
+        }))
+        .field(Field::new(
+            "error",
+            TypeRef::named(TypeRef::STRING),
+            |ctx| {
+                FieldFuture::new(async move {
+                    let applied = applied::<N, E, I>(&ctx)?;
+                    Ok(applied
+                        .result
+                        .as_ref()
+                        .err()
+                        .map(|err| FieldValue::value(err.to_string())))
+                })
+            },
+        ))
+}
+
+//
+// root types
+//
+
+fn query_type<N, E, I>() -> Object
+where
+    N: GraphNode + Unpin + 'static,
+    E: GraphEdge + Unpin + 'static,
+    I: GraphNodeIndex + From<N> + Unpin + 'static,
+{
+    Object::new("Query")
+        .field(
+            Field::new("node", TypeRef::named("Node"), |ctx| {
+                FieldFuture::new(async move {
+                    let database = database::<N, E, I>(&ctx)?;
+                    let key: I = ctx.args.try_get("key")?.deserialize()?;
+
+                    match database.get_node(key).await {
+                        Ok(node) => Ok(Some(node_value::<N, I>(node)?)),
+                        Err(StoreError::NodeNotFound) => Ok(None),
+                        Err(err) => Err(store_error(err)),
+                    }
+                })
+            })
+            .argument(InputValue::new("key", TypeRef::named_nn("JSON"))),
+        )
+        .field(Field::new(
+            "nodes",
+            TypeRef::named_nn_list_nn("Node"),
+            |ctx| {
+                FieldFuture::new(async move {
+                    let database = database::<N, E, I>(&ctx)?;
+                    let nodes =
+                        database.get_nodes().await.map_err(store_error)?;
+
+                    Ok(Some(FieldValue::list(
+                        nodes
+                            .into_iter()
+                            .map(node_value::<N, I>)
+                            .collect::<Result<Vec<_>, _>>()?,
+                    )))
+                })
+            },
+        ))
+        .field(
+            Field::new("edge", TypeRef::named("Edge"), |ctx| {
+                FieldFuture::new(async move {
+                    let database = database::<N, E, I>(&ctx)?;
+                    let from: I = ctx.args.try_get("from")?.deserialize()?;
+                    let to: I = ctx.args.try_get("to")?.deserialize()?;
+
+                    match database.get_edge(from.clone(), to.clone()).await {
+                        Ok(edge) => Ok(Some(edge_value(from, to, &edge)?)),
+                        Err(StoreError::EdgeNotFound) => Ok(None),
+                        Err(err) => Err(store_error(err)),
+                    }
+                })
+            })
+            .argument(InputValue::new("from", TypeRef::named_nn("JSON")))
+            .argument(InputValue::new("to", TypeRef::named_nn("JSON"))),
+        )
+        .field(Field::new(
+            "edges",
+            TypeRef::named_nn_list_nn("Edge"),
+            |ctx| {
+                FieldFuture::new(async move {
+                    let database = database::<N, E, I>(&ctx)?;
+                    let edges = database
+                        .get_edges_with_endpoints()
+                        .await
+                        .map_err(store_error)?;
+
+                    Ok(Some(FieldValue::list(
+                        edges
+                            .into_iter()
+                            .map(|(from, to, edge)| edge_value(from, to, &edge))
+                            .collect::<Result<Vec<_>, _>>()?,
+                    )))
+                })
+            },
+        ))
+}
+
+fn mutation_type<N, E, I>() -> Object
+where
+    N: GraphNode + Unpin + 'static,
+    E: GraphEdge + Unpin + 'static,
+    I: GraphNodeIndex + From<N> + Unpin + 'static,
+{
+    Object::new("Mutation")
+        .field(
+            Field::new("addNode", TypeRef::named_nn("Node"), |ctx| {
+                FieldFuture::new(async move {
+                    let database = database::<N, E, I>(&ctx)?;
+                    let node: N = ctx.args.try_get("node")?.deserialize()?;
+                    let node =
+                        database.add_node(node).await.map_err(store_error)?;
+
+                    Ok(Some(node_value::<N, I>(node)?))
+                })
+            })
+            .argument(InputValue::new("node", TypeRef::named_nn("JSON"))),
+        )
+        .field(
+            Field::new("removeNode", TypeRef::named_nn("Node"), |ctx| {
+                FieldFuture::new(async move {
+                    let database = database::<N, E, I>(&ctx)?;
+                    let key: I = ctx.args.try_get("key")?.deserialize()?;
+                    let policy = match ctx.args.get("policy") {
+                        Some(policy) if !policy.is_null() => {
+                            match policy.enum_name()? {
+                                "DETACH" => DeletionPolicy::Detach,
+                                "CASCADE" => DeletionPolicy::Cascade,
+                                _ => DeletionPolicy::Restrict,
+                            }
+                        }
+                        _ => DeletionPolicy::Restrict,
+                    };
+
+                    let (node, _) = database
+                        .remove_node(key, policy)
+                        .await
+                        .map_err(store_error)?;
+
+                    Ok(Some(node_value::<N, I>(node)?))
+                })
+            })
+            .argument(InputValue::new("key", TypeRef::named_nn("JSON")))
+            .argument(
+                InputValue::new("policy", TypeRef::named("DeletionPolicy"))
+                    .default_value(Value::Enum(Name::new("RESTRICT"))),
+            ),
+        )
+        .field(
+            Field::new("addEdge", TypeRef::named_nn("Edge"), |ctx| {
+                FieldFuture::new(async move {
+                    let database = database::<N, E, I>(&ctx)?;
+                    let from: I = ctx.args.try_get("from")?.deserialize()?;
+                    let to: I = ctx.args.try_get("to")?.deserialize()?;
+                    let edge: E = ctx.args.try_get("edge")?.deserialize()?;
+
+                    database
+                        .add_edge(from.clone(), to.clone(), edge.clone())
+                        .await
+                        .map_err(store_error)?;
+
+                    Ok(Some(edge_value(from, to, &edge)?))
+                })
+            })
+            .argument(InputValue::new("from", TypeRef::named_nn("JSON")))
+            .argument(InputValue::new("to", TypeRef::named_nn("JSON")))
+            .argument(InputValue::new("edge", TypeRef::named_nn("JSON"))),
+        )
+        .field(
+            Field::new("removeEdge", TypeRef::named_nn("Edge"), |ctx| {
+                FieldFuture::new(async move {
+                    let database = database::<N, E, I>(&ctx)?;
+                    let from: I = ctx.args.try_get("from")?.deserialize()?;
+                    let to: I = ctx.args.try_get("to")?.deserialize()?;
+
+                    let edge = database
+                        .remove_edge(from.clone(), to.clone())
+                        .await
+                        .map_err(store_error)?;
+
+                    Ok(Some(edge_value(from, to, &edge)?))
+                })
+            })
+            .argument(InputValue::new("from", TypeRef::named_nn("JSON")))
+            .argument(InputValue::new("to", TypeRef::named_nn("JSON"))),
+        )
+}
+
+fn subscription_type<N, E, I>() -> Subscription
+where
+    N: GraphNode + Unpin + 'static,
+    E: GraphEdge + Unpin + 'static,
+    I: GraphNodeIndex + From<N> + Unpin + 'static,
+{
+    Subscription::new("Subscription").field(
+        SubscriptionField::new(
+            "mutations",
+            TypeRef::named_nn("AppliedMutation"),
+            |ctx| {
+                SubscriptionFieldFuture::new(async move {
+                    let database = database::<N, E, I>(&ctx)?;
+
+                    let mut filter = SubscriptionFilter::default();
+                    if let Some(keys) = ctx.args.get("keys") {
+                        if !keys.is_null() {
+                            filter.keys = keys.deserialize()?;
+                        }
+                    }
+                    if let Some(kinds) = ctx.args.get("kinds") {
+                        if !kinds.is_null() {
+                            for kind in kinds.list()?.iter() {
+                                filter.kinds.push(match kind.enum_name()? {
+                                    "ADD_EDGE" => MutationKind::AddEdge,
+                                    "REMOVE_EDGE" => MutationKind::RemoveEdge,
+                                    "ADD_NODE" => MutationKind::AddNode,
+                                    _ => MutationKind::RemoveNode,
+                                });
+                            }
+                        }
+                    }
+                    if let Some(after) = ctx.args.get("after") {
+                        if !after.is_null() {
+                            filter.after = Some(after.i64()?);
+                        }
+                    }
+
+                    let stream = database
+                        .subscribe(filter)
+                        .await
+                        .map_err(store_error)?;
+
+                    Ok(stream.map(|applied| {
+                        applied.map(FieldValue::owned_any).map_err(store_error)
+                    }))
+                })
+            },
+        )
+        .argument(InputValue::new("keys", TypeRef::named_nn_list("JSON")))
+        .argument(InputValue::new(
+            "kinds",
+            TypeRef::named_nn_list("MutationKind"),
+        ))
+        .argument(InputValue::new("after", TypeRef::named(TypeRef::INT))),
+    )
+}
+
+//
+// helpers
+//
+
+fn database<'a, N, E, I>(
+    ctx: &ResolverContext<'a>,
+) -> Result<&'a GraphDatabase<N, E, I>, Error>
+where
+    N: GraphNode + Unpin + 'static,
+    E: GraphEdge + Unpin + 'static,
+    I: GraphNodeIndex + From<N> + Unpin + 'static,
+{
+    ctx.ctx.data::<GraphDatabase<N, E, I>>()
+}
+
+/// The `dir` argument, `None` for `BOTH`.
+fn direction_arg(
+    args: &ObjectAccessor,
+    default: Option<Direction>,
+) -> Result<Option<Direction>, Error> {
+    match args.get("dir") {
+        Some(dir) if !dir.is_null() => match dir.enum_name()? {
+            "OUTGOING" => Ok(Some(Direction::Outgoing)),
+            "INCOMING" => Ok(Some(Direction::Incoming)),
+            _ => Ok(None),
+        },
+        _ => Ok(default),
+    }
+}
+
+/// GraphQL fields for the fields of the serde representation of `value`,
+/// typed after the JSON values of `value`.
+fn serde_fields<T: Serialize>(value: &T) -> Vec<(String, TypeRef)> {
+    let Ok(serde_json::Value::Object(fields)) = serde_json::to_value(value)
+    else {
+        return Vec::new();
+    };
+
+    fields
+        .into_iter()
+        .filter(|(name, _)| is_graphql_name(name) && !name.starts_with('_'))
+        .map(|(name, value)| {
+            let ty = match value {
+                serde_json::Value::Bool(_) => TypeRef::BOOLEAN,
+                serde_json::Value::Number(number) if number.is_f64() => {
+                    TypeRef::FLOAT
+                }
+                serde_json::Value::Number(_) => TypeRef::INT,
+                serde_json::Value::String(_) => TypeRef::STRING,
+                _ => "JSON",
+            };
+            (name, TypeRef::named(ty))
+        })
+        .collect()
+}
+
+fn is_graphql_name(name: &str) -> bool {
+    let mut chars = name.chars();
+
+    matches!(chars.next(), Some(c) if c == '_' || c.is_ascii_alphabetic())
+        && chars.all(|c| c == '_' || c.is_ascii_alphanumeric())
+}
+
+fn node_value<'a, N, I>(node: N) -> Result<FieldValue<'a>, Error>
+where
+    N: GraphNode + 'static,
+    I: GraphNodeIndex + From<N> + 'static,
+{
+    let json = to_json(&node)?;
+
+    Ok(FieldValue::owned_any(NodeValue {
+        key: I::from(node),
+        json,
+    }))
+}
+
+fn edge_value<'a, I, E>(
+    from: I,
+    to: I,
+    edge: &E,
+) -> Result<FieldValue<'a>, Error>
+where
+    I: GraphNodeIndex + 'static,
+    E: Serialize,
+{
+    Ok(FieldValue::owned_any(EdgeValue {
+        from,
+        to,
+        json: to_json(edge)?,
+    }))
+}
+
+fn json_field<'a>(json: serde_json::Value) -> FieldValue<'a> {
+    FieldValue::value(Value::from_json(json).unwrap_or(Value::Null))
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<serde_json::Value, Error> {
+    serde_json::to_value(value).map_err(|err| Error::new(err.to_string()))
+}
+
+fn store_error(err: StoreError) -> Error {
+    Error::new(err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use async_graphql::{Request, Variables};
+    use serde::Deserialize;
+    use serde_json::json;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::DatabaseConfig;
+
+    #[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+    struct Node {
+        id: Uuid,
+        name: String,
+    }
+
+    impl Node {
+        fn new(name: &str) -> Self {
+            Self {
+                id: Uuid::new_v4(),
+                name: name.to_string(),
+            }
+        }
+    }
+
+    impl GraphNode for Node {}
+
+    #[derive(
+        Debug,
+        Default,
+        Clone,
+        Copy,
+        Hash,
+        PartialEq,
+        Eq,
+        PartialOrd,
+        Ord,
+        Serialize,
+        Deserialize,
+    )]
+    struct Edge(usize);
+    impl GraphEdge for Edge {}
+
+    #[derive(
+        Debug,
+        Default,
+        Clone,
+        Copy,
+        Hash,
+        PartialEq,
+        Eq,
+        PartialOrd,
+        Ord,
+        Serialize,
+        Deserialize,
+    )]
+    struct NodeId(Uuid);
+
+    impl GraphNodeIndex for NodeId {}
+
+    impl From<Node> for NodeId {
+        fn from(node: Node) -> Self {
+            Self(node.id)
+        }
+    }
+
+    /// Data of the response to `query`, panicking on errors.
+    async fn execute(
+        schema: &Schema,
+        query: &str,
+        variables: serde_json::Value,
+    ) -> serde_json::Value {
+        let request =
+            Request::new(query).variables(Variables::from_json(variables));
+        let response = schema.execute(request).await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+
+        response.data.into_json().unwrap()
+    }
+
+    #[actix_rt::test]
+    async fn test_graphql_ok() {
+        let test_dir = "test-data/test_graphql_ok";
+        if std::path::Path::new(test_dir).is_dir() {
+            std::fs::remove_dir_all(test_dir).unwrap();
+        }
+
+        let mut config = DatabaseConfig::init();
+        config.set_store_path(test_dir.to_string());
+        config.set_server_url("http://127.0.0.1:4034".to_string());
+        config.set_initial_remote_addresses(Vec::new());
+        let database = GraphDatabase::<Node, Edge, NodeId>::run(config)
+            .await
+            .unwrap();
+        let schema = build_schema(database).unwrap();
+
+        let node1 = Node::new("a");
+        let node2 = Node::new("b");
+
+        for node in [&node1, &node2] {
+            let data = execute(
+                &schema,
+                "mutation($node: JSON!) { addNode(node: $node) { _key name } }",
+                json!({ "node": node }),
+            )
+            .await;
+            assert_eq!(
+                data,
+                json!({ "addNode": { "_key": node.id, "name": node.name } })
+            );
+        }
+
+        let data = execute(
+            &schema,
+            "mutation($from: JSON!, $to: JSON!) {
+                addEdge(from: $from, to: $to, edge: 1) { _json }
+            }",
+            json!({ "from": node1.id, "to": node2.id }),
+        )
+        .await;
+        assert_eq!(data, json!({ "addEdge": { "_json": 1 } }));
+
+        // nested neighborhoods in one round trip
+        let data = execute(
+            &schema,
+            "query($key: JSON!) {
+                node(key: $key) {
+                    name
+                    _neighbors(dir: OUTGOING) {
+                        name
+                        _edges(dir: INCOMING) { _from { name } }
+                    }
+                }
+            }",
+            json!({ "key": node1.id }),
+        )
+        .await;
+        assert_eq!(
+            data,
+            json!({ "node": {
+                "name": "a",
+                "_neighbors": [{
+                    "name": "b",
+                    "_edges": [{ "_from": { "name": "a" } }],
+                }],
+            }})
+        );
+
+        let data = execute(
+            &schema,
+            "query($key: JSON!) { node(key: $key) { name } }",
+            json!({ "key": Uuid::new_v4() }),
+        )
+        .await;
+        assert_eq!(data, json!({ "node": null }));
+
+        let data = execute(
+            &schema,
+            "{ edges { _from { name } _to { name } _json } }",
+            json!({}),
+        )
+        .await;
+        assert_eq!(
+            data,
+            json!({ "edges": [{
+                "_from": { "name": "a" },
+                "_to": { "name": "b" },
+                "_json": 1,
+            }]})
+        );
+
+        // restricted removals of nodes with edges fail
+        let request = Request::new(
+            "mutation($key: JSON!) { removeNode(key: $key) { name } }",
+        )
+        .variables(Variables::from_json(json!({ "key": node1.id })));
+        let response = schema.execute(request).await;
+        assert_eq!(
+            response.errors[0].message,
+            StoreError::NodeHasEdges.to_string()
+        );
+
+        let data = execute(
+            &schema,
+            "mutation($key: JSON!) {
+                removeNode(key: $key, policy: CASCADE) { name }
+            }",
+            json!({ "key": node1.id }),
+        )
+        .await;
+        assert_eq!(data, json!({ "removeNode": { "name": "a" } }));
+
+        // subscriptions replay the log after the given cursor
+        let mut stream = Box::pin(schema.execute_stream(Request::new(
+            "subscription {
+                mutations(after: 0, kinds: [ADD_NODE]) { kind keys error }
+            }",
+        )));
+        let response = stream.next().await.unwrap();
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+        assert_eq!(
+            response.data.into_json().unwrap(),
+            json!({ "mutations": {
+                "kind": "ADD_NODE",
+                "keys": [node1.id],
+                "error": null,
+            }})
+        );
+
+        if std::path::Path::new(test_dir).is_dir() {
+            std::fs::remove_dir_all(test_dir).unwrap();
+        }
+    }
+}
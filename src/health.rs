@@ -0,0 +1,101 @@
+use std::time::Duration;
+
+use actix::Addr;
+use tonic::server::NamedService;
+use tonic_health::server::HealthReporter;
+use tonic_health::ServingStatus;
+
+use crate::api::ApiServer;
+use crate::graph::{Graph, GraphStats, GraphStatsQuery};
+use crate::graph_api::graph_api_server::GraphApiServer;
+use crate::mutations_log::{MutationsLog, MutationsLogStatsQuery};
+use crate::remotes::Remotes;
+use crate::server::GraphServer;
+use crate::sync_graph::sync_graph_server::SyncGraphServer;
+use crate::{GraphEdge, GraphNode, GraphNodeIndex, StoreError};
+
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Checks that the actors are running and that the graph snapshot and the
+/// mutations log can still be read.
+pub async fn check<N, E, I>(
+    graph: &Addr<Graph<N, E, I>>,
+    mutations_log: &Addr<MutationsLog<N, E, I>>,
+    remotes: &Addr<Remotes<N, E, I>>,
+) -> Result<(), StoreError>
+where
+    N: GraphNode + Unpin + 'static,
+    E: GraphEdge + Unpin + 'static,
+    I: GraphNodeIndex + From<N> + Unpin + 'static,
+{
+    if !remotes.connected() {
+        return Err(StoreError::MailboxError("Remotes stopped".to_string()));
+    }
+
+    let GraphStats { snapshot, .. } = graph.send(GraphStatsQuery).await?;
+    if snapshot.is_none() {
+        return Err(StoreError::FileLoadError(
+            "Graph snapshot is missing".to_string(),
+        ));
+    }
+
+    mutations_log.send(MutationsLogStatsQuery).await??;
+
+    Ok(())
+}
+
+/// Sets the overall status and the status of the `SyncGraph` and `GraphApi`
+/// services.
+pub async fn set_status<N, E, I>(
+    reporter: &mut HealthReporter,
+    status: ServingStatus,
+) where
+    N: GraphNode + Unpin + 'static,
+    E: GraphEdge + Unpin + 'static,
+    I: GraphNodeIndex + From<N> + Unpin + 'static,
+{
+    let service_names = [
+        "",
+        <SyncGraphServer<GraphServer<N, E, I>> as NamedService>::NAME,
+        <GraphApiServer<ApiServer<N, E, I>> as NamedService>::NAME,
+    ];
+
+    for service_name in service_names {
+        reporter.set_service_status(service_name, status).await;
+    }
+}
+
+/// Periodically checks the database once the initial synchronization is
+/// done, reporting the services as serving while the check passes.
+pub fn spawn_monitor<N, E, I>(
+    mut reporter: HealthReporter,
+    graph: Addr<Graph<N, E, I>>,
+    mutations_log: Addr<MutationsLog<N, E, I>>,
+    remotes: Addr<Remotes<N, E, I>>,
+) where
+    N: GraphNode + Unpin + 'static,
+    E: GraphEdge + Unpin + 'static,
+    I: GraphNodeIndex + From<N> + Unpin + 'static,
+{
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(HEALTH_CHECK_INTERVAL);
+        let mut serving = None;
+
+        loop {
+            interval.tick().await;
+
+            let status = match check(&graph, &mutations_log, &remotes).await {
+                Ok(()) => ServingStatus::Serving,
+                Err(err) => {
+                    log::warn!("Health check failed. Error: '{err:?}'");
+                    ServingStatus::NotServing
+                }
+            };
+
+            if serving != Some(status) {
+                set_status::<N, E, I>(&mut reporter, status).await;
+                serving = Some(status);
+            }
+        }
+    });
+}
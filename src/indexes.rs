@@ -0,0 +1,412 @@
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::ops::Bound;
+
+use petgraph::stable_graph::{EdgeIndex, NodeIndex};
+use petgraph::Direction;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::{GraphNode, GraphNodeIndex};
+
+/// Key of a node in a secondary index, see [`GraphNode::index_keys`] and
+/// [`GraphNode::ordered_index_keys`].
+///
+/// Keys of different variants are ordered by variant, floats are ordered by
+/// [`f64::total_cmp`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum IndexKey {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Str(String),
+}
+
+impl IndexKey {
+    fn variant(&self) -> u8 {
+        match self {
+            Self::Bool(_) => 0,
+            Self::Int(_) => 1,
+            Self::Float(_) => 2,
+            Self::Str(_) => 3,
+        }
+    }
+}
+
+impl PartialEq for IndexKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for IndexKey {}
+
+impl PartialOrd for IndexKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for IndexKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Self::Bool(a), Self::Bool(b)) => a.cmp(b),
+            (Self::Int(a), Self::Int(b)) => a.cmp(b),
+            (Self::Float(a), Self::Float(b)) => a.total_cmp(b),
+            (Self::Str(a), Self::Str(b)) => a.cmp(b),
+            (a, b) => a.variant().cmp(&b.variant()),
+        }
+    }
+}
+
+impl Hash for IndexKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.variant().hash(state);
+
+        match self {
+            Self::Bool(value) => value.hash(state),
+            Self::Int(value) => value.hash(state),
+            Self::Float(value) => value.to_bits().hash(state),
+            Self::Str(value) => value.hash(state),
+        }
+    }
+}
+
+impl From<bool> for IndexKey {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+impl From<i64> for IndexKey {
+    fn from(value: i64) -> Self {
+        Self::Int(value)
+    }
+}
+
+impl From<i32> for IndexKey {
+    fn from(value: i32) -> Self {
+        Self::Int(value.into())
+    }
+}
+
+impl From<u32> for IndexKey {
+    fn from(value: u32) -> Self {
+        Self::Int(value.into())
+    }
+}
+
+impl From<f64> for IndexKey {
+    fn from(value: f64) -> Self {
+        Self::Float(value)
+    }
+}
+
+impl From<String> for IndexKey {
+    fn from(value: String) -> Self {
+        Self::Str(value)
+    }
+}
+
+impl From<&str> for IndexKey {
+    fn from(value: &str) -> Self {
+        Self::Str(value.to_string())
+    }
+}
+
+/// Total ordering of keys passed along with an [`OrderedRange`], keys don't
+/// have to implement `Ord` to be stored in the graph.
+pub type KeyOrdering<K> = fn(&K, &K) -> Ordering;
+
+/// Range of keys in ascending or descending order, limited to the first
+/// `limit` keys.
+#[derive(Debug, Clone)]
+pub struct OrderedRange<K> {
+    pub start: Bound<K>,
+    pub end: Bound<K>,
+    pub descending: bool,
+    pub limit: Option<usize>,
+}
+
+impl<K> OrderedRange<K> {
+    /// Whether `key` lies within the range given the ordering `cmp`.
+    pub fn contains_by(&self, key: &K, cmp: KeyOrdering<K>) -> bool {
+        let after_start = match &self.start {
+            Bound::Included(start) => cmp(key, start) != Ordering::Less,
+            Bound::Excluded(start) => cmp(key, start) == Ordering::Greater,
+            Bound::Unbounded => true,
+        };
+        let before_end = match &self.end {
+            Bound::Included(end) => cmp(key, end) != Ordering::Greater,
+            Bound::Excluded(end) => cmp(key, end) == Ordering::Less,
+            Bound::Unbounded => true,
+        };
+
+        after_start && before_end
+    }
+
+    /// Whether no key can lie within the range given the ordering `cmp`,
+    /// ranging over it would panic.
+    pub fn is_empty_by(&self, cmp: KeyOrdering<K>) -> bool {
+        match (&self.start, &self.end) {
+            (Bound::Included(start), Bound::Included(end)) => {
+                cmp(start, end) == Ordering::Greater
+            }
+            (
+                Bound::Included(start) | Bound::Excluded(start),
+                Bound::Included(end) | Bound::Excluded(end),
+            ) => cmp(start, end) != Ordering::Less,
+            _ => false,
+        }
+    }
+}
+
+/// Secondary indexes from the keys returned by [`GraphNode::index_keys`] and
+/// [`GraphNode::ordered_index_keys`] to the keys of the nodes.
+#[derive(Debug, Clone, Default)]
+pub struct SecondaryIndexes<I>
+where
+    I: GraphNodeIndex,
+{
+    indexes: HashMap<String, HashMap<IndexKey, HashSet<I>>>,
+    ordered_indexes: HashMap<String, BTreeMap<IndexKey, HashSet<I>>>,
+}
+
+impl<I> SecondaryIndexes<I>
+where
+    I: GraphNodeIndex,
+{
+    pub fn insert<N: GraphNode>(&mut self, key: &I, node: &N) {
+        for (name, index_key) in node.index_keys() {
+            self.indexes
+                .entry(name.to_string())
+                .or_default()
+                .entry(index_key)
+                .or_default()
+                .insert(key.clone());
+        }
+
+        for (name, index_key) in node.ordered_index_keys() {
+            self.ordered_indexes
+                .entry(name.to_string())
+                .or_default()
+                .entry(index_key)
+                .or_default()
+                .insert(key.clone());
+        }
+    }
+
+    pub fn remove<N: GraphNode>(&mut self, key: &I, node: &N) {
+        for (name, index_key) in node.index_keys() {
+            if let Some(index) = self.indexes.get_mut(name) {
+                if let Some(keys) = index.get_mut(&index_key) {
+                    keys.remove(key);
+
+                    if keys.is_empty() {
+                        index.remove(&index_key);
+                    }
+                }
+            }
+        }
+
+        for (name, index_key) in node.ordered_index_keys() {
+            if let Some(index) = self.ordered_indexes.get_mut(name) {
+                if let Some(keys) = index.get_mut(&index_key) {
+                    keys.remove(key);
+
+                    if keys.is_empty() {
+                        index.remove(&index_key);
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn find(&self, name: &str, index_key: &IndexKey) -> Vec<I> {
+        self.indexes
+            .get(name)
+            .and_then(|index| index.get(index_key))
+            .map(|keys| keys.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn find_range(
+        &self,
+        name: &str,
+        range: OrderedRange<IndexKey>,
+    ) -> Vec<I> {
+        let index = match self.ordered_indexes.get(name) {
+            Some(index) if !range.is_empty_by(IndexKey::cmp) => index,
+            _ => return Vec::new(),
+        };
+
+        let entries = index.range((range.start, range.end));
+        let entries: Box<dyn Iterator<Item = _>> = if range.descending {
+            Box::new(entries.rev())
+        } else {
+            Box::new(entries)
+        };
+
+        entries
+            .flat_map(|(_, keys)| keys.iter().cloned())
+            .take(range.limit.unwrap_or(usize::MAX))
+            .collect()
+    }
+}
+
+/// Claims of the keys returned by [`GraphNode::unique_keys`], each held by a
+/// node along with the hash of the mutation which added it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "I: Serialize",
+    deserialize = "I: DeserializeOwned"
+))]
+pub struct UniqueConstraints<I>
+where
+    I: GraphNodeIndex,
+{
+    claims: HashMap<(String, IndexKey), (I, String)>,
+}
+
+impl<I> UniqueConstraints<I>
+where
+    I: GraphNodeIndex,
+{
+    pub fn insert<N: GraphNode>(&mut self, key: &I, node: &N, hash: &str) {
+        for (name, unique_key) in node.unique_keys() {
+            self.claims.insert(
+                (name.to_string(), unique_key),
+                (key.clone(), hash.to_string()),
+            );
+        }
+    }
+
+    pub fn remove<N: GraphNode>(&mut self, key: &I, node: &N) {
+        for (name, unique_key) in node.unique_keys() {
+            let claim = (name.to_string(), unique_key);
+
+            if matches!(self.claims.get(&claim), Some((owner, _)) if owner == key)
+            {
+                self.claims.remove(&claim);
+            }
+        }
+    }
+
+    /// Name, key and mutation hash of the other nodes claiming any of the
+    /// unique keys of `node`.
+    pub fn get_owners<N: GraphNode>(
+        &self,
+        key: &I,
+        node: &N,
+    ) -> Vec<(String, I, String)> {
+        node.unique_keys()
+            .into_iter()
+            .filter_map(|(name, unique_key)| {
+                let (owner, hash) =
+                    self.claims.get(&(name.to_string(), unique_key))?;
+                (owner != key)
+                    .then(|| (name.to_string(), owner.clone(), hash.clone()))
+            })
+            .collect()
+    }
+
+    /// Mutation hash of the claims of `node`, if it holds any.
+    pub fn get_hash<N: GraphNode>(&self, key: &I, node: &N) -> Option<String> {
+        node.unique_keys()
+            .into_iter()
+            .find_map(|(name, unique_key)| {
+                match self.claims.get(&(name.to_string(), unique_key)) {
+                    Some((owner, hash)) if owner == key => Some(hash.clone()),
+                    _ => None,
+                }
+            })
+    }
+}
+
+/// Index from the types returned by [`crate::GraphEdge::edge_type`] to the
+/// edges, overall and per node and direction.
+#[derive(Debug, Clone, Default)]
+pub struct EdgeTypeIndex {
+    by_type: HashMap<String, HashSet<EdgeIndex>>,
+    by_node: HashMap<(NodeIndex, Direction, String), HashSet<EdgeIndex>>,
+}
+
+impl EdgeTypeIndex {
+    pub fn insert(
+        &mut self,
+        edge_type: &str,
+        edge: EdgeIndex,
+        source: NodeIndex,
+        target: NodeIndex,
+    ) {
+        self.by_type
+            .entry(edge_type.to_string())
+            .or_default()
+            .insert(edge);
+
+        for key in Self::get_node_keys(edge_type, source, target) {
+            self.by_node.entry(key).or_default().insert(edge);
+        }
+    }
+
+    pub fn remove(
+        &mut self,
+        edge_type: &str,
+        edge: EdgeIndex,
+        source: NodeIndex,
+        target: NodeIndex,
+    ) {
+        if let Some(edges) = self.by_type.get_mut(edge_type) {
+            edges.remove(&edge);
+
+            if edges.is_empty() {
+                self.by_type.remove(edge_type);
+            }
+        }
+
+        for key in Self::get_node_keys(edge_type, source, target) {
+            if let Some(edges) = self.by_node.get_mut(&key) {
+                edges.remove(&edge);
+
+                if edges.is_empty() {
+                    self.by_node.remove(&key);
+                }
+            }
+        }
+    }
+
+    pub fn get_by_type(&self, edge_type: &str) -> Vec<EdgeIndex> {
+        self.by_type
+            .get(edge_type)
+            .map(|edges| edges.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn get_by_node(
+        &self,
+        node: NodeIndex,
+        dir: Direction,
+        edge_type: &str,
+    ) -> Vec<EdgeIndex> {
+        self.by_node
+            .get(&(node, dir, edge_type.to_string()))
+            .map(|edges| edges.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    //
+    // private methods
+    //
+
+    fn get_node_keys(
+        edge_type: &str,
+        source: NodeIndex,
+        target: NodeIndex,
+    ) -> [(NodeIndex, Direction, String); 2] {
+        [
+            (source, Direction::Outgoing, edge_type.to_string()),
+            (target, Direction::Incoming, edge_type.to_string()),
+        ]
+    }
+}
@@ -1,6 +1,8 @@
 use std::env;
 use std::fmt::Debug;
 use std::hash::Hash;
+use std::sync::Arc;
+use std::sync::Mutex;
 use std::sync::PoisonError;
 use std::time::{SystemTime, UNIX_EPOCH};
 
@@ -11,27 +13,75 @@ use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use petgraph::stable_graph::{NodeIndex, StableGraph};
 use petgraph::{Directed, Direction};
 
+mod admin;
+pub mod algorithms;
+mod api;
+mod auth;
 mod client;
 mod database;
 mod graph;
 mod graph_store;
+mod graphql;
+mod health;
+mod indexes;
+mod metrics;
 mod mutations_log;
 mod mutations_log_store;
+pub mod query;
 mod remotes;
+mod rest;
+mod search;
 mod server;
+mod telemetry;
+mod tls;
 
+pub use admin::DatabaseStatus;
+pub use auth::{AuthConfig, PeerRole};
 pub(crate) use client::GraphClient;
 pub use database::GraphDatabase;
+pub use indexes::{IndexKey, KeyOrdering, OrderedRange};
+pub use mutations_log::{AppliedMutation, SubscriptionFilter};
+use query::{Bindings, Query};
+pub use remotes::RemoteStatus;
 pub(crate) use remotes::SyncRemotesMessage;
+pub use tls::TlsConfig;
 use url::Url;
 
 mod sync_graph {
     tonic::include_proto!("sync_graph");
 }
 
+mod graph_api {
+    tonic::include_proto!("graph_api");
+}
+
 pub trait GraphNode:
     Debug + Default + Clone + Sync + Send + PartialEq + Serialize + DeserializeOwned
 {
+    /// Keys of the node in named secondary indexes, looked up with
+    /// [`GraphDatabase::find_by_index`].
+    fn index_keys(&self) -> Vec<(&'static str, IndexKey)> {
+        Vec::new()
+    }
+
+    /// Keys of the node in named ordered secondary indexes, scanned with
+    /// [`GraphDatabase::range_by_index`].
+    fn ordered_index_keys(&self) -> Vec<(&'static str, IndexKey)> {
+        Vec::new()
+    }
+
+    /// Keys which no two nodes may share, named by constraint. Concurrently
+    /// added nodes sharing a key are resolved in favor of the earlier
+    /// mutation on every replica.
+    fn unique_keys(&self) -> Vec<(&'static str, IndexKey)> {
+        Vec::new()
+    }
+
+    /// Text of the node in the full-text index, searched with
+    /// [`GraphDatabase::search`].
+    fn search_text(&self) -> Option<String> {
+        None
+    }
 }
 
 pub trait GraphEdge:
@@ -44,8 +94,42 @@ pub trait GraphEdge:
     + Serialize
     + DeserializeOwned
 {
+    /// Type of the edge in the edge type index, used to look up edges with
+    /// [`GraphDatabase::get_edges_by_type`].
+    fn edge_type(&self) -> Option<String> {
+        None
+    }
+
+    /// Key telling apart parallel edges between the same nodes when the
+    /// database runs as a multigraph, see [`DatabaseConfig::set_multigraph`].
+    fn key(&self) -> Option<String> {
+        None
+    }
+}
+
+/// Constraints checked by the graph before adding nodes and edges, local as
+/// well as replicated ones, see [`GraphDatabase::run_with_schema`].
+pub trait GraphSchema<N, E>: Send + Sync
+where
+    N: GraphNode,
+    E: GraphEdge,
+{
+    fn validate_node(&self, _node: &N) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn validate_edge(
+        &self,
+        _from: &N,
+        _to: &N,
+        _edge: &E,
+    ) -> Result<(), String> {
+        Ok(())
+    }
 }
 
+/// Key of a node. Keys which also implement `Ord` can be ranged over with
+/// [`GraphDatabase::get_keys_ordered`] and paged through in key order.
 pub trait GraphNodeIndex:
     Debug + Default + Clone + Sync + Send + Hash + Eq + Serialize + DeserializeOwned
 {
@@ -63,8 +147,40 @@ where
     RemoveEdge((I, I)),
     AddNode(N),
     RemoveNode(I),
+    RemoveEdgeByKey((I, I, String)),
+    RemoveNodeWithPolicy((I, DeletionPolicy)),
+}
+
+/// What happens to the edges of a node when removing it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DeletionPolicy {
+    /// Removes the edges along with the node and returns them.
+    Cascade,
+    /// Fails with [`StoreError::NodeHasEdges`] if the node has any edges.
+    Restrict,
+    /// Removes the edges along with the node without returning them.
+    Detach,
 }
 
+/// Kind of a [`GraphMutation`], used to filter subscriptions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum MutationKind {
+    AddEdge,
+    RemoveEdge,
+    AddNode,
+    RemoveNode,
+}
+
+/// Removed node along with the edges removed by [`DeletionPolicy::Cascade`].
+pub type NodeRemoval<N, E, I> = (N, Vec<(I, I, E)>);
+
+/// Nodes of a part of the graph along with the edges between them.
+pub type Subgraph<N, E, I> = (Vec<N>, Vec<(I, I, E)>);
+
+/// Position of an edge in the pages of [`GraphDatabase::get_edges_page`],
+/// the keys of its endpoints and its [`GraphEdge::key`].
+pub type EdgeCursor<I> = (I, I, Option<String>);
+
 impl<N, E, I> GraphMutation<N, E, I>
 where
     N: GraphNode + 'static,
@@ -79,6 +195,59 @@ where
 
         format!("{}{:x}", now, md5::compute(format!("{:?}", self)))
     }
+
+    pub fn kind(&self) -> MutationKind {
+        match self {
+            Self::AddEdge(_) => MutationKind::AddEdge,
+            Self::RemoveEdge(_) | Self::RemoveEdgeByKey(_) => {
+                MutationKind::RemoveEdge
+            }
+            Self::AddNode(_) => MutationKind::AddNode,
+            Self::RemoveNode(_) | Self::RemoveNodeWithPolicy(_) => {
+                MutationKind::RemoveNode
+            }
+        }
+    }
+
+    /// Key and deletion policy of a node removal.
+    pub(crate) fn node_removal(&self) -> Option<(I, DeletionPolicy)> {
+        match self {
+            Self::RemoveNode(key) => {
+                Some((key.clone(), DeletionPolicy::Detach))
+            }
+            Self::RemoveNodeWithPolicy((key, policy)) => {
+                Some((key.clone(), *policy))
+            }
+            _ => None,
+        }
+    }
+
+    /// Name of the variant, used as metrics label.
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            Self::AddEdge(_) => "AddEdge",
+            Self::RemoveEdge(_) => "RemoveEdge",
+            Self::AddNode(_) => "AddNode",
+            Self::RemoveNode(_) => "RemoveNode",
+            Self::RemoveEdgeByKey(_) => "RemoveEdgeByKey",
+            Self::RemoveNodeWithPolicy(_) => "RemoveNodeWithPolicy",
+        }
+    }
+
+    /// Keys of the nodes the mutation applies to.
+    pub fn keys(&self) -> Vec<I> {
+        match self {
+            Self::AddEdge((from, to, _))
+            | Self::RemoveEdge((from, to))
+            | Self::RemoveEdgeByKey((from, to, _)) => {
+                vec![from.clone(), to.clone()]
+            }
+            Self::AddNode(node) => vec![node.clone().into()],
+            Self::RemoveNode(key) | Self::RemoveNodeWithPolicy((key, _)) => {
+                vec![key.clone()]
+            }
+        }
+    }
 }
 
 impl<N, E, I> TryFrom<Vec<u8>> for GraphMutation<N, E, I>
@@ -127,17 +296,33 @@ where
 {
     GetGraph,
     FilterGraph((Option<Vec<N>>, Option<Vec<E>>)),
+    FilterGraphBy((Predicate<N>, Predicate<E>)),
+    FilterKeysBy(Predicate<N>),
     RetainNodes(Vec<I>),
     GetNeighborsUnd(I),
     GetNeighborsDir((I, Direction)),
+    GetNeighborsDirByType((I, Direction, String)),
+    GetNeighborhood((I, usize)),
     GetEdge((I, I)),
+    GetEdgeByKey((I, I, String)),
+    GetEdgesBetween((I, I)),
     GetEdges,
+    GetEdgesWithEndpoints,
+    GetIncidentEdges((I, Direction)),
+    GetEdgesByType(String),
     HasNode(I),
     GetNode(I),
     GetNodes,
+    GetNodesPage((Option<I>, usize, KeyOrdering<I>)),
+    GetEdgesPage((Option<EdgeCursor<I>>, usize, KeyOrdering<I>)),
     GetNodeIndex(I),
+    FindByIndex((String, IndexKey)),
+    RangeByIndex((String, OrderedRange<IndexKey>)),
+    RangeByKey((OrderedRange<I>, KeyOrdering<I>)),
+    Search(String),
     GetSourceNodes,
     GetSinkNodes,
+    Match(Query),
 }
 
 impl<N, E, I> Message for GraphQuery<N, E, I>
@@ -149,6 +334,82 @@ where
     type Result = Result<GraphResponse<N, E, I>, StoreError>;
 }
 
+impl<N, E, I> GraphQuery<N, E, I>
+where
+    N: GraphNode + 'static,
+    E: GraphEdge + 'static,
+    I: GraphNodeIndex + From<N> + 'static,
+{
+    /// Name of the variant, used as metrics label.
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            Self::GetGraph => "GetGraph",
+            Self::FilterGraph(_) => "FilterGraph",
+            Self::FilterGraphBy(_) => "FilterGraphBy",
+            Self::FilterKeysBy(_) => "FilterKeysBy",
+            Self::RetainNodes(_) => "RetainNodes",
+            Self::GetNeighborsUnd(_) => "GetNeighborsUnd",
+            Self::GetNeighborsDir(_) => "GetNeighborsDir",
+            Self::GetNeighborsDirByType(_) => "GetNeighborsDirByType",
+            Self::GetNeighborhood(_) => "GetNeighborhood",
+            Self::GetEdge(_) => "GetEdge",
+            Self::GetEdgeByKey(_) => "GetEdgeByKey",
+            Self::GetEdgesBetween(_) => "GetEdgesBetween",
+            Self::GetEdges => "GetEdges",
+            Self::GetEdgesWithEndpoints => "GetEdgesWithEndpoints",
+            Self::GetIncidentEdges(_) => "GetIncidentEdges",
+            Self::GetEdgesByType(_) => "GetEdgesByType",
+            Self::HasNode(_) => "HasNode",
+            Self::GetNode(_) => "GetNode",
+            Self::GetNodes => "GetNodes",
+            Self::GetNodesPage(_) => "GetNodesPage",
+            Self::GetEdgesPage(_) => "GetEdgesPage",
+            Self::GetNodeIndex(_) => "GetNodeIndex",
+            Self::FindByIndex(_) => "FindByIndex",
+            Self::RangeByIndex(_) => "RangeByIndex",
+            Self::RangeByKey(_) => "RangeByKey",
+            Self::Search(_) => "Search",
+            Self::GetSourceNodes => "GetSourceNodes",
+            Self::GetSinkNodes => "GetSinkNodes",
+            Self::Match(_) => "Match",
+        }
+    }
+}
+
+/// Predicate over a node or edge weight, evaluated inside the Graph actor.
+/// It is called by one thread at a time, so it only has to be `Send`.
+pub struct Predicate<T>(Arc<Mutex<dyn Fn(&T) -> bool + Send>>);
+
+impl<T> Predicate<T> {
+    pub fn new(predicate: impl Fn(&T) -> bool + Send + 'static) -> Self {
+        Self(Arc::new(Mutex::new(predicate)))
+    }
+
+    /// Predicate matching every weight.
+    pub fn any() -> Self {
+        Self::new(|_| true)
+    }
+
+    pub fn matches(&self, weight: &T) -> bool {
+        // a predicate which panicked before is still usable
+        let predicate = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+
+        (*predicate)(weight)
+    }
+}
+
+impl<T> Clone for Predicate<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T> Debug for Predicate<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Predicate")
+    }
+}
+
 #[derive(Debug)]
 pub enum GraphResponse<N, E, I>
 where
@@ -163,8 +424,12 @@ where
     NodeIndex(NodeIndex),
     Edge(E),
     Edges(Vec<E>),
+    Triples(Vec<(I, I, E)>),
     Key(I),
     Keys(Vec<I>),
+    Bindings(Vec<Bindings<I>>),
+    RemovedNode(NodeRemoval<N, E, I>),
+    Subgraph(Subgraph<N, E, I>),
     Graph(StableGraph<N, E, Directed>),
 }
 
@@ -182,6 +447,11 @@ pub enum StoreError {
     NodeNotDeleted,
     ConflictDuplicateNode,
     ConflictDuplicateEdge,
+    NodeHasEdges,
+    AmbiguousEdge,
+    ConstraintViolation(String),
+    ConflictUniqueKey(String),
+    GraphModeConflict(String),
     // store
     StoreError,
     FileSaveError(String),
@@ -196,8 +466,10 @@ pub enum StoreError {
     // rpc
     ClientSendError,
     ClientError,
+    ServerError(String),
     // internal
     ParseError,
+    QueryError(String),
     Serde(String),
 }
 
@@ -231,6 +503,14 @@ pub struct DatabaseConfig {
     initial_remote_addresses: Vec<String>,
     store_path: Option<String>,
     sync_with_remotes: usize,
+    multigraph: bool,
+    undirected: bool,
+    http_port: Option<u16>,
+    graphql_port: Option<u16>,
+    metrics_port: Option<u16>,
+    otlp_endpoint: Option<String>,
+    tls: Option<TlsConfig>,
+    auth: Option<AuthConfig>,
 }
 
 impl DatabaseConfig {
@@ -274,6 +554,86 @@ is not exactly 8 charackters long."
             config.sync_with_remotes = sync_with_remotes;
         }
 
+        if let Ok(multigraph) = env::var("AGRAPHSTORE_MULTIGRAPH") {
+            config.multigraph = multigraph.parse().expect(
+                "Configuration error provided AGRAPHSTORE_MULTIGRAPH \
+is not a boolean.",
+            );
+        }
+
+        if let Ok(undirected) = env::var("AGRAPHSTORE_UNDIRECTED") {
+            config.undirected = undirected.parse().expect(
+                "Configuration error provided AGRAPHSTORE_UNDIRECTED \
+is not a boolean.",
+            );
+        }
+
+        if let Ok(http_port) = env::var("AGRAPHSTORE_HTTP_PORT") {
+            config.http_port = Some(http_port.parse().expect(
+                "Configuration error provided AGRAPHSTORE_HTTP_PORT \
+is not a valid port.",
+            ));
+        }
+
+        if let Ok(graphql_port) = env::var("AGRAPHSTORE_GRAPHQL_PORT") {
+            config.graphql_port = Some(graphql_port.parse().expect(
+                "Configuration error provided AGRAPHSTORE_GRAPHQL_PORT \
+is not a valid port.",
+            ));
+        }
+
+        if let (Ok(cert_path), Ok(key_path), Ok(ca_path)) = (
+            env::var("AGRAPHSTORE_TLS_CERT"),
+            env::var("AGRAPHSTORE_TLS_KEY"),
+            env::var("AGRAPHSTORE_TLS_CA"),
+        ) {
+            let mut tls = TlsConfig::new(cert_path, key_path, ca_path);
+
+            if let (Ok(client_cert_path), Ok(client_key_path)) = (
+                env::var("AGRAPHSTORE_TLS_CLIENT_CERT"),
+                env::var("AGRAPHSTORE_TLS_CLIENT_KEY"),
+            ) {
+                tls.set_client_identity(client_cert_path, client_key_path);
+            }
+
+            if let Ok(domain_name) = env::var("AGRAPHSTORE_TLS_DOMAIN_NAME") {
+                tls.set_domain_name(domain_name);
+            }
+
+            config.tls = Some(tls);
+        }
+
+        if let (Ok(cluster_id), Ok(token)) = (
+            env::var("AGRAPHSTORE_CLUSTER_ID"),
+            env::var("AGRAPHSTORE_CLUSTER_TOKEN"),
+        ) {
+            let mut auth = AuthConfig::new(cluster_id, token);
+
+            for (var, role) in [
+                ("AGRAPHSTORE_READ_ONLY_TOKENS", PeerRole::ReadOnly),
+                ("AGRAPHSTORE_READ_WRITE_TOKENS", PeerRole::ReadWrite),
+            ] {
+                if let Ok(tokens) = env::var(var) {
+                    for token in tokens.split(',') {
+                        auth.add_peer_token(token.to_string(), role);
+                    }
+                }
+            }
+
+            config.auth = Some(auth);
+        }
+
+        if let Ok(otlp_endpoint) = env::var("AGRAPHSTORE_OTLP_ENDPOINT") {
+            config.otlp_endpoint = Some(otlp_endpoint);
+        }
+
+        if let Ok(metrics_port) = env::var("AGRAPHSTORE_METRICS_PORT") {
+            config.metrics_port = Some(metrics_port.parse().expect(
+                "Configuration error provided AGRAPHSTORE_METRICS_PORT \
+is not a valid port.",
+            ));
+        }
+
         config
     }
 
@@ -302,6 +662,64 @@ of `initial_remote_addresses` is not a valid URL.",
     pub fn set_store_path(&mut self, store_path: String) {
         self.store_path = Some(store_path);
     }
+
+    /// Allows parallel edges between the same nodes as long as their
+    /// [`GraphEdge::key`]s differ. Remotes running in the other mode are not
+    /// synchronized with, and the mode can't be turned off for a store
+    /// holding parallel edges.
+    pub fn set_multigraph(&mut self, multigraph: bool) {
+        self.multigraph = multigraph;
+    }
+
+    /// Treats edges as undirected: edges are looked up and removed in either
+    /// direction, and neighbor queries, source and sink nodes, pattern
+    /// queries and the graph algorithms ignore the direction. Edges are still
+    /// stored, listed and paged with the direction they were added with.
+    /// Remotes running in the other mode are not synchronized with, and the
+    /// mode can't be turned on for a store holding edges in both directions
+    /// between the same nodes.
+    pub fn set_undirected(&mut self, undirected: bool) {
+        self.undirected = undirected;
+    }
+
+    /// Serves the HTTP/JSON gateway on `http_port`, on the host of the
+    /// `server_url`.
+    pub fn set_http_port(&mut self, http_port: u16) {
+        self.http_port = Some(http_port);
+    }
+
+    /// Serves the GraphQL endpoint on `graphql_port`, on the host of the
+    /// `server_url`.
+    pub fn set_graphql_port(&mut self, graphql_port: u16) {
+        self.graphql_port = Some(graphql_port);
+    }
+
+    /// Serves the Prometheus metrics on `/metrics` on `metrics_port`, on the
+    /// host of the `server_url`.
+    pub fn set_metrics_port(&mut self, metrics_port: u16) {
+        self.metrics_port = Some(metrics_port);
+    }
+
+    /// Exports the tracing spans of the database to the OTLP collector at
+    /// `otlp_endpoint`, e.g. `http://localhost:4317`.
+    pub fn set_otlp_endpoint(&mut self, otlp_endpoint: String) {
+        self.otlp_endpoint = Some(otlp_endpoint);
+    }
+
+    /// Secures the `SyncGraph` and `GraphApi` services and the connections
+    /// to the remotes with mutual TLS, remotes should then be addressed with
+    /// `https` urls and a domain name set to validate their certificates.
+    pub fn set_tls(&mut self, tls: TlsConfig) {
+        self.tls = Some(tls);
+    }
+
+    /// Requires the peers to present a token of the cluster on the
+    /// `SyncGraph` and `GraphApi` services and rejects remotes of other
+    /// clusters. The HTTP and GraphQL gateways are not authenticated and
+    /// can't be served along with it.
+    pub fn set_auth(&mut self, auth: AuthConfig) {
+        self.auth = Some(auth);
+    }
 }
 
 impl Default for DatabaseConfig {
@@ -311,6 +729,14 @@ impl Default for DatabaseConfig {
             initial_remote_addresses: Vec::default(),
             store_path: Option::default(),
             sync_with_remotes: 2,
+            multigraph: false,
+            undirected: false,
+            http_port: None,
+            graphql_port: None,
+            metrics_port: None,
+            otlp_endpoint: None,
+            tls: None,
+            auth: None,
         }
     }
 }
@@ -19,7 +19,11 @@ use graph_db::{
 )]
 pub struct EdgeType(usize);
 
-impl GraphEdge for EdgeType {}
+impl GraphEdge for EdgeType {
+    fn edge_type(&self) -> Option<String> {
+        Some(self.0.to_string())
+    }
+}
 
 #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct NodeTypes {
@@ -30,7 +34,16 @@ pub struct NodeTypes {
 impl GraphNode for NodeTypes {}
 
 #[derive(
-    Debug, Clone, Default, Hash, PartialEq, Eq, Serialize, Deserialize,
+    Debug,
+    Clone,
+    Default,
+    Hash,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Serialize,
+    Deserialize,
 )]
 pub struct NodeKey(usize);
 
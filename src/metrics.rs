@@ -0,0 +1,312 @@
+use std::net::SocketAddr;
+
+use actix::dev::ToEnvelope;
+use actix::{Actor, Addr, Handler, MailboxError, Message};
+use actix_web::{web, App, HttpResponse, HttpServer};
+use once_cell::sync::Lazy;
+use prometheus::core::Collector;
+use prometheus::{
+    Encoder, Histogram, HistogramOpts, HistogramVec, IntCounterVec, IntGauge,
+    IntGaugeVec, Opts, TextEncoder,
+};
+
+use crate::StoreError;
+
+//
+// metrics
+//
+
+pub static MUTATIONS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register(IntCounterVec::new(
+        Opts::new(
+            "graph_db_mutations_total",
+            "Mutations committed through the local database.",
+        ),
+        &["mutation", "result"],
+    ))
+});
+
+pub static MUTATION_DURATION: Lazy<HistogramVec> = Lazy::new(|| {
+    register(HistogramVec::new(
+        HistogramOpts::new(
+            "graph_db_mutation_duration_seconds",
+            "Time to replicate, log and apply a mutation.",
+        ),
+        &["mutation"],
+    ))
+});
+
+pub static QUERY_DURATION: Lazy<HistogramVec> = Lazy::new(|| {
+    register(HistogramVec::new(
+        HistogramOpts::new(
+            "graph_db_query_duration_seconds",
+            "Time to run a query in the Graph actor.",
+        ),
+        &["query"],
+    ))
+});
+
+pub static PENDING_MESSAGES: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register(IntGaugeVec::new(
+        Opts::new(
+            "graph_db_pending_messages",
+            "Messages awaiting the response of an actor, queued in its \
+mailbox or being handled.",
+        ),
+        &["server", "actor"],
+    ))
+});
+
+pub static REPLICATIONS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register(IntCounterVec::new(
+        Opts::new("graph_db_replications_total", "Mutations sent to remotes."),
+        &["remote", "result"],
+    ))
+});
+
+pub static MUTATIONS_LOG_LENGTH: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register(IntGaugeVec::new(
+        Opts::new(
+            "graph_db_mutations_log_length",
+            "Mutations in the mutations log.",
+        ),
+        &["server"],
+    ))
+});
+
+pub static SNAPSHOT_WRITE_DURATION: Lazy<Histogram> = Lazy::new(|| {
+    register(Histogram::with_opts(HistogramOpts::new(
+        "graph_db_snapshot_write_duration_seconds",
+        "Time to save the graph to data.bin.",
+    )))
+});
+
+fn register<C>(collector: Result<C, prometheus::Error>) -> C
+where
+    C: Collector + Clone + 'static,
+{
+    let collector = collector.unwrap();
+    prometheus::register(Box::new(collector.clone())).unwrap();
+
+    collector
+}
+
+pub fn result_label<T>(result: &Result<T, StoreError>) -> &'static str {
+    match result {
+        Ok(_) => "ok",
+        Err(_) => "error",
+    }
+}
+
+/// Sends `msg` to the actor named `actor` of the database serving
+/// `server`, counting it in the pending messages of the actor until it is
+/// answered. Messages sent without awaiting a response aren't counted.
+pub async fn send<A, M>(
+    server: &str,
+    actor: &str,
+    addr: &Addr<A>,
+    msg: M,
+) -> Result<M::Result, MailboxError>
+where
+    A: Actor + Handler<M>,
+    A::Context: ToEnvelope<A, M>,
+    M: Message + Send + 'static,
+    M::Result: Send,
+{
+    let _pending =
+        Pending::new(PENDING_MESSAGES.with_label_values(&[server, actor]));
+
+    addr.send(msg).await
+}
+
+/// Counts a message in the pending messages until dropped, also when the
+/// sending future is cancelled.
+struct Pending(IntGauge);
+
+impl Pending {
+    fn new(pending: IntGauge) -> Self {
+        pending.inc();
+        Self(pending)
+    }
+}
+
+impl Drop for Pending {
+    fn drop(&mut self) {
+        self.0.dec();
+    }
+}
+
+//
+// server
+//
+
+/// Serves the metrics in the Prometheus text format on `/metrics`.
+pub struct MetricsServer;
+
+impl MetricsServer {
+    pub fn run(address: SocketAddr) -> Result<(), StoreError> {
+        let server = HttpServer::new(|| {
+            App::new().route("/metrics", web::get().to(get_metrics))
+        })
+        .disable_signals()
+        .bind(address)
+        .map_err(|err| StoreError::ServerError(err.to_string()))?
+        .run();
+
+        tokio::spawn(async move {
+            if let Err(err) = server.await {
+                log::error!(
+                    "Error while running MetricsServer. Error: '{err}'"
+                );
+            }
+        });
+
+        Ok(())
+    }
+}
+
+async fn get_metrics() -> HttpResponse {
+    let encoder = TextEncoder::new();
+    let mut buffer = Vec::new();
+
+    match encoder.encode(&prometheus::gather(), &mut buffer) {
+        Ok(()) => HttpResponse::Ok()
+            .content_type(encoder.format_type())
+            .body(buffer),
+        Err(err) => {
+            log::error!("Error while encoding metrics. Error: '{err}'");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::test::{call_service, init_service, read_body, TestRequest};
+    use serde::{Deserialize, Serialize};
+    use tracing::Span;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::mutations_log::MutationsLogMutation;
+    use crate::mutations_log_store::{AppendMutation, MutationsLogStore};
+    use crate::{GraphEdge, GraphMutation, GraphNode, GraphNodeIndex};
+
+    #[derive(
+        Debug,
+        Default,
+        Clone,
+        Copy,
+        Hash,
+        PartialEq,
+        Eq,
+        PartialOrd,
+        Ord,
+        Serialize,
+        Deserialize,
+    )]
+    struct Edge(usize);
+    impl GraphEdge for Edge {}
+
+    #[derive(
+        Debug,
+        Default,
+        Clone,
+        Copy,
+        Hash,
+        PartialEq,
+        Eq,
+        PartialOrd,
+        Ord,
+        Serialize,
+        Deserialize,
+    )]
+    struct Node(Uuid);
+    impl GraphNode for Node {}
+
+    #[derive(
+        Debug,
+        Default,
+        Clone,
+        Copy,
+        Hash,
+        PartialEq,
+        Eq,
+        PartialOrd,
+        Ord,
+        Serialize,
+        Deserialize,
+    )]
+    struct NodeId(Uuid);
+
+    impl GraphNodeIndex for NodeId {}
+
+    impl From<Node> for NodeId {
+        fn from(node: Node) -> Self {
+            Self(node.0)
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_get_metrics_ok() {
+        let test_dir = "test-data/test_get_metrics_ok";
+        if std::path::Path::new(test_dir).is_dir() {
+            std::fs::remove_dir_all(test_dir).unwrap();
+        }
+        std::fs::create_dir_all(test_dir).unwrap();
+
+        let server_url = "http://127.0.0.1:4060";
+        let store = MutationsLogStore::<Node, Edge, NodeId>::new(
+            Some(test_dir.into()),
+            server_url,
+        )
+        .start();
+
+        let entry = MutationsLogMutation {
+            hash: "1".to_string(),
+            origin: server_url.to_string(),
+            mutation: GraphMutation::AddNode(Node(Uuid::new_v4())),
+            span: Span::none(),
+        };
+        send(
+            server_url,
+            "mutations_log_store",
+            &store,
+            AppendMutation(entry),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+
+        let app = init_service(
+            App::new().route("/metrics", web::get().to(get_metrics)),
+        )
+        .await;
+        let request = TestRequest::get().uri("/metrics").to_request();
+        let response = call_service(&app, request).await;
+
+        assert!(response.status().is_success());
+        assert_eq!(
+            response.headers().get("content-type").unwrap(),
+            "text/plain; version=0.0.4"
+        );
+
+        let body = read_body(response).await;
+        let body = std::str::from_utf8(&body).unwrap();
+
+        // the series of the gauges are labeled per database
+        assert!(body.contains("# TYPE graph_db_pending_messages gauge"));
+        assert!(body.contains(
+            "graph_db_pending_messages{actor=\"mutations_log_store\",\
+server=\"http://127.0.0.1:4060\"} 0"
+        ));
+        assert!(body.contains("# TYPE graph_db_mutations_log_length gauge"));
+        assert!(body.contains(
+            "graph_db_mutations_log_length{server=\"http://127.0.0.1:4060\"} 1"
+        ));
+
+        if std::path::Path::new(test_dir).is_dir() {
+            std::fs::remove_dir_all(test_dir).unwrap();
+        }
+    }
+}
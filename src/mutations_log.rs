@@ -9,14 +9,20 @@ use actix::{
 use actix_interop::FutureInterop;
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+use tracing::{info_span, Instrument, Span};
 
-use crate::graph::Graph;
-use crate::mutations_log_store::MutationsLogStore;
+use crate::graph::{
+    ApplyMutation, CheckMutation, Graph, HasUnknownUniqueKeyHashes,
+    RemoveNodeWithEdges, RestoreUniqueKeyHashes, UniqueKeyEvictions,
+};
+use crate::metrics;
+use crate::mutations_log_store::{AppendMutation, MutationsLogStore};
 use crate::remotes::Remotes;
 use crate::sync_graph::GraphMutationRequest;
 use crate::{
-    GraphEdge, GraphMutation, GraphNode, GraphNodeIndex, GraphResponse,
-    StoreError,
+    DeletionPolicy, GraphEdge, GraphMutation, GraphNode, GraphNodeIndex,
+    GraphResponse, MutationKind, StoreError,
 };
 
 pub struct MutationsLog<N, E, I>
@@ -29,6 +35,8 @@ where
     remotes: Addr<Remotes<N, E, I>>,
     mutations_log_store: Addr<MutationsLogStore<N, E, I>>,
     pending_mutations_log: HashMap<String, GraphMutation<N, E, I>>,
+    server_url: String,
+    events: broadcast::Sender<AppliedMutation<N, E, I>>,
 }
 
 impl<N, E, I> Actor for MutationsLog<N, E, I>
@@ -53,8 +61,11 @@ where
         graph: Addr<Graph<N, E, I>>,
         remotes: Addr<Remotes<N, E, I>>,
         store_path: Option<String>,
+        server_url: String,
+        events: broadcast::Sender<AppliedMutation<N, E, I>>,
     ) -> Result<Self, StoreError> {
-        let mutations_log_store = MutationsLogStore::new(store_path).start();
+        let mutations_log_store =
+            MutationsLogStore::new(store_path, &server_url).start();
         let pending_mutations_log = HashMap::new();
 
         Ok(Self {
@@ -62,19 +73,163 @@ where
             remotes,
             mutations_log_store,
             pending_mutations_log,
+            server_url,
+            events,
         })
     }
 
+    /// Appends the mutation to the log, applies it to the graph and
+    /// publishes the outcome to the subscribers. Nodes evicted by an added
+    /// node claiming their unique keys are removed by mutations committed
+    /// ahead of it, see [`UniqueKeyEvictions`].
     pub async fn commit_mutation(
+        server_url: &str,
+        mutations_log_store_addr: &Addr<MutationsLogStore<N, E, I>>,
+        graph_addr: &Addr<Graph<N, E, I>>,
+        events: &broadcast::Sender<AppliedMutation<N, E, I>>,
+        graph_mutation_log_entry: MutationsLogMutation<N, E, I>,
+    ) -> Result<GraphResponse<N, E, I>, StoreError> {
+        let evictions = graph_addr
+            .send(UniqueKeyEvictions(graph_mutation_log_entry.clone()))
+            .await?;
+
+        for (position, mutation) in evictions.into_iter().enumerate() {
+            let eviction_log_entry = MutationsLogMutation {
+                hash: format!("{}-{position}", graph_mutation_log_entry.hash),
+                origin: graph_mutation_log_entry.origin.clone(),
+                mutation,
+                span: graph_mutation_log_entry.span.clone(),
+            };
+
+            Self::append_and_apply(
+                server_url,
+                mutations_log_store_addr,
+                graph_addr,
+                events,
+                eviction_log_entry,
+            )
+            .await?;
+        }
+
+        Self::append_and_apply(
+            server_url,
+            mutations_log_store_addr,
+            graph_addr,
+            events,
+            graph_mutation_log_entry,
+        )
+        .await
+    }
+
+    async fn append_and_apply(
+        server_url: &str,
         mutations_log_store_addr: &Addr<MutationsLogStore<N, E, I>>,
         graph_addr: &Addr<Graph<N, E, I>>,
+        events: &broadcast::Sender<AppliedMutation<N, E, I>>,
+        graph_mutation_log_entry: MutationsLogMutation<N, E, I>,
+    ) -> Result<GraphResponse<N, E, I>, StoreError> {
+        let cursor = metrics::send(
+            server_url,
+            "mutations_log_store",
+            mutations_log_store_addr,
+            AppendMutation(graph_mutation_log_entry.clone()),
+        )
+        .instrument(info_span!("append_mutation"))
+        .await??;
+
+        let response = metrics::send(
+            server_url,
+            "graph",
+            graph_addr,
+            ApplyMutation(graph_mutation_log_entry.clone()),
+        )
+        .instrument(info_span!("apply_mutation"))
+        .await?;
+
+        Self::publish(
+            events,
+            cursor,
+            graph_mutation_log_entry,
+            response.as_ref().map(|_| ()).map_err(Clone::clone),
+        );
+
+        response
+    }
+
+    /// Appends the mutation already applied to the graph to the log and
+    /// publishes it to the subscribers.
+    async fn append_applied(
+        server_url: &str,
+        mutations_log_store_addr: &Addr<MutationsLogStore<N, E, I>>,
+        events: &broadcast::Sender<AppliedMutation<N, E, I>>,
+        graph_mutation_log_entry: MutationsLogMutation<N, E, I>,
+    ) -> Result<(), StoreError> {
+        let cursor = metrics::send(
+            server_url,
+            "mutations_log_store",
+            mutations_log_store_addr,
+            AppendMutation(graph_mutation_log_entry.clone()),
+        )
+        .instrument(info_span!("append_mutation"))
+        .await??;
+
+        Self::publish(events, cursor, graph_mutation_log_entry, Ok(()));
+
+        Ok(())
+    }
+
+    fn publish(
+        events: &broadcast::Sender<AppliedMutation<N, E, I>>,
+        cursor: i64,
         graph_mutation_log_entry: MutationsLogMutation<N, E, I>,
+        result: Result<(), StoreError>,
+    ) {
+        // sending only fails if there are no subscribers
+        let _ = events.send(AppliedMutation {
+            cursor,
+            hash: graph_mutation_log_entry.hash,
+            origin: graph_mutation_log_entry.origin,
+            mutation: graph_mutation_log_entry.mutation,
+            result,
+        });
+    }
+
+    /// Response to the node removal `mutation` from the responses to the
+    /// removals it was committed as, see [`RemoveNodeWithEdges`].
+    fn into_response(
+        mutation: GraphMutation<N, E, I>,
+        mut committed: Vec<(GraphMutation<N, E, I>, GraphResponse<N, E, I>)>,
     ) -> Result<GraphResponse<N, E, I>, StoreError> {
-        mutations_log_store_addr
-            .send(graph_mutation_log_entry.clone())
-            .await??;
+        // the node is removed after its edges
+        let Some((GraphMutation::RemoveNode(_), GraphResponse::Node(node))) =
+            committed.pop()
+        else {
+            return Err(StoreError::NodeNotDeleted);
+        };
+
+        let GraphMutation::RemoveNodeWithPolicy((_, policy)) = mutation else {
+            return Ok(GraphResponse::Node(node));
+        };
+
+        let removed_edges = match policy {
+            DeletionPolicy::Cascade => committed
+                .into_iter()
+                .filter_map(|committed| match committed {
+                    (
+                        GraphMutation::RemoveEdge((from, to)),
+                        GraphResponse::Edge(edge),
+                    )
+                    | (
+                        GraphMutation::RemoveEdgeByKey((from, to, _)),
+                        GraphResponse::Edge(edge),
+                    ) => Some((from, to, edge)),
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
 
-        graph_addr.send(graph_mutation_log_entry.mutation).await?
+        Ok(GraphResponse::RemovedNode((node, removed_edges)))
     }
 }
 
@@ -96,14 +251,66 @@ where
         let graph = self.graph.clone();
         let mutations_log_store = self.mutations_log_store.clone();
         let mut pending_mutations_log = self.pending_mutations_log.clone();
+        let origin = self.server_url.clone();
+        let events = self.events.clone();
+
+        let name = msg.name();
+        let timer = metrics::MUTATION_DURATION
+            .with_label_values(&[name])
+            .start_timer();
+        let span = info_span!("commit_mutation", mutation = name);
+
+        let future = async move {
+            if msg.node_removal().is_some() {
+                // applied at once and logged afterwards, so no edge added to
+                // the node in between is dropped without being logged
+                let removals =
+                    graph.send(RemoveNodeWithEdges(msg.clone())).await??;
+                let mut committed = Vec::with_capacity(removals.len());
+
+                for (hash, mutation, response) in removals {
+                    pending_mutations_log
+                        .insert(hash.clone(), mutation.clone());
+
+                    let query = MutationsLogMutation {
+                        hash,
+                        origin: origin.clone(),
+                        mutation: mutation.clone(),
+                        span: Span::current(),
+                    };
+
+                    Self::append_applied(
+                        &origin,
+                        &mutations_log_store,
+                        &events,
+                        query.clone(),
+                    )
+                    .await?;
+
+                    // includes also remotes to which we send
+                    // synchronous mutations
+                    remotes.do_send(query.clone());
+
+                    remotes.send(query).await??;
+
+                    committed.push((mutation, response));
+                }
+
+                return Self::into_response(msg, committed);
+            }
+
+            // fails before anything is logged if the mutation can't succeed
+            // on any replica
+            graph.send(CheckMutation(msg.clone())).await??;
 
-        async move {
             let hash = msg.get_hash();
             pending_mutations_log.insert(hash.clone(), msg.clone());
 
             let query = MutationsLogMutation {
                 hash,
-                mutation: msg,
+                origin: origin.clone(),
+                mutation: msg.clone(),
+                span: Span::current(),
             };
 
             // includes also remotes to which we send
@@ -112,9 +319,28 @@ where
 
             remotes.send(query.clone()).await??;
 
-            Self::commit_mutation(&mutations_log_store, &graph, query).await
+            Self::commit_mutation(
+                &origin,
+                &mutations_log_store,
+                &graph,
+                &events,
+                query,
+            )
+            .await
         }
-        .interop_actor_boxed(self)
+        .instrument(span);
+
+        let actor_future =
+            future.into_actor(self).map(move |result, _actor, _ctx| {
+                timer.observe_duration();
+                metrics::MUTATIONS
+                    .with_label_values(&[name, metrics::result_label(&result)])
+                    .inc();
+
+                result
+            });
+
+        Box::pin(actor_future)
     }
 }
 
@@ -127,7 +353,13 @@ where
     I: GraphNodeIndex + From<N> + 'static,
 {
     pub hash: String,
+    /// Server url of the database the mutation was first committed on.
+    pub origin: String,
     pub mutation: GraphMutation<N, E, I>,
+    /// Span the mutation is committed in, passed along to the actors and
+    /// remotes committing it.
+    #[serde(skip, default = "Span::none")]
+    pub span: Span,
 }
 
 impl<N, E, I> TryFrom<GraphMutationRequest> for MutationsLogMutation<N, E, I>
@@ -142,13 +374,19 @@ where
         let GraphMutationRequest {
             graph_mutation,
             hash,
+            origin,
         } = request;
 
         let mutation: GraphMutation<N, E, I> =
             bincode::deserialize(&graph_mutation)
                 .map_err(|err| StoreError::Serde(err.to_string()))?;
 
-        Ok(Self { hash, mutation })
+        Ok(Self {
+            hash,
+            origin,
+            mutation,
+            span: Span::none(),
+        })
     }
 }
 
@@ -167,6 +405,7 @@ where
         Ok(GraphMutationRequest {
             hash: self.hash,
             graph_mutation,
+            origin: self.origin,
         })
     }
 }
@@ -193,13 +432,24 @@ where
         msg: MutationsLogMutation<N, E, I>,
         _ctx: &mut Self::Context,
     ) -> Self::Result {
+        let server_url = self.server_url.clone();
         let mutations_log_store = self.mutations_log_store.clone();
         let graph = self.graph.clone();
+        let events = self.events.clone();
+        let span = info_span!(parent: &msg.span, "commit_replicated_mutation");
 
         async move {
-            Self::commit_mutation(&mutations_log_store, &graph, msg).await?;
+            Self::commit_mutation(
+                &server_url,
+                &mutations_log_store,
+                &graph,
+                &events,
+                msg,
+            )
+            .await?;
             Ok(())
         }
+        .instrument(span)
         .interop_actor_boxed(self)
     }
 }
@@ -246,6 +496,176 @@ where
     }
 }
 
+/// Mutations of the log along with their positions in the log.
+pub type LoggedMutations<N, E, I> = Vec<(i64, MutationsLogMutation<N, E, I>)>;
+
+/// Mutations appended to the log after the position `cursor`, along with
+/// their positions.
+pub struct MutationsLogSince<N, E, I> {
+    pub cursor: i64,
+    phantom: PhantomData<(N, E, I)>,
+}
+
+impl<N, E, I> MutationsLogSince<N, E, I> {
+    pub fn new(cursor: i64) -> Self {
+        Self {
+            cursor,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<N, E, I> Message for MutationsLogSince<N, E, I>
+where
+    N: GraphNode + 'static,
+    E: GraphEdge + 'static,
+    I: GraphNodeIndex + From<N> + 'static,
+{
+    type Result = Result<LoggedMutations<N, E, I>, StoreError>;
+}
+
+impl<N, E, I> Handler<MutationsLogSince<N, E, I>> for MutationsLog<N, E, I>
+where
+    N: GraphNode + Unpin + 'static,
+    E: GraphEdge + Unpin + 'static,
+    I: GraphNodeIndex + From<N> + Unpin + 'static,
+{
+    type Result =
+        ResponseActFuture<Self, Result<LoggedMutations<N, E, I>, StoreError>>;
+
+    fn handle(
+        &mut self,
+        msg: MutationsLogSince<N, E, I>,
+        _ctx: &mut Self::Context,
+    ) -> Self::Result {
+        let mutations_log_store = self.mutations_log_store.clone();
+
+        async move { mutations_log_store.send(msg).await? }
+            .interop_actor_boxed(self)
+    }
+}
+
+/// Mutation committed to the log and applied to the graph, published to the
+/// subscribers of [`crate::GraphDatabase::subscribe`].
+#[derive(Debug, Clone)]
+pub struct AppliedMutation<N, E, I>
+where
+    N: GraphNode + 'static,
+    E: GraphEdge + 'static,
+    I: GraphNodeIndex + From<N> + 'static,
+{
+    /// Position in the local mutations log, to resume a subscription from.
+    pub cursor: i64,
+    pub hash: String,
+    /// Server url of the database the mutation was first committed on.
+    pub origin: String,
+    pub mutation: GraphMutation<N, E, I>,
+    /// Outcome of applying the mutation, `Ok` for mutations replayed from
+    /// the log.
+    pub result: Result<(), StoreError>,
+}
+
+impl<N, E, I> AppliedMutation<N, E, I>
+where
+    N: GraphNode + 'static,
+    E: GraphEdge + 'static,
+    I: GraphNodeIndex + From<N> + 'static,
+{
+    pub(crate) fn from_log(
+        cursor: i64,
+        mutation: MutationsLogMutation<N, E, I>,
+    ) -> Self {
+        Self {
+            cursor,
+            hash: mutation.hash,
+            origin: mutation.origin,
+            mutation: mutation.mutation,
+            result: Ok(()),
+        }
+    }
+}
+
+/// Mutations a subscription is interested in. Empty `keys` or `kinds` match
+/// any mutation, `after` replays the log from a previous cursor.
+#[derive(Debug, Clone)]
+pub struct SubscriptionFilter<I> {
+    pub keys: Vec<I>,
+    pub kinds: Vec<MutationKind>,
+    pub after: Option<i64>,
+}
+
+impl<I> Default for SubscriptionFilter<I> {
+    fn default() -> Self {
+        Self {
+            keys: Vec::new(),
+            kinds: Vec::new(),
+            after: None,
+        }
+    }
+}
+
+impl<I> SubscriptionFilter<I>
+where
+    I: GraphNodeIndex,
+{
+    pub fn matches<N, E>(&self, mutation: &GraphMutation<N, E, I>) -> bool
+    where
+        N: GraphNode + 'static,
+        E: GraphEdge + 'static,
+        I: From<N> + 'static,
+    {
+        let kind_matches =
+            self.kinds.is_empty() || self.kinds.contains(&mutation.kind());
+        let key_matches = self.keys.is_empty()
+            || mutation.keys().iter().any(|key| self.keys.contains(key));
+
+        kind_matches && key_matches
+    }
+}
+
+/// Length and last hash of the mutations log, along with the hashes of the
+/// replicated mutations which could not be applied to the graph.
+#[derive(Debug, Clone)]
+pub struct MutationsLogStats {
+    pub length: usize,
+    pub last_hash: Option<String>,
+    pub pending: Vec<String>,
+}
+
+pub struct MutationsLogStatsQuery;
+
+impl Message for MutationsLogStatsQuery {
+    type Result = Result<MutationsLogStats, StoreError>;
+}
+
+impl<N, E, I> Handler<MutationsLogStatsQuery> for MutationsLog<N, E, I>
+where
+    N: GraphNode + Unpin + 'static,
+    E: GraphEdge + Unpin + 'static,
+    I: GraphNodeIndex + From<N> + Unpin + 'static,
+{
+    type Result =
+        ResponseActFuture<Self, Result<MutationsLogStats, StoreError>>;
+
+    fn handle(
+        &mut self,
+        msg: MutationsLogStatsQuery,
+        _ctx: &mut Self::Context,
+    ) -> Self::Result {
+        let mutations_log_store = self.mutations_log_store.clone();
+        let mut pending: Vec<String> =
+            self.pending_mutations_log.keys().cloned().collect();
+        pending.sort();
+
+        async move {
+            let stats = mutations_log_store.send(msg).await??;
+
+            Ok(MutationsLogStats { pending, ..stats })
+        }
+        .interop_actor_boxed(self)
+    }
+}
+
 pub struct InitializeMutationsLog;
 
 impl Message for InitializeMutationsLog {
@@ -265,19 +685,35 @@ where
         _msg: InitializeMutationsLog,
         _ctx: &mut Self::Context,
     ) -> Self::Result {
+        let server_url = self.server_url.clone();
         let remotes = self.remotes.clone();
         let graph = self.graph.clone();
         let mutations_log_store = self.mutations_log_store.clone();
         let mut pending_mutations_log = self.pending_mutations_log.clone();
+        let events = self.events.clone();
 
         let future = async move {
+            // unique keys loaded without their hashes win against any
+            // concurrently added node until restored from the local log, so
+            // restore them before replaying mutations which could evict them
+            if graph.send(HasUnknownUniqueKeyHashes).await? {
+                let local_mutations_log = mutations_log_store
+                    .send(MutationsLogQuery::full())
+                    .await??;
+                graph
+                    .send(RestoreUniqueKeyHashes(local_mutations_log))
+                    .await??;
+            }
+
             let mutations_log_mutations =
                 remotes.send(MutationsLogQuery::full()).await??;
 
             for mutation_log_mutation in mutations_log_mutations.into_iter() {
                 if let Err(err) = Self::commit_mutation(
+                    &server_url,
                     &mutations_log_store,
                     &graph,
+                    &events,
                     mutation_log_mutation.clone(),
                 )
                 .await
@@ -313,3 +749,151 @@ while synchronizing. Error: '{:?}'",
         Box::pin(actor_future)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use actix::Actor;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::graph::{GraphMode, UNIQUE_CONSTRAINTS_FILE_NAME};
+    use crate::{GraphQuery, IndexKey};
+
+    #[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+    struct User {
+        id: Uuid,
+        email: String,
+    }
+
+    impl GraphNode for User {
+        fn unique_keys(&self) -> Vec<(&'static str, IndexKey)> {
+            vec![("email", self.email.as_str().into())]
+        }
+    }
+
+    #[derive(
+        Debug,
+        Default,
+        Clone,
+        Copy,
+        Hash,
+        PartialEq,
+        Eq,
+        PartialOrd,
+        Ord,
+        Serialize,
+        Deserialize,
+    )]
+    struct UserId(Uuid);
+
+    impl GraphNodeIndex for UserId {}
+
+    impl From<User> for UserId {
+        fn from(user: User) -> Self {
+            Self(user.id)
+        }
+    }
+
+    #[derive(
+        Debug,
+        Default,
+        Clone,
+        Copy,
+        Hash,
+        PartialEq,
+        Eq,
+        PartialOrd,
+        Ord,
+        Serialize,
+        Deserialize,
+    )]
+    struct Edge(usize);
+    impl GraphEdge for Edge {}
+
+    type UserGraph = Graph<User, Edge, UserId>;
+
+    async fn start(
+        test_dir: &str,
+    ) -> (Addr<UserGraph>, Addr<MutationsLog<User, Edge, UserId>>) {
+        let graph = UserGraph::new(Some(test_dir.into())).unwrap().start();
+        let remotes = Remotes::new(1, None, None, GraphMode::default()).start();
+        let (events, _) = broadcast::channel(16);
+        let mutations_log = MutationsLog::new(
+            graph.clone(),
+            remotes,
+            Some(test_dir.into()),
+            "http://127.0.0.1:0".to_string(),
+            events,
+        )
+        .await
+        .unwrap()
+        .start();
+
+        (graph, mutations_log)
+    }
+
+    fn user(email: &str) -> User {
+        User {
+            id: Uuid::new_v4(),
+            email: email.to_string(),
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_unique_key_evicted_after_restart_ok() {
+        let test_dir = "test-data/test_unique_key_evicted_after_restart_ok";
+        if std::path::Path::new(test_dir).is_dir() {
+            std::fs::remove_dir_all(test_dir).unwrap();
+        }
+
+        let (_, mutations_log) = start(test_dir).await;
+        let user1 = user("a@example.com");
+        mutations_log
+            .send(GraphMutation::AddNode(user1.clone()))
+            .await
+            .unwrap()
+            .unwrap();
+
+        // restart without the persisted claims, so the key of the first user
+        // is loaded without its mutation hash
+        std::fs::remove_file(format!(
+            "{test_dir}/{UNIQUE_CONSTRAINTS_FILE_NAME}"
+        ))
+        .unwrap();
+        let (graph, mutations_log) = start(test_dir).await;
+        assert!(graph.send(HasUnknownUniqueKeyHashes).await.unwrap());
+
+        mutations_log
+            .send(InitializeMutationsLog)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(!graph.send(HasUnknownUniqueKeyHashes).await.unwrap());
+
+        // a replicated mutation committed ahead of the first user evicts it
+        let user2 = user("a@example.com");
+        mutations_log
+            .send(MutationsLogMutation {
+                hash: "0".to_string(),
+                origin: "http://127.0.0.1:1".to_string(),
+                mutation: GraphMutation::AddNode(user2.clone()),
+                span: Span::none(),
+            })
+            .await
+            .unwrap()
+            .unwrap();
+
+        for (user, expected) in [(user1, false), (user2, true)] {
+            let Ok(GraphResponse::Bool(has)) =
+                graph.send(GraphQuery::HasNode(user.into())).await.unwrap()
+            else {
+                panic!("HasNode should respond with a bool");
+            };
+            assert_eq!(has, expected);
+        }
+
+        if std::path::Path::new(test_dir).is_dir() {
+            std::fs::remove_dir_all(test_dir).unwrap();
+        }
+    }
+}
@@ -1,10 +1,16 @@
 use std::marker::PhantomData;
 
-use actix::{Actor, Context, Handler};
-use rusqlite::{params, Connection, Error as SqliteError};
+use actix::{Actor, Context, Handler, Message};
+use prometheus::IntGauge;
+use rusqlite::{params, Connection, Error as SqliteError, OptionalExtension};
+use tracing::Span;
 
 use crate::{
-    mutations_log::{MutationsLogMutation, MutationsLogQuery},
+    metrics,
+    mutations_log::{
+        LoggedMutations, MutationsLogMutation, MutationsLogQuery,
+        MutationsLogSince, MutationsLogStats, MutationsLogStatsQuery,
+    },
     GraphEdge, GraphMutation, GraphNode, GraphNodeIndex, StoreError,
 };
 
@@ -17,6 +23,8 @@ where
     I: GraphNodeIndex + From<N> + Unpin + 'static,
 {
     conn: Connection,
+    /// Length of the log, labeled with the server url of the database.
+    length: IntGauge,
     phantom_n: PhantomData<N>,
     phantom_e: PhantomData<E>,
     phantom_i: PhantomData<I>,
@@ -28,7 +36,7 @@ where
     E: GraphEdge + Unpin + 'static,
     I: GraphNodeIndex + From<N> + Unpin + 'static,
 {
-    pub fn new(store_path: Option<String>) -> Self {
+    pub fn new(store_path: Option<String>, server_url: &str) -> Self {
         let mutations_log_path = match store_path {
             Some(path) => format!("{}/log.sqlite", path),
             None => DEFAULT_GRAPH_LOG_PATH.to_string(),
@@ -36,9 +44,13 @@ where
 
         let conn = Connection::open(mutations_log_path).unwrap();
         Self::init_log_table(&conn).unwrap();
+        let length =
+            metrics::MUTATIONS_LOG_LENGTH.with_label_values(&[server_url]);
+        length.set(Self::count_mutations(&conn).unwrap());
 
         Self {
             conn,
+            length,
             phantom_n: PhantomData,
             phantom_e: PhantomData,
             phantom_i: PhantomData,
@@ -49,12 +61,97 @@ where
         conn.execute(
             "CREATE TABLE IF NOT EXISTS mutations_log (
                 id CHAR(40) PRIMARY KEY NOT NULL UNIQUE,
-                mutation BLOB NOT NULL
+                mutation BLOB NOT NULL,
+                origin TEXT NOT NULL DEFAULT ''
             )",
             (),
         )?;
+
+        // logs created before the origin was recorded
+        let has_origin = conn
+            .prepare("SELECT name FROM pragma_table_info('mutations_log')")?
+            .query_map([], |row| row.get::<_, String>(0))?
+            .any(|name| matches!(name.as_deref(), Ok("origin")));
+
+        if !has_origin {
+            conn.execute(
+                "ALTER TABLE mutations_log
+                    ADD COLUMN origin TEXT NOT NULL DEFAULT ''",
+                (),
+            )?;
+        }
+
         Ok(())
     }
+
+    fn count_mutations(conn: &Connection) -> Result<i64, rusqlite::Error> {
+        conn.query_row("SELECT COUNT(*) FROM mutations_log", [], |row| {
+            row.get(0)
+        })
+    }
+
+    fn get_mutations(
+        &self,
+        after: i64,
+    ) -> Result<LoggedMutations<N, E, I>, StoreError> {
+        let mut statement = self.conn.prepare(
+            "SELECT rowid, id, mutation, origin FROM mutations_log
+                WHERE rowid > ?1 ORDER BY rowid",
+        )?;
+
+        let mutations_log_iter = statement
+            .query_map([after], |row| {
+                let cursor: i64 = row.get(0)?;
+                let hash: String = row.get(1)?;
+                let mutation: Vec<u8> = row.get(2)?;
+                let mutation: GraphMutation<N, E, I> = mutation
+                    .try_into()
+                    .map_err(|_| SqliteError::ExecuteReturnedResults)?;
+                let origin: String = row.get(3)?;
+
+                Ok((
+                    cursor,
+                    MutationsLogMutation {
+                        hash,
+                        origin,
+                        mutation,
+                        span: Span::none(),
+                    },
+                ))
+            })
+            .map_err(|err| StoreError::SqliteError(err.to_string()))?;
+
+        let mut mutations_log = Vec::new();
+
+        for row in mutations_log_iter {
+            match row {
+                Ok(mutation_entry) => mutations_log.push(mutation_entry),
+                Err(err) => log::error!(
+                    "Error while building MutationsLog \
+from sqlite file. Error: '{}'",
+                    err
+                ),
+            }
+        }
+
+        Ok(mutations_log)
+    }
+}
+
+/// Appends a mutation to the log, returning its position in the log.
+pub struct AppendMutation<N, E, I>(pub MutationsLogMutation<N, E, I>)
+where
+    N: GraphNode + 'static,
+    E: GraphEdge + 'static,
+    I: GraphNodeIndex + From<N> + 'static;
+
+impl<N, E, I> Message for AppendMutation<N, E, I>
+where
+    N: GraphNode + 'static,
+    E: GraphEdge + 'static,
+    I: GraphNodeIndex + From<N> + 'static,
+{
+    type Result = Result<i64, StoreError>;
 }
 
 impl<N, E, I> Actor for MutationsLogStore<N, E, I>
@@ -66,31 +163,37 @@ where
     type Context = Context<Self>;
 }
 
-impl<N, E, I> Handler<MutationsLogMutation<N, E, I>>
-    for MutationsLogStore<N, E, I>
+impl<N, E, I> Handler<AppendMutation<N, E, I>> for MutationsLogStore<N, E, I>
 where
     N: GraphNode + Unpin + 'static,
     E: GraphEdge + Unpin + 'static,
     I: GraphNodeIndex + From<N> + Unpin + 'static,
 {
-    type Result = Result<(), StoreError>;
+    type Result = Result<i64, StoreError>;
 
     fn handle(
         &mut self,
-        msg: MutationsLogMutation<N, E, I>,
+        msg: AppendMutation<N, E, I>,
         _ctx: &mut Self::Context,
     ) -> Self::Result {
-        let mutation: Vec<u8> = msg.mutation.try_into()?;
+        let AppendMutation(MutationsLogMutation {
+            hash,
+            origin,
+            mutation,
+            ..
+        }) = msg;
+        let mutation: Vec<u8> = mutation.try_into()?;
 
         self.conn
             .execute(
-                "INSERT INTO mutations_log (id, mutation)
-                            VALUES (?1, ?2)",
-                params![msg.hash, mutation],
+                "INSERT INTO mutations_log (id, mutation, origin)
+                            VALUES (?1, ?2, ?3)",
+                params![hash, mutation, origin],
             )
             .map_err(|err| StoreError::WriteLogError(err.to_string()))?;
+        self.length.inc();
 
-        Ok(())
+        Ok(self.conn.last_insert_rowid())
     }
 }
 
@@ -107,40 +210,61 @@ where
         _msg: MutationsLogQuery<N, E, I>,
         _ctx: &mut Self::Context,
     ) -> Self::Result {
-        let mut statement = self
-            .conn
-            .prepare("SELECT id, mutation FROM mutations_log")?;
+        let mutations_log = self
+            .get_mutations(0)?
+            .into_iter()
+            .map(|(_, mutation)| mutation)
+            .collect();
 
-        let mutations_log_iter = statement
-            .query_map([], |row| {
-                let hash: String = row.get(0)?;
-                let mutation: Vec<u8> = row.get(1)?;
-                let mutation: GraphMutation<N, E, I> = mutation
-                    .try_into()
-                    .map_err(|_| SqliteError::ExecuteReturnedResults)?;
+        Ok(mutations_log)
+    }
+}
 
-                Ok((hash, mutation))
-            })
-            .map_err(|err| StoreError::SqliteError(err.to_string()))?;
+impl<N, E, I> Handler<MutationsLogSince<N, E, I>> for MutationsLogStore<N, E, I>
+where
+    N: GraphNode + Unpin + 'static,
+    E: GraphEdge + Unpin + 'static,
+    I: GraphNodeIndex + From<N> + Unpin + 'static,
+{
+    type Result = Result<LoggedMutations<N, E, I>, StoreError>;
 
-        let mut mutations_log = Vec::new();
+    fn handle(
+        &mut self,
+        msg: MutationsLogSince<N, E, I>,
+        _ctx: &mut Self::Context,
+    ) -> Self::Result {
+        self.get_mutations(msg.cursor)
+    }
+}
 
-        for row in mutations_log_iter {
-            match row {
-                Ok(mutation_entry) => {
-                    mutations_log.push(MutationsLogMutation {
-                        hash: mutation_entry.0,
-                        mutation: mutation_entry.1,
-                    });
-                }
-                Err(err) => log::error!(
-                    "Error while building MutationsLog \
-from sqlite file. Error: '{}'",
-                    err
-                ),
-            }
-        }
+impl<N, E, I> Handler<MutationsLogStatsQuery> for MutationsLogStore<N, E, I>
+where
+    N: GraphNode + Unpin + 'static,
+    E: GraphEdge + Unpin + 'static,
+    I: GraphNodeIndex + From<N> + Unpin + 'static,
+{
+    type Result = Result<MutationsLogStats, StoreError>;
 
-        Ok(mutations_log)
+    fn handle(
+        &mut self,
+        _msg: MutationsLogStatsQuery,
+        _ctx: &mut Self::Context,
+    ) -> Self::Result {
+        let length = Self::count_mutations(&self.conn)?;
+
+        let last_hash: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT id FROM mutations_log ORDER BY rowid DESC LIMIT 1",
+                [],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        Ok(MutationsLogStats {
+            length: length as usize,
+            last_hash,
+            pending: Vec::new(),
+        })
     }
 }
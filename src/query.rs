@@ -0,0 +1,872 @@
+//! A small pattern matching language in the spirit of Cypher.
+//!
+//! A query consists of a path pattern optionally followed by `WHERE` and a
+//! conjunction of comparisons over the fields of bound nodes and edges:
+//!
+//! ```text
+//! MATCH (a)-[e]->(b)<-[]-(c) WHERE a.status = "Active" AND e.weight >= 2
+//! ```
+//!
+//! Fields are looked up in the serde representation of `N` and `E`, a bare
+//! variable compares the whole value, e.g. `e = 2` for `struct Edge(usize)`.
+//! Edges are written `-[e]->`, `<-[e]-` or `-[e]-` for either direction,
+//! the edge variable is optional. On an undirected graph every edge pattern
+//! matches either direction.
+
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::iter::Peekable;
+use std::str::Chars;
+
+use petgraph::stable_graph::{EdgeIndex, NodeIndex, StableGraph};
+use petgraph::visit::EdgeRef;
+use petgraph::{Directed, Direction};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{GraphEdge, GraphNode, GraphNodeIndex, StoreError};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query {
+    nodes: Vec<NodePattern>,
+    edges: Vec<EdgePattern>,
+    conditions: Vec<Condition>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct NodePattern {
+    variable: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct EdgePattern {
+    variable: Option<String>,
+    direction: Option<Direction>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Condition {
+    variable: String,
+    path: Vec<String>,
+    operator: Operator,
+    value: Value,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operator {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+/// Keys bound to the variables of a query for one match.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bindings<I> {
+    pub nodes: HashMap<String, I>,
+    pub edges: HashMap<String, (I, I)>,
+}
+
+impl Query {
+    pub fn parse(input: &str) -> Result<Self, StoreError> {
+        let tokens = tokenize(input)?;
+        Parser {
+            tokens,
+            position: 0,
+        }
+        .parse()
+    }
+
+    pub fn execute<N, E, I>(
+        &self,
+        graph: &StableGraph<N, E, Directed>,
+        undirected: bool,
+    ) -> Vec<Bindings<I>>
+    where
+        N: GraphNode,
+        E: GraphEdge,
+        I: GraphNodeIndex + From<N>,
+    {
+        let plan = self.plan();
+        let mut matcher = Matcher {
+            query: self,
+            graph,
+            undirected,
+            nodes: vec![None; self.nodes.len()],
+            edges: vec![None; self.edges.len()],
+            matches: Vec::new(),
+        };
+
+        for index in graph.node_indices() {
+            if matcher.bind_node(plan.anchor, index) {
+                matcher.expand(&plan.steps);
+                matcher.nodes[plan.anchor] = None;
+            }
+        }
+
+        matcher
+            .matches
+            .into_iter()
+            .map(|(nodes, edges)| self.get_bindings(graph, &nodes, &edges))
+            .collect()
+    }
+
+    //
+    // private methods
+    //
+
+    fn validate(&self) -> Result<(), StoreError> {
+        let node_variables: Vec<&String> = self
+            .nodes
+            .iter()
+            .filter_map(|n| n.variable.as_ref())
+            .collect();
+        let edge_variables: Vec<&String> = self
+            .edges
+            .iter()
+            .filter_map(|e| e.variable.as_ref())
+            .collect();
+
+        for (index, variable) in edge_variables.iter().enumerate() {
+            if node_variables.contains(variable)
+                || edge_variables[..index].contains(variable)
+            {
+                return Err(StoreError::QueryError(format!(
+                    "Variable '{variable}' is bound more than once"
+                )));
+            }
+        }
+
+        for condition in self.conditions.iter() {
+            if !node_variables.contains(&&condition.variable)
+                && !edge_variables.contains(&&condition.variable)
+            {
+                return Err(StoreError::QueryError(format!(
+                    "Unknown variable '{}'",
+                    condition.variable
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Starts at the node with the most conditions, then expands the pattern
+    /// to the right and afterwards to the left of it.
+    fn plan(&self) -> Plan {
+        let anchor = (0..self.nodes.len())
+            .rev()
+            .max_by_key(|position| {
+                self.nodes[*position]
+                    .variable
+                    .as_ref()
+                    .map(|variable| {
+                        self.conditions
+                            .iter()
+                            .filter(|c| &c.variable == variable)
+                            .count()
+                    })
+                    .unwrap_or_default()
+            })
+            .unwrap_or_default();
+
+        let right = (anchor..self.edges.len()).map(|edge| Step {
+            edge,
+            from: edge,
+            to: edge + 1,
+        });
+        let left = (0..anchor).rev().map(|edge| Step {
+            edge,
+            from: edge + 1,
+            to: edge,
+        });
+
+        Plan {
+            anchor,
+            steps: right.chain(left).collect(),
+        }
+    }
+
+    fn get_bindings<N, E, I>(
+        &self,
+        graph: &StableGraph<N, E, Directed>,
+        nodes: &[NodeIndex],
+        edges: &[EdgeIndex],
+    ) -> Bindings<I>
+    where
+        N: GraphNode,
+        E: GraphEdge,
+        I: GraphNodeIndex + From<N>,
+    {
+        let get_key = |index: NodeIndex| -> Option<I> {
+            graph.node_weight(index).map(|node| node.clone().into())
+        };
+
+        let nodes = self
+            .nodes
+            .iter()
+            .zip(nodes)
+            .filter_map(|(pattern, index)| {
+                Some((pattern.variable.clone()?, get_key(*index)?))
+            })
+            .collect();
+
+        let edges = self
+            .edges
+            .iter()
+            .zip(edges)
+            .filter_map(|(pattern, index)| {
+                let (source, target) = graph.edge_endpoints(*index)?;
+                Some((
+                    pattern.variable.clone()?,
+                    (get_key(source)?, get_key(target)?),
+                ))
+            })
+            .collect();
+
+        Bindings { nodes, edges }
+    }
+}
+
+struct Plan {
+    anchor: usize,
+    steps: Vec<Step>,
+}
+
+/// Traverses the edge at position `edge` from the bound node at position
+/// `from` to the node at position `to`.
+struct Step {
+    edge: usize,
+    from: usize,
+    to: usize,
+}
+
+struct Matcher<'a, N, E> {
+    query: &'a Query,
+    graph: &'a StableGraph<N, E, Directed>,
+    undirected: bool,
+    nodes: Vec<Option<NodeIndex>>,
+    edges: Vec<Option<EdgeIndex>>,
+    matches: Vec<(Vec<NodeIndex>, Vec<EdgeIndex>)>,
+}
+
+impl<'a, N, E> Matcher<'a, N, E>
+where
+    N: GraphNode,
+    E: GraphEdge,
+{
+    fn expand(&mut self, steps: &[Step]) {
+        let Some((step, rest)) = steps.split_first() else {
+            self.matches.push((
+                self.nodes.iter().flatten().copied().collect(),
+                self.edges.iter().flatten().copied().collect(),
+            ));
+            return;
+        };
+
+        let Some(from) = self.nodes[step.from] else {
+            return;
+        };
+
+        // pattern directions are written from left to right
+        let directions = match self.query.edges[step.edge].direction {
+            _ if self.undirected => {
+                vec![Direction::Outgoing, Direction::Incoming]
+            }
+            Some(dir) if step.from < step.to => vec![dir],
+            Some(dir) => vec![dir.opposite()],
+            None => vec![Direction::Outgoing, Direction::Incoming],
+        };
+
+        // a self loop is both an outgoing and an incoming edge of the node
+        let mut seen = HashSet::new();
+        let candidates: Vec<(EdgeIndex, NodeIndex)> = directions
+            .into_iter()
+            .flat_map(|dir| {
+                self.graph
+                    .edges_directed(from, dir)
+                    .map(move |e| match dir {
+                        Direction::Outgoing => (e.id(), e.target()),
+                        Direction::Incoming => (e.id(), e.source()),
+                    })
+            })
+            .filter(|(edge, _)| seen.insert(*edge))
+            .collect();
+
+        for (edge, node) in candidates {
+            if self.bind_edge(step.edge, edge) {
+                if self.bind_node(step.to, node) {
+                    self.expand(rest);
+                    self.nodes[step.to] = None;
+                }
+                self.edges[step.edge] = None;
+            }
+        }
+    }
+
+    fn bind_node(&mut self, position: usize, index: NodeIndex) -> bool {
+        let variable = &self.query.nodes[position].variable;
+
+        let consistent = self.query.nodes.iter().zip(self.nodes.iter()).all(
+            |(pattern, bound)| match bound {
+                Some(bound) if variable.is_some() => {
+                    &pattern.variable != variable || *bound == index
+                }
+                _ => true,
+            },
+        );
+
+        let matches = consistent
+            && match (variable, self.graph.node_weight(index)) {
+                (Some(variable), Some(node)) => self.check(variable, node),
+                (None, weight) => weight.is_some(),
+                _ => false,
+            };
+
+        if matches {
+            self.nodes[position] = Some(index);
+        }
+
+        matches
+    }
+
+    fn bind_edge(&mut self, position: usize, index: EdgeIndex) -> bool {
+        let variable = &self.query.edges[position].variable;
+
+        // an edge is traversed at most once per match
+        let unused = !self.edges.contains(&Some(index));
+
+        let matches = unused
+            && match (variable, self.graph.edge_weight(index)) {
+                (Some(variable), Some(edge)) => self.check(variable, edge),
+                (None, weight) => weight.is_some(),
+                _ => false,
+            };
+
+        if matches {
+            self.edges[position] = Some(index);
+        }
+
+        matches
+    }
+
+    fn check<T: Serialize>(&self, variable: &str, weight: &T) -> bool {
+        let mut conditions = self
+            .query
+            .conditions
+            .iter()
+            .filter(|condition| condition.variable == variable)
+            .peekable();
+
+        if conditions.peek().is_none() {
+            return true;
+        }
+
+        let value = match serde_json::to_value(weight) {
+            Ok(value) => value,
+            Err(err) => {
+                log::warn!(
+                    "Error serializing weight for query. Error: '{err}'"
+                );
+                return false;
+            }
+        };
+
+        conditions.all(|condition| condition.matches(&value))
+    }
+}
+
+impl Condition {
+    fn matches(&self, value: &Value) -> bool {
+        let field =
+            self.path
+                .iter()
+                .try_fold(value, |value, segment| match value {
+                    Value::Object(map) => map.get(segment),
+                    Value::Array(items) => {
+                        items.get(segment.parse::<usize>().ok()?)
+                    }
+                    _ => None,
+                });
+
+        let field = field.unwrap_or(&Value::Null);
+        let ordering = compare(field, &self.value);
+
+        match self.operator {
+            Operator::Eq => ordering == Some(Ordering::Equal),
+            Operator::Ne => ordering != Some(Ordering::Equal),
+            Operator::Lt => ordering == Some(Ordering::Less),
+            Operator::Le => {
+                matches!(ordering, Some(Ordering::Less | Ordering::Equal))
+            }
+            Operator::Gt => ordering == Some(Ordering::Greater),
+            Operator::Ge => {
+                matches!(ordering, Some(Ordering::Greater | Ordering::Equal))
+            }
+        }
+    }
+}
+
+fn compare(left: &Value, right: &Value) -> Option<Ordering> {
+    match (left, right) {
+        (Value::Number(left), Value::Number(right)) => {
+            left.as_f64()?.partial_cmp(&right.as_f64()?)
+        }
+        (Value::String(left), Value::String(right)) => Some(left.cmp(right)),
+        (Value::Bool(left), Value::Bool(right)) => Some(left.cmp(right)),
+        (left, right) if left == right => Some(Ordering::Equal),
+        _ => None,
+    }
+}
+
+//
+// parsing
+//
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+    Dash,
+    Dot,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+    Ident(String),
+    Number(String),
+    Str(String),
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, StoreError> {
+    let mut chars = input.chars().peekable();
+    let mut tokens = Vec::new();
+
+    while let Some(c) = chars.next() {
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '(' => Token::LParen,
+            ')' => Token::RParen,
+            '[' => Token::LBracket,
+            ']' => Token::RBracket,
+            '-' => Token::Dash,
+            '.' => Token::Dot,
+            '<' if chars.next_if_eq(&'=').is_some() => Token::Le,
+            '<' => Token::Lt,
+            '>' if chars.next_if_eq(&'=').is_some() => Token::Ge,
+            '>' => Token::Gt,
+            '=' => Token::Eq,
+            '!' if chars.next_if_eq(&'=').is_some() => Token::Ne,
+            '"' | '\'' => Token::Str(take_string(&mut chars, c)?),
+            c if c.is_ascii_digit() => {
+                let mut number = c.to_string();
+                while let Some(c) =
+                    chars.next_if(|c| c.is_ascii_digit() || *c == '.')
+                {
+                    number.push(c);
+                }
+                Token::Number(number)
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut ident = c.to_string();
+                while let Some(c) =
+                    chars.next_if(|c| c.is_alphanumeric() || *c == '_')
+                {
+                    ident.push(c);
+                }
+                Token::Ident(ident)
+            }
+            c => {
+                return Err(StoreError::QueryError(format!(
+                    "Unexpected character '{c}'"
+                )))
+            }
+        };
+
+        tokens.push(token);
+    }
+
+    Ok(tokens)
+}
+
+fn take_string(
+    chars: &mut Peekable<Chars>,
+    quote: char,
+) -> Result<String, StoreError> {
+    let mut string = String::new();
+
+    loop {
+        match chars.next() {
+            Some('\\') => match chars.next() {
+                Some(c) => string.push(c),
+                None => break,
+            },
+            Some(c) if c == quote => return Ok(string),
+            Some(c) => string.push(c),
+            None => break,
+        }
+    }
+
+    Err(StoreError::QueryError("Unterminated string".to_string()))
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn parse(mut self) -> Result<Query, StoreError> {
+        self.keyword("MATCH");
+
+        let mut nodes = vec![self.node()?];
+        let mut edges = Vec::new();
+
+        while matches!(self.peek(), Some(Token::Dash | Token::Lt)) {
+            edges.push(self.edge()?);
+            nodes.push(self.node()?);
+        }
+
+        let mut conditions = Vec::new();
+
+        if self.keyword("WHERE") {
+            conditions.push(self.condition()?);
+
+            while self.keyword("AND") {
+                conditions.push(self.condition()?);
+            }
+        }
+
+        if let Some(token) = self.peek() {
+            return Err(StoreError::QueryError(format!(
+                "Unexpected token {token:?}"
+            )));
+        }
+
+        let query = Query {
+            nodes,
+            edges,
+            conditions,
+        };
+        query.validate()?;
+
+        Ok(query)
+    }
+
+    fn node(&mut self) -> Result<NodePattern, StoreError> {
+        self.expect(Token::LParen)?;
+        let variable = self.variable();
+        self.expect(Token::RParen)?;
+
+        Ok(NodePattern { variable })
+    }
+
+    fn edge(&mut self) -> Result<EdgePattern, StoreError> {
+        let incoming = self.next_if(Token::Lt);
+        self.expect(Token::Dash)?;
+        self.expect(Token::LBracket)?;
+        let variable = self.variable();
+        self.expect(Token::RBracket)?;
+        self.expect(Token::Dash)?;
+        let outgoing = self.next_if(Token::Gt);
+
+        let direction = match (incoming, outgoing) {
+            (false, true) => Some(Direction::Outgoing),
+            (true, false) => Some(Direction::Incoming),
+            (false, false) => None,
+            (true, true) => {
+                return Err(StoreError::QueryError(
+                    "Edge can't point in both directions".to_string(),
+                ))
+            }
+        };
+
+        Ok(EdgePattern {
+            variable,
+            direction,
+        })
+    }
+
+    fn condition(&mut self) -> Result<Condition, StoreError> {
+        let variable = self.variable().ok_or_else(|| {
+            StoreError::QueryError("Expected variable in condition".into())
+        })?;
+
+        let mut path = Vec::new();
+
+        while self.next_if(Token::Dot) {
+            match self.next() {
+                Some(Token::Ident(segment) | Token::Number(segment)) => {
+                    path.push(segment)
+                }
+                token => {
+                    return Err(StoreError::QueryError(format!(
+                        "Expected field name, got {token:?}"
+                    )))
+                }
+            }
+        }
+
+        let operator = match self.next() {
+            Some(Token::Eq) => Operator::Eq,
+            Some(Token::Ne) => Operator::Ne,
+            Some(Token::Lt) => Operator::Lt,
+            Some(Token::Le) => Operator::Le,
+            Some(Token::Gt) => Operator::Gt,
+            Some(Token::Ge) => Operator::Ge,
+            token => {
+                return Err(StoreError::QueryError(format!(
+                    "Expected comparison operator, got {token:?}"
+                )))
+            }
+        };
+
+        let value = self.literal()?;
+
+        Ok(Condition {
+            variable,
+            path,
+            operator,
+            value,
+        })
+    }
+
+    fn literal(&mut self) -> Result<Value, StoreError> {
+        let negative = self.next_if(Token::Dash);
+
+        match (negative, self.next()) {
+            (false, Some(Token::Str(string))) => Ok(Value::String(string)),
+            (_, Some(Token::Number(number))) => {
+                let number = if negative {
+                    format!("-{number}")
+                } else {
+                    number
+                };
+
+                serde_json::from_str(&number).map_err(|_| {
+                    StoreError::QueryError(format!("Invalid number {number}"))
+                })
+            }
+            (false, Some(Token::Ident(ident))) => {
+                match ident.to_lowercase().as_str() {
+                    "true" => Ok(Value::Bool(true)),
+                    "false" => Ok(Value::Bool(false)),
+                    "null" => Ok(Value::Null),
+                    _ => Err(StoreError::QueryError(format!(
+                        "Expected literal, got '{ident}'"
+                    ))),
+                }
+            }
+            (_, token) => Err(StoreError::QueryError(format!(
+                "Expected literal, got {token:?}"
+            ))),
+        }
+    }
+
+    fn variable(&mut self) -> Option<String> {
+        match self.peek() {
+            Some(Token::Ident(ident)) => {
+                let ident = ident.clone();
+                self.position += 1;
+                Some(ident)
+            }
+            _ => None,
+        }
+    }
+
+    fn keyword(&mut self, keyword: &str) -> bool {
+        match self.peek() {
+            Some(Token::Ident(ident))
+                if ident.eq_ignore_ascii_case(keyword) =>
+            {
+                self.position += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), StoreError> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            token => Err(StoreError::QueryError(format!(
+                "Expected {expected:?}, got {token:?}"
+            ))),
+        }
+    }
+
+    fn next_if(&mut self, expected: Token) -> bool {
+        let matches = self.peek() == Some(&expected);
+        if matches {
+            self.position += 1;
+        }
+        matches
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(
+        Debug, Default, Clone, PartialEq, PartialOrd, Serialize, Deserialize,
+    )]
+    struct Edge(usize);
+    impl GraphEdge for Edge {}
+
+    #[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+    struct Node {
+        id: usize,
+        kind: String,
+    }
+    impl GraphNode for Node {}
+
+    #[derive(
+        Debug,
+        Default,
+        Clone,
+        Hash,
+        PartialEq,
+        Eq,
+        PartialOrd,
+        Ord,
+        Serialize,
+        Deserialize,
+    )]
+    struct NodeId(usize);
+    impl GraphNodeIndex for NodeId {}
+
+    impl From<Node> for NodeId {
+        fn from(node: Node) -> Self {
+            Self(node.id)
+        }
+    }
+
+    // users 0 and 1 follow user 2 (edge 1), user 2 bought product 3 (edge 2)
+    fn get_graph() -> StableGraph<Node, Edge, Directed> {
+        let mut graph = StableGraph::default();
+        let kinds = ["user", "user", "user", "product"];
+        let nodes: Vec<_> = kinds
+            .iter()
+            .enumerate()
+            .map(|(id, kind)| {
+                graph.add_node(Node {
+                    id,
+                    kind: kind.to_string(),
+                })
+            })
+            .collect();
+
+        graph.add_edge(nodes[0], nodes[2], Edge(1));
+        graph.add_edge(nodes[1], nodes[2], Edge(1));
+        graph.add_edge(nodes[2], nodes[3], Edge(2));
+
+        graph
+    }
+
+    #[test]
+    fn test_parse_nok() {
+        assert!(Query::parse("(a)-[e]->").is_err());
+        assert!(Query::parse("(a)<-[e]->(b)").is_err());
+        assert!(Query::parse("(a)-[a]->(b)").is_err());
+        assert!(Query::parse("(a) WHERE b.id = 1").is_err());
+        assert!(Query::parse("(a) WHERE a.id = \"1").is_err());
+    }
+
+    #[test]
+    fn test_execute_path_ok() {
+        let query = Query::parse(
+            "MATCH (a)-[f]->(b)-[]->(c) \
+WHERE f = 1 AND c.kind = 'product' AND a.id != 1",
+        )
+        .unwrap();
+
+        let bindings: Vec<Bindings<NodeId>> =
+            query.execute(&get_graph(), false);
+
+        assert_eq!(bindings.len(), 1);
+        assert_eq!(bindings[0].nodes["a"], NodeId(0));
+        assert_eq!(bindings[0].nodes["c"], NodeId(3));
+        assert_eq!(bindings[0].edges["f"], (NodeId(0), NodeId(2)));
+    }
+
+    #[test]
+    fn test_execute_directions_ok() {
+        let graph = get_graph();
+
+        let query = Query::parse("(a)<-[]-(b) WHERE a.id = 2").unwrap();
+        let bindings: Vec<Bindings<NodeId>> = query.execute(&graph, false);
+        assert_eq!(bindings.len(), 2);
+
+        let query = Query::parse("(a)-[]-(b) WHERE a.id = 2").unwrap();
+        let bindings: Vec<Bindings<NodeId>> = query.execute(&graph, false);
+        assert_eq!(bindings.len(), 3);
+
+        let query = Query::parse("(a)-[]->(b)<-[]-(a)").unwrap();
+        let bindings: Vec<Bindings<NodeId>> = query.execute(&graph, false);
+        assert!(bindings.is_empty());
+    }
+
+    #[test]
+    fn test_execute_undirected_ok() {
+        let graph = get_graph();
+
+        let query = Query::parse("(a)-[e]->(b) WHERE a.id = 2").unwrap();
+        let bindings: Vec<Bindings<NodeId>> = query.execute(&graph, true);
+        assert_eq!(bindings.len(), 3);
+        // edges are bound with the direction they were added with
+        assert!(bindings
+            .iter()
+            .any(|b| b.edges["e"] == (NodeId(0), NodeId(2))));
+
+        let query = Query::parse("(a)<-[]-(b) WHERE a.id = 3").unwrap();
+        let bindings: Vec<Bindings<NodeId>> = query.execute(&graph, true);
+        assert_eq!(bindings.len(), 1);
+        let bindings: Vec<Bindings<NodeId>> = query.execute(&graph, false);
+        assert_eq!(bindings.len(), 1);
+
+        let query = Query::parse("(a)-[]->(b) WHERE b.id = 0").unwrap();
+        let bindings: Vec<Bindings<NodeId>> = query.execute(&graph, true);
+        assert_eq!(bindings.len(), 1);
+        let bindings: Vec<Bindings<NodeId>> = query.execute(&graph, false);
+        assert!(bindings.is_empty());
+    }
+
+    #[test]
+    fn test_execute_self_loop_ok() {
+        let mut graph = get_graph();
+        let user = graph
+            .node_indices()
+            .find(|index| graph[*index].id == 2)
+            .unwrap();
+        graph.add_edge(user, user, Edge(3));
+
+        let query =
+            Query::parse("(a)-[e]->(b) WHERE a.id = 2 AND e = 3").unwrap();
+        let bindings: Vec<Bindings<NodeId>> = query.execute(&graph, true);
+        assert_eq!(bindings.len(), 1);
+        assert_eq!(bindings[0].nodes["b"], NodeId(2));
+
+        let query = Query::parse("(a)-[e]-(b) WHERE e = 3").unwrap();
+        let bindings: Vec<Bindings<NodeId>> = query.execute(&graph, false);
+        assert_eq!(bindings.len(), 1);
+    }
+}
@@ -1,12 +1,17 @@
 use std::collections::HashMap;
 
 use actix::{
-    Actor, ActorFutureExt, Addr, Context, Handler, Message, ResponseActFuture,
-    WrapFuture,
+    Actor, ActorFutureExt, Addr, Context, Handler, Message, MessageResult,
+    ResponseActFuture, WrapFuture,
 };
 use actix_interop::FutureInterop;
 use rand::seq::SliceRandom;
+use tonic::transport::ClientTlsConfig;
+use tracing::{info_span, Instrument};
 
+use crate::auth::AuthConfig;
+use crate::graph::GraphMode;
+use crate::metrics;
 use crate::mutations_log::{MutationsLogMutation, MutationsLogQuery};
 use crate::{GraphClient, GraphEdge, GraphNode, GraphNodeIndex, StoreError};
 
@@ -39,6 +44,9 @@ where
 {
     remotes: HashMap<String, RemotesEntry<N, E, I>>,
     sync_with_n: usize,
+    tls: Option<ClientTlsConfig>,
+    auth: Option<AuthConfig>,
+    mode: GraphMode,
 }
 
 impl<N, E, I> Remotes<N, E, I>
@@ -47,10 +55,18 @@ where
     E: GraphEdge + Unpin + 'static,
     I: GraphNodeIndex + From<N> + Unpin + 'static,
 {
-    pub fn new(sync_with_n: usize) -> Self {
+    pub fn new(
+        sync_with_n: usize,
+        tls: Option<ClientTlsConfig>,
+        auth: Option<AuthConfig>,
+        mode: GraphMode,
+    ) -> Self {
         Self {
             remotes: HashMap::new(),
             sync_with_n,
+            tls,
+            auth,
+            mode,
         }
     }
 }
@@ -80,28 +96,50 @@ where
     ) -> Self::Result {
         let sync_with_n = self.sync_with_n.clone();
         let mut rng = rand::thread_rng();
-        let random_remotes: Vec<Addr<_>> = self
+        let random_remotes: Vec<(String, Addr<_>)> = self
             .remotes
             .clone()
             .into_iter()
-            .filter_map(|(_, remotes_entry)| {
+            .filter_map(|(remote, remotes_entry)| {
                 remotes_entry
                     .remotes_log
                     .ok()
-                    .map(|_| remotes_entry.client_addr)
+                    .map(|_| (remote, remotes_entry.client_addr))
             })
             .collect();
+        let span = info_span!(parent: &msg.span, "replicate_mutation");
+
+        let future = Box::pin(
+            async move {
+                let random_remotes = random_remotes
+                    .choose_multiple(&mut rng, sync_with_n.into());
+
+                for (remote, addr) in random_remotes {
+                    let span = info_span!("send_to_remote", remote = %remote);
+                    let msg = MutationsLogMutation {
+                        span: span.clone(),
+                        ..msg.clone()
+                    };
+
+                    let result = match addr.send(msg).instrument(span).await {
+                        Ok(result) => result,
+                        Err(err) => Err(StoreError::from(err)),
+                    };
+
+                    metrics::REPLICATIONS
+                        .with_label_values(&[
+                            remote.as_str(),
+                            metrics::result_label(&result),
+                        ])
+                        .inc();
+
+                    result?;
+                }
 
-        let future = Box::pin(async move {
-            let random_remotes =
-                random_remotes.choose_multiple(&mut rng, sync_with_n.into());
-
-            for addr in random_remotes {
-                addr.send(msg.clone()).await??;
+                Ok(())
             }
-
-            Ok(())
-        });
+            .instrument(span),
+        );
         let actor_future = future.into_actor(self);
 
         Box::pin(actor_future)
@@ -128,6 +166,9 @@ where
         } = msg;
 
         let mut remotes = self.remotes.clone();
+        let tls = self.tls.clone();
+        let auth = self.auth.clone();
+        let mode = self.mode;
 
         let future = async move {
             let from = from.clone();
@@ -137,7 +178,9 @@ where
                 let mut entry = entry.clone();
                 entry.remotes_log = Ok(flat_remotes_log.clone());
                 remotes.insert(from.clone(), entry);
-            } else if let Ok(client) = GraphClient::new(from.clone()).await {
+            } else if let Ok(client) =
+                GraphClient::new(from.clone(), tls, auth, mode).await
+            {
                 let client_addr = client.start();
 
                 let remotes_entry = RemotesEntry {
@@ -181,6 +224,47 @@ where
     }
 }
 
+/// Known remote along with the remotes log it last reported, or the error
+/// synchronizing the remotes log with it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteStatus {
+    pub address: String,
+    pub remotes_log: Result<HashMap<String, bool>, StoreError>,
+}
+
+pub struct RemotesStatusQuery;
+
+impl Message for RemotesStatusQuery {
+    type Result = Vec<RemoteStatus>;
+}
+
+impl<N, E, I> Handler<RemotesStatusQuery> for Remotes<N, E, I>
+where
+    N: GraphNode + Unpin + 'static,
+    E: GraphEdge + Unpin + 'static,
+    I: GraphNodeIndex + From<N> + Unpin + 'static,
+{
+    type Result = MessageResult<RemotesStatusQuery>;
+
+    fn handle(
+        &mut self,
+        _msg: RemotesStatusQuery,
+        _ctx: &mut Self::Context,
+    ) -> Self::Result {
+        let mut remotes: Vec<RemoteStatus> = self
+            .remotes
+            .iter()
+            .map(|(address, entry)| RemoteStatus {
+                address: address.clone(),
+                remotes_log: entry.remotes_log.clone(),
+            })
+            .collect();
+        remotes.sort_by(|a, b| a.address.cmp(&b.address));
+
+        MessageResult(remotes)
+    }
+}
+
 pub struct InitializeRemotes {
     pub initial_addresses: Vec<String>,
     pub server_address: String,
@@ -208,11 +292,21 @@ where
         let mut remote_addresses: HashMap<String, Addr<_>> = HashMap::new();
         let mut initial_flat_remotes_log: HashMap<String, bool> =
             HashMap::new();
+        let tls = self.tls.clone();
+        let auth = self.auth.clone();
+        let mode = self.mode;
 
         let future = Box::pin(async move {
             // build up initial clients
             for address in initial_addresses {
-                if let Ok(client) = GraphClient::new(address.clone()).await {
+                if let Ok(client) = GraphClient::new(
+                    address.clone(),
+                    tls.clone(),
+                    auth.clone(),
+                    mode,
+                )
+                .await
+                {
                     let client_addr = client.start();
 
                     remote_addresses.insert(address.clone(), client_addr);
@@ -285,11 +379,15 @@ where
         _msg: MutationsLogQuery<N, E, I>,
         _ctx: &mut Self::Context,
     ) -> Self::Result {
+        // remotes which failed to sync, e.g. running in another graph mode,
+        // aren't replayed from
         let clients: Vec<Addr<GraphClient<N, E, I>>> = self
             .remotes
             .clone()
             .into_iter()
-            .map(|(_, entry)| entry.client_addr)
+            .filter_map(|(_, entry)| {
+                entry.remotes_log.ok().map(|_| entry.client_addr)
+            })
             .collect();
 
         async move {
@@ -0,0 +1,917 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::net::SocketAddr;
+
+use actix_web::http::StatusCode;
+use actix_web::{
+    rt, web, App, HttpRequest, HttpResponse, HttpServer, ResponseError,
+};
+use actix_ws::{Message, MessageStream, Session};
+use futures_util::StreamExt;
+
+use petgraph::Direction;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AppliedMutation, DeletionPolicy, GraphDatabase, GraphEdge, GraphMutation,
+    GraphNode, GraphNodeIndex, StoreError, SubscriptionFilter,
+};
+
+/// HTTP/JSON gateway to the graph, nodes, edges and keys are (de)serialized
+/// as JSON with their serde implementations.
+///
+/// - `GET /nodes`, `POST /nodes`
+/// - `GET /nodes/{key}`, `DELETE /nodes/{key}?policy=restrict|detach|cascade`
+/// - `GET /nodes/{key}/neighbors?dir=out|in|both`
+/// - `GET /edges`, `POST /edges`
+/// - `GET /edges/{from}/{to}`, `DELETE /edges/{from}/{to}`
+/// - `GET /live?root={key}&depth={depth}`, websocket of live updates
+///
+/// Keys in paths are parsed as JSON, falling back to a JSON string.
+pub struct RestGateway<N, E, I>
+where
+    N: GraphNode + Unpin + 'static,
+    E: GraphEdge + Unpin + 'static,
+    I: GraphNodeIndex + From<N> + Unpin + 'static,
+{
+    database: GraphDatabase<N, E, I>,
+}
+
+impl<N, E, I> RestGateway<N, E, I>
+where
+    N: GraphNode + Unpin + 'static,
+    E: GraphEdge + Unpin + 'static,
+    I: GraphNodeIndex + From<N> + Unpin + 'static,
+{
+    pub fn run(
+        address: SocketAddr,
+        database: GraphDatabase<N, E, I>,
+    ) -> Result<(), StoreError> {
+        let gateway = web::Data::new(Self { database });
+
+        let server = HttpServer::new(move || {
+            App::new().app_data(gateway.clone()).configure(Self::routes)
+        })
+        .disable_signals()
+        .bind(address)
+        .map_err(|err| StoreError::ServerError(err.to_string()))?
+        .run();
+
+        tokio::spawn(async move {
+            if let Err(err) = server.await {
+                log::error!("Error while running RestGateway. Error: '{err}'");
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Routes of the gateway, served with the gateway as app data.
+    fn routes(config: &mut web::ServiceConfig) {
+        config
+            .route("/nodes", web::get().to(get_nodes::<N, E, I>))
+            .route("/nodes", web::post().to(add_node::<N, E, I>))
+            .route("/nodes/{key}", web::get().to(get_node::<N, E, I>))
+            .route("/nodes/{key}", web::delete().to(remove_node::<N, E, I>))
+            .route(
+                "/nodes/{key}/neighbors",
+                web::get().to(get_neighbors::<N, E, I>),
+            )
+            .route("/edges", web::get().to(get_edges::<N, E, I>))
+            .route("/edges", web::post().to(add_edge::<N, E, I>))
+            .route("/edges/{from}/{to}", web::get().to(get_edge::<N, E, I>))
+            .route(
+                "/edges/{from}/{to}",
+                web::delete().to(remove_edge::<N, E, I>),
+            )
+            .route("/live", web::get().to(live::<N, E, I>));
+    }
+}
+
+type Gateway<N, E, I> = web::Data<RestGateway<N, E, I>>;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct EdgeBody<I, E> {
+    from: I,
+    to: I,
+    edge: E,
+}
+
+#[derive(Debug, Serialize)]
+struct RemovedNodeBody<N, I, E> {
+    node: N,
+    edges: Vec<EdgeBody<I, E>>,
+}
+
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Dir {
+    Out,
+    In,
+    #[default]
+    Both,
+}
+
+#[derive(Debug, Deserialize)]
+struct NeighborsParams {
+    #[serde(default)]
+    dir: Dir,
+}
+
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Policy {
+    #[default]
+    Restrict,
+    Detach,
+    Cascade,
+}
+
+#[derive(Debug, Deserialize)]
+struct RemoveNodeParams {
+    #[serde(default)]
+    policy: Policy,
+}
+
+async fn get_nodes<N, E, I>(
+    gateway: Gateway<N, E, I>,
+) -> Result<HttpResponse, HttpError>
+where
+    N: GraphNode + Unpin + 'static,
+    E: GraphEdge + Unpin + 'static,
+    I: GraphNodeIndex + From<N> + Unpin + 'static,
+{
+    let nodes = gateway.database.get_nodes().await?;
+
+    Ok(HttpResponse::Ok().json(nodes))
+}
+
+async fn add_node<N, E, I>(
+    gateway: Gateway<N, E, I>,
+    node: web::Json<N>,
+) -> Result<HttpResponse, HttpError>
+where
+    N: GraphNode + Unpin + 'static,
+    E: GraphEdge + Unpin + 'static,
+    I: GraphNodeIndex + From<N> + Unpin + 'static,
+{
+    let node = gateway.database.add_node(node.into_inner()).await?;
+
+    Ok(HttpResponse::Created().json(node))
+}
+
+async fn get_node<N, E, I>(
+    gateway: Gateway<N, E, I>,
+    key: web::Path<String>,
+) -> Result<HttpResponse, HttpError>
+where
+    N: GraphNode + Unpin + 'static,
+    E: GraphEdge + Unpin + 'static,
+    I: GraphNodeIndex + From<N> + Unpin + 'static,
+{
+    let node = gateway.database.get_node(parse_key(&key)?).await?;
+
+    Ok(HttpResponse::Ok().json(node))
+}
+
+async fn remove_node<N, E, I>(
+    gateway: Gateway<N, E, I>,
+    key: web::Path<String>,
+    params: web::Query<RemoveNodeParams>,
+) -> Result<HttpResponse, HttpError>
+where
+    N: GraphNode + Unpin + 'static,
+    E: GraphEdge + Unpin + 'static,
+    I: GraphNodeIndex + From<N> + Unpin + 'static,
+{
+    let key: I = parse_key(&key)?;
+    let policy = match params.policy {
+        Policy::Restrict => DeletionPolicy::Restrict,
+        Policy::Detach => DeletionPolicy::Detach,
+        Policy::Cascade => DeletionPolicy::Cascade,
+    };
+
+    let (node, edges) = gateway.database.remove_node(key, policy).await?;
+
+    Ok(HttpResponse::Ok().json(RemovedNodeBody {
+        node,
+        edges: edges
+            .into_iter()
+            .map(|(from, to, edge)| EdgeBody { from, to, edge })
+            .collect(),
+    }))
+}
+
+async fn get_neighbors<N, E, I>(
+    gateway: Gateway<N, E, I>,
+    key: web::Path<String>,
+    params: web::Query<NeighborsParams>,
+) -> Result<HttpResponse, HttpError>
+where
+    N: GraphNode + Unpin + 'static,
+    E: GraphEdge + Unpin + 'static,
+    I: GraphNodeIndex + From<N> + Unpin + 'static,
+{
+    let key: I = parse_key(&key)?;
+    let database = &gateway.database;
+
+    let nodes = match params.dir {
+        Dir::Out => database.get_neighbors_dir(key, Direction::Outgoing).await,
+        Dir::In => database.get_neighbors_dir(key, Direction::Incoming).await,
+        Dir::Both => database.get_neighbors_und(key).await,
+    }?;
+
+    Ok(HttpResponse::Ok().json(nodes))
+}
+
+async fn get_edges<N, E, I>(
+    gateway: Gateway<N, E, I>,
+) -> Result<HttpResponse, HttpError>
+where
+    N: GraphNode + Unpin + 'static,
+    E: GraphEdge + Unpin + 'static,
+    I: GraphNodeIndex + From<N> + Unpin + 'static,
+{
+    let edges: Vec<_> = gateway
+        .database
+        .get_edges_with_endpoints()
+        .await?
+        .into_iter()
+        .map(|(from, to, edge)| EdgeBody { from, to, edge })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(edges))
+}
+
+async fn add_edge<N, E, I>(
+    gateway: Gateway<N, E, I>,
+    body: web::Json<EdgeBody<I, E>>,
+) -> Result<HttpResponse, HttpError>
+where
+    N: GraphNode + Unpin + 'static,
+    E: GraphEdge + Unpin + 'static,
+    I: GraphNodeIndex + From<N> + Unpin + 'static,
+{
+    let EdgeBody { from, to, edge } = body.into_inner();
+
+    gateway.database.add_edge(from, to, edge).await?;
+
+    Ok(HttpResponse::Created().finish())
+}
+
+async fn get_edge<N, E, I>(
+    gateway: Gateway<N, E, I>,
+    path: web::Path<(String, String)>,
+) -> Result<HttpResponse, HttpError>
+where
+    N: GraphNode + Unpin + 'static,
+    E: GraphEdge + Unpin + 'static,
+    I: GraphNodeIndex + From<N> + Unpin + 'static,
+{
+    let (from, to) = path.into_inner();
+    let edge = gateway
+        .database
+        .get_edge(parse_key(&from)?, parse_key(&to)?)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(edge))
+}
+
+async fn remove_edge<N, E, I>(
+    gateway: Gateway<N, E, I>,
+    path: web::Path<(String, String)>,
+) -> Result<HttpResponse, HttpError>
+where
+    N: GraphNode + Unpin + 'static,
+    E: GraphEdge + Unpin + 'static,
+    I: GraphNodeIndex + From<N> + Unpin + 'static,
+{
+    let (from, to) = path.into_inner();
+    let edge = gateway
+        .database
+        .remove_edge(parse_key::<I>(&from)?, parse_key::<I>(&to)?)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(edge))
+}
+
+//
+// live updates
+//
+
+/// Message sent over the `/live` websocket, tagged by `type`. The
+/// `snapshot` of the graph, or of the neighborhood of `root` within `depth`
+/// hops, is followed by the applied mutations touching it. Mutations
+/// committed while taking the snapshot may be sent although the snapshot
+/// already contains them, and removing a node implies removing its edges.
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum LiveMessage<N, E, I> {
+    Snapshot {
+        nodes: Vec<N>,
+        edges: Vec<EdgeBody<I, E>>,
+    },
+    AddNode {
+        cursor: i64,
+        node: N,
+    },
+    RemoveNode {
+        cursor: i64,
+        key: I,
+    },
+    AddEdge {
+        cursor: i64,
+        from: I,
+        to: I,
+        edge: E,
+    },
+    RemoveEdge {
+        cursor: i64,
+        from: I,
+        to: I,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        key: Option<String>,
+    },
+    Error {
+        error: StoreError,
+    },
+}
+
+#[derive(Debug, Deserialize)]
+struct LiveParams {
+    root: Option<String>,
+    #[serde(default = "default_depth")]
+    depth: usize,
+}
+
+fn default_depth() -> usize {
+    1
+}
+
+async fn live<N, E, I>(
+    gateway: Gateway<N, E, I>,
+    params: web::Query<LiveParams>,
+    request: HttpRequest,
+    body: web::Payload,
+) -> Result<HttpResponse, actix_web::Error>
+where
+    N: GraphNode + Unpin + 'static,
+    E: GraphEdge + Unpin + 'static,
+    I: GraphNodeIndex + From<N> + Unpin + 'static,
+{
+    let root: Option<I> = match &params.root {
+        Some(root) => Some(parse_key(root)?),
+        None => None,
+    };
+
+    let (response, session, messages) = actix_ws::handle(&request, body)?;
+    let database = gateway.database.clone();
+    let depth = params.depth;
+
+    rt::spawn(async move {
+        if let Err(err) =
+            send_live_updates(database, root, depth, session, messages).await
+        {
+            log::debug!("Live updates websocket closed. Error: '{err:?}'");
+        }
+    });
+
+    Ok(response)
+}
+
+async fn send_live_updates<N, E, I>(
+    database: GraphDatabase<N, E, I>,
+    root: Option<I>,
+    depth: usize,
+    mut session: Session,
+    mut messages: MessageStream,
+) -> Result<(), StoreError>
+where
+    N: GraphNode + Unpin + 'static,
+    E: GraphEdge + Unpin + 'static,
+    I: GraphNodeIndex + From<N> + Unpin + 'static,
+{
+    // subscribe before the snapshot to not miss mutations in between
+    let updates = database.subscribe(SubscriptionFilter::default()).await?;
+    let mut updates = Box::pin(updates);
+
+    let (nodes, edges) = match &root {
+        Some(root) => database.get_neighborhood(root.clone(), depth).await?,
+        None => (
+            database.get_nodes().await?,
+            database.get_edges_with_endpoints().await?,
+        ),
+    };
+
+    // with a root only updates touching the nodes in sight are sent
+    let mut visible: Option<HashSet<I>> = root
+        .as_ref()
+        .map(|_| nodes.iter().map(|node| I::from(node.clone())).collect());
+
+    let snapshot = LiveMessage::Snapshot {
+        nodes,
+        edges: edges
+            .into_iter()
+            .map(|(from, to, edge)| EdgeBody { from, to, edge })
+            .collect(),
+    };
+    send_live_message(&mut session, &snapshot).await?;
+
+    loop {
+        tokio::select! {
+            message = messages.recv() => match message {
+                Some(Ok(Message::Ping(bytes))) => {
+                    if session.pong(&bytes).await.is_err() {
+                        break;
+                    }
+                }
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+            update = updates.next() => match update {
+                Some(Ok(applied)) => {
+                    if let Some(message) = live_message(applied, &mut visible) {
+                        send_live_message(&mut session, &message).await?;
+                    }
+                }
+                Some(Err(error)) => {
+                    send_live_message(
+                        &mut session,
+                        &LiveMessage::<N, E, I>::Error { error },
+                    )
+                    .await?;
+                    break;
+                }
+                None => break,
+            },
+        }
+    }
+
+    let _ = session.close(None).await;
+
+    Ok(())
+}
+
+/// Live message for an applied mutation, `None` for failed mutations and
+/// mutations not touching the `visible` nodes. Edges added to visible nodes
+/// make the nodes at their other end visible.
+fn live_message<N, E, I>(
+    applied: AppliedMutation<N, E, I>,
+    visible: &mut Option<HashSet<I>>,
+) -> Option<LiveMessage<N, E, I>>
+where
+    N: GraphNode + Unpin + 'static,
+    E: GraphEdge + Unpin + 'static,
+    I: GraphNodeIndex + From<N> + Unpin + 'static,
+{
+    if applied.result.is_err() {
+        return None;
+    }
+
+    if let Some(visible) = visible {
+        let keys = applied.mutation.keys();
+        if !keys.iter().any(|key| visible.contains(key)) {
+            return None;
+        }
+
+        match &applied.mutation {
+            GraphMutation::AddEdge(_) => visible.extend(keys),
+            GraphMutation::RemoveNode(key)
+            | GraphMutation::RemoveNodeWithPolicy((key, _)) => {
+                visible.remove(key);
+            }
+            _ => {}
+        }
+    }
+
+    let cursor = applied.cursor;
+
+    Some(match applied.mutation {
+        GraphMutation::AddNode(node) => LiveMessage::AddNode { cursor, node },
+        GraphMutation::RemoveNode(key)
+        | GraphMutation::RemoveNodeWithPolicy((key, _)) => {
+            LiveMessage::RemoveNode { cursor, key }
+        }
+        GraphMutation::AddEdge((from, to, edge)) => LiveMessage::AddEdge {
+            cursor,
+            from,
+            to,
+            edge,
+        },
+        GraphMutation::RemoveEdge((from, to)) => LiveMessage::RemoveEdge {
+            cursor,
+            from,
+            to,
+            key: None,
+        },
+        GraphMutation::RemoveEdgeByKey((from, to, key)) => {
+            LiveMessage::RemoveEdge {
+                cursor,
+                from,
+                to,
+                key: Some(key),
+            }
+        }
+    })
+}
+
+async fn send_live_message<N, E, I>(
+    session: &mut Session,
+    message: &LiveMessage<N, E, I>,
+) -> Result<(), StoreError>
+where
+    N: Serialize,
+    E: Serialize,
+    I: Serialize,
+{
+    let text = serde_json::to_string(message)
+        .map_err(|err| StoreError::Serde(err.to_string()))?;
+
+    session
+        .text(text)
+        .await
+        .map_err(|err| StoreError::ServerError(err.to_string()))
+}
+
+//
+// helpers
+//
+
+/// Parses a key from a path segment, as JSON or else as a JSON string so
+/// string keys don't need to be quoted.
+fn parse_key<I: DeserializeOwned>(segment: &str) -> Result<I, HttpError> {
+    serde_json::from_str(segment)
+        .or_else(|_| {
+            serde_json::from_value(serde_json::Value::String(
+                segment.to_string(),
+            ))
+        })
+        .map_err(|_| HttpError(StoreError::ParseError))
+}
+
+/// [`StoreError`] mapped to a HTTP status code.
+#[derive(Debug)]
+struct HttpError(StoreError);
+
+impl fmt::Display for HttpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.0)
+    }
+}
+
+impl From<StoreError> for HttpError {
+    fn from(err: StoreError) -> Self {
+        Self(err)
+    }
+}
+
+impl ResponseError for HttpError {
+    fn status_code(&self) -> StatusCode {
+        match self.0 {
+            StoreError::GraphNotFound
+            | StoreError::EdgeNotFound
+            | StoreError::NodeNotFound => StatusCode::NOT_FOUND,
+            StoreError::ConflictDuplicateNode
+            | StoreError::ConflictDuplicateEdge
+            | StoreError::ConflictUniqueKey(_)
+            | StoreError::NodeHasEdges
+            | StoreError::AmbiguousEdge => StatusCode::CONFLICT,
+            StoreError::ConstraintViolation(_) => {
+                StatusCode::UNPROCESSABLE_ENTITY
+            }
+            StoreError::ParseError | StoreError::QueryError(_) => {
+                StatusCode::BAD_REQUEST
+            }
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::build(self.status_code()).json(&self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use actix_web::test::{call_and_read_body_json, call_service, TestRequest};
+    use awc::error::WsProtocolError;
+    use awc::ws::Frame;
+    use futures_util::Stream;
+    use serde_json::{json, Value};
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::DatabaseConfig;
+
+    #[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+    struct Node {
+        id: Uuid,
+        name: String,
+    }
+
+    impl Node {
+        fn new(name: &str) -> Self {
+            Self {
+                id: Uuid::new_v4(),
+                name: name.to_string(),
+            }
+        }
+    }
+
+    impl GraphNode for Node {}
+
+    #[derive(
+        Debug,
+        Default,
+        Clone,
+        Copy,
+        Hash,
+        PartialEq,
+        Eq,
+        PartialOrd,
+        Ord,
+        Serialize,
+        Deserialize,
+    )]
+    struct Edge(usize);
+    impl GraphEdge for Edge {}
+
+    #[derive(
+        Debug,
+        Default,
+        Clone,
+        Copy,
+        Hash,
+        PartialEq,
+        Eq,
+        PartialOrd,
+        Ord,
+        Serialize,
+        Deserialize,
+    )]
+    struct NodeId(Uuid);
+
+    impl GraphNodeIndex for NodeId {}
+
+    impl From<Node> for NodeId {
+        fn from(node: Node) -> Self {
+            Self(node.id)
+        }
+    }
+
+    async fn setup(
+        test_dir: &str,
+        port: u16,
+        http_port: Option<u16>,
+    ) -> GraphDatabase<Node, Edge, NodeId> {
+        if std::path::Path::new(test_dir).is_dir() {
+            std::fs::remove_dir_all(test_dir).unwrap();
+        }
+
+        let mut config = DatabaseConfig::init();
+        config.set_store_path(test_dir.to_string());
+        config.set_server_url(format!("http://127.0.0.1:{port}"));
+        config.set_initial_remote_addresses(Vec::new());
+        if let Some(http_port) = http_port {
+            config.set_http_port(http_port);
+        }
+
+        GraphDatabase::run(config).await.unwrap()
+    }
+
+    /// Opens the websocket `path` of the gateway on `port`.
+    async fn connect_live(
+        port: u16,
+        path: &str,
+    ) -> impl Stream<Item = Result<Frame, WsProtocolError>> + Unpin {
+        let (response, connection) = awc::Client::new()
+            .ws(format!("ws://127.0.0.1:{port}{path}"))
+            .connect()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::SWITCHING_PROTOCOLS);
+
+        connection
+    }
+
+    /// Next live message, skipping the control frames.
+    async fn next_live_message<S>(connection: &mut S) -> Value
+    where
+        S: Stream<Item = Result<Frame, WsProtocolError>> + Unpin,
+    {
+        loop {
+            let frame =
+                tokio::time::timeout(Duration::from_secs(5), connection.next())
+                    .await
+                    .unwrap()
+                    .unwrap()
+                    .unwrap();
+
+            if let Frame::Text(text) = frame {
+                return serde_json::from_slice(&text).unwrap();
+            }
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_rest_gateway_ok() {
+        let test_dir = "test-data/test_rest_gateway_ok";
+        let database = setup(test_dir, 4030, None).await;
+        let app = actix_web::test::init_service(
+            App::new()
+                .app_data(web::Data::new(RestGateway { database }))
+                .configure(RestGateway::<Node, Edge, NodeId>::routes),
+        )
+        .await;
+
+        let node1 = Node::new("a");
+        let node2 = Node::new("b");
+
+        for node in [&node1, &node2] {
+            let request = TestRequest::post()
+                .uri("/nodes")
+                .set_json(node)
+                .to_request();
+            let response = call_service(&app, request).await;
+            assert_eq!(response.status(), StatusCode::CREATED);
+        }
+
+        let request = TestRequest::post()
+            .uri("/nodes")
+            .set_json(&node1)
+            .to_request();
+        let response = call_service(&app, request).await;
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+
+        // keys in paths don't need to be quoted
+        let request = TestRequest::get()
+            .uri(&format!("/nodes/{}", node1.id))
+            .to_request();
+        let node: Node = call_and_read_body_json(&app, request).await;
+        assert_eq!(node, node1);
+
+        let request = TestRequest::get().uri("/nodes/not-a-key").to_request();
+        let response = call_service(&app, request).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let request = TestRequest::post()
+            .uri("/edges")
+            .set_json(json!({ "from": node1.id, "to": node2.id, "edge": 1 }))
+            .to_request();
+        let response = call_service(&app, request).await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        let request = TestRequest::get()
+            .uri(&format!("/nodes/{}/neighbors?dir=out", node1.id))
+            .to_request();
+        let neighbors: Vec<Node> = call_and_read_body_json(&app, request).await;
+        assert_eq!(neighbors, vec![node2.clone()]);
+
+        let request = TestRequest::get()
+            .uri(&format!("/nodes/{}/neighbors?dir=in", node1.id))
+            .to_request();
+        let neighbors: Vec<Node> = call_and_read_body_json(&app, request).await;
+        assert!(neighbors.is_empty());
+
+        let request = TestRequest::get()
+            .uri(&format!("/edges/{}/{}", node1.id, node2.id))
+            .to_request();
+        let edge: Edge = call_and_read_body_json(&app, request).await;
+        assert_eq!(edge, Edge(1));
+
+        let request = TestRequest::delete()
+            .uri(&format!("/nodes/{}", node1.id))
+            .to_request();
+        let response = call_service(&app, request).await;
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+
+        let request = TestRequest::delete()
+            .uri(&format!("/nodes/{}?policy=cascade", node1.id))
+            .to_request();
+        let removed: Value = call_and_read_body_json(&app, request).await;
+        assert_eq!(
+            removed,
+            json!({
+                "node": node1,
+                "edges": [{ "from": node1.id, "to": node2.id, "edge": 1 }],
+            })
+        );
+
+        let request = TestRequest::get()
+            .uri(&format!("/nodes/{}", node1.id))
+            .to_request();
+        let response = call_service(&app, request).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let request = TestRequest::delete()
+            .uri(&format!("/edges/{}/{}", node1.id, node2.id))
+            .to_request();
+        let response = call_service(&app, request).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        if std::path::Path::new(test_dir).is_dir() {
+            std::fs::remove_dir_all(test_dir).unwrap();
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_send_live_updates_ok() {
+        let test_dir = "test-data/test_send_live_updates_ok";
+        let database = setup(test_dir, 4031, Some(4032)).await;
+
+        let node1 = database.add_node(Node::new("a")).await.unwrap();
+        let node2 = database.add_node(Node::new("b")).await.unwrap();
+        database
+            .add_edge(node1.clone().into(), node2.clone().into(), Edge(1))
+            .await
+            .unwrap();
+
+        // snapshot of the whole graph followed by the applied mutations
+        let mut connection = connect_live(4032, "/live").await;
+        let snapshot = next_live_message(&mut connection).await;
+        assert_eq!(snapshot["type"], "snapshot");
+        assert_eq!(snapshot["nodes"].as_array().unwrap().len(), 2);
+        assert_eq!(
+            snapshot["edges"],
+            json!([{ "from": node1.id, "to": node2.id, "edge": 1 }])
+        );
+
+        let node3 = database.add_node(Node::new("c")).await.unwrap();
+        let message = next_live_message(&mut connection).await;
+        assert_eq!(message["type"], "add_node");
+        assert_eq!(message["node"], json!(node3));
+
+        // snapshot of the neighborhood followed by the mutations touching it
+        let path = format!("/live?root={}&depth=1", node2.id);
+        let mut connection = connect_live(4032, &path).await;
+        let snapshot = next_live_message(&mut connection).await;
+        assert_eq!(snapshot["type"], "snapshot");
+        assert_eq!(snapshot["nodes"], json!([node2, node1]));
+
+        let node4 = database.add_node(Node::new("d")).await.unwrap();
+        database
+            .add_edge(node4.clone().into(), node1.clone().into(), Edge(2))
+            .await
+            .unwrap();
+        database
+            .remove_node(node4.clone().into(), DeletionPolicy::Detach)
+            .await
+            .unwrap();
+
+        // adding the node out of sight isn't sent
+        let message = next_live_message(&mut connection).await;
+        assert_eq!(message["type"], "add_edge");
+        assert_eq!(message["from"], json!(node4.id));
+        let message = next_live_message(&mut connection).await;
+        assert_eq!(message["type"], "remove_edge");
+        let message = next_live_message(&mut connection).await;
+        assert_eq!(message["type"], "remove_node");
+        assert_eq!(message["key"], json!(node4.id));
+
+        if std::path::Path::new(test_dir).is_dir() {
+            std::fs::remove_dir_all(test_dir).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_live_message_visible_ok() {
+        let node1 = Node::new("a");
+        let node2 = Node::new("b");
+        let applied =
+            |mutation: GraphMutation<Node, Edge, NodeId>| AppliedMutation {
+                cursor: 1,
+                hash: String::new(),
+                origin: String::new(),
+                mutation,
+                result: Ok(()),
+            };
+
+        let mut visible: Option<HashSet<NodeId>> =
+            Some(HashSet::from([node1.clone().into()]));
+
+        assert!(live_message(
+            applied(GraphMutation::AddNode(node2.clone())),
+            &mut visible
+        )
+        .is_none());
+        assert!(matches!(
+            live_message(
+                applied(GraphMutation::AddEdge((
+                    node1.clone().into(),
+                    node2.clone().into(),
+                    Edge(1),
+                ))),
+                &mut visible
+            ),
+            Some(LiveMessage::AddEdge { .. })
+        ));
+        assert!(visible.as_ref().unwrap().contains(&node2.clone().into()));
+
+        let mut failed = applied(GraphMutation::RemoveNode(node2.into()));
+        failed.result = Err(StoreError::NodeNotFound);
+        assert!(live_message(failed, &mut visible).is_none());
+    }
+}
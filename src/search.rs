@@ -0,0 +1,162 @@
+use std::collections::HashMap;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::GraphNodeIndex;
+
+// BM25 parameters
+const K1: f64 = 1.2;
+const B: f64 = 0.75;
+
+/// Inverted index over the texts returned by [`crate::GraphNode::search_text`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "I: Serialize",
+    deserialize = "I: DeserializeOwned"
+))]
+pub struct SearchIndex<I>
+where
+    I: GraphNodeIndex,
+{
+    postings: HashMap<String, HashMap<I, u32>>,
+    lengths: HashMap<I, u32>,
+    total_length: u64,
+}
+
+impl<I> Default for SearchIndex<I>
+where
+    I: GraphNodeIndex,
+{
+    fn default() -> Self {
+        Self {
+            postings: HashMap::new(),
+            lengths: HashMap::new(),
+            total_length: 0,
+        }
+    }
+}
+
+impl<I> SearchIndex<I>
+where
+    I: GraphNodeIndex,
+{
+    pub fn insert(&mut self, key: &I, text: &str) {
+        let tokens = tokenize(text);
+
+        for token in tokens.iter() {
+            *self
+                .postings
+                .entry(token.clone())
+                .or_default()
+                .entry(key.clone())
+                .or_default() += 1;
+        }
+
+        self.lengths.insert(key.clone(), tokens.len() as u32);
+        self.total_length += tokens.len() as u64;
+    }
+
+    pub fn remove(&mut self, key: &I, text: &str) {
+        for token in tokenize(text) {
+            if let Some(documents) = self.postings.get_mut(&token) {
+                documents.remove(key);
+
+                if documents.is_empty() {
+                    self.postings.remove(&token);
+                }
+            }
+        }
+
+        if let Some(length) = self.lengths.remove(key) {
+            self.total_length -= u64::from(length);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.lengths.len()
+    }
+
+    /// Keys of the documents containing any token of `text` ranked by BM25.
+    pub fn search(&self, text: &str) -> Vec<I> {
+        let count = self.lengths.len() as f64;
+        let average_length = self.total_length as f64 / count.max(1.0);
+        let mut scores: HashMap<&I, f64> = HashMap::new();
+
+        for token in tokenize(text) {
+            let Some(documents) = self.postings.get(&token) else {
+                continue;
+            };
+
+            let frequency = documents.len() as f64;
+            let idf =
+                ((count - frequency + 0.5) / (frequency + 0.5) + 1.0).ln();
+
+            for (key, term_count) in documents.iter() {
+                let term_count = f64::from(*term_count);
+                let length =
+                    f64::from(self.lengths.get(key).copied().unwrap_or(0));
+                let norm = K1 * (1.0 - B + B * length / average_length);
+
+                *scores.entry(key).or_default() +=
+                    idf * term_count * (K1 + 1.0) / (term_count + norm);
+            }
+        }
+
+        let mut ranked: Vec<(&I, f64)> = scores.into_iter().collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        ranked.into_iter().map(|(key, _)| key.clone()).collect()
+    }
+}
+
+/// Lowercased alphanumeric words of `text`.
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokenize_ok() {
+        assert_eq!(
+            tokenize("Hello, World! user_1 Ünïcode"),
+            vec!["hello", "world", "user", "1", "ünïcode"]
+        );
+        assert!(tokenize(" ,.- ").is_empty());
+    }
+
+    #[test]
+    fn test_search_ranks_by_bm25_ok() {
+        let mut index = SearchIndex::default();
+        index.insert(&"a".to_string(), "graph database graph");
+        index.insert(&"b".to_string(), "graph store with many more words");
+        index.insert(&"c".to_string(), "key value store");
+
+        // "a" mentions graph twice in a shorter text
+        assert_eq!(index.search("graph"), vec!["a", "b"]);
+        // the rare token "database" outweighs the common "store"
+        assert_eq!(index.search("database store")[0], "a");
+        assert!(index.search("missing").is_empty());
+    }
+
+    #[test]
+    fn test_remove_ok() {
+        let mut index = SearchIndex::default();
+        index.insert(&"a".to_string(), "graph database");
+        index.insert(&"b".to_string(), "graph store");
+
+        index.remove(&"a".to_string(), "graph database");
+
+        assert_eq!(index.len(), 1);
+        assert_eq!(index.search("graph"), vec!["b"]);
+        assert!(index.search("database").is_empty());
+        assert!(!index.postings.contains_key("database"));
+        assert_eq!(index.total_length, 2);
+    }
+}
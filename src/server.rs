@@ -1,11 +1,24 @@
 use std::fmt::Debug;
 use std::net::SocketAddr;
+use std::time::Instant;
 
 use actix::Addr;
 
-use tonic::transport::Server;
+use tokio::net::TcpListener;
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::transport::{Server, ServerTlsConfig};
 use tonic::{Request, Response, Status};
+use tonic_health::server::{health_reporter, HealthReporter};
+use tonic_health::ServingStatus;
+use tracing::{info_span, Instrument};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
 
+use crate::admin::DatabaseStatus;
+use crate::api::ApiServer;
+use crate::auth::{AuthConfig, ServerAuth};
+use crate::graph::{Graph, GraphMode, GraphStats, GraphStatsQuery};
+use crate::graph_api::graph_api_server::GraphApiServer;
+use crate::health;
 use crate::mutations_log::{
     MutationsLog, MutationsLogMutation, MutationsLogQuery,
 };
@@ -13,8 +26,10 @@ use crate::remotes::Remotes;
 use crate::sync_graph::sync_graph_server::{SyncGraph, SyncGraphServer};
 use crate::sync_graph::{
     GraphMutationRequest, GraphMutationResponse, MutationsLogRequest,
-    MutationsLogResponse, RemotesLogRequest, RemotesLogResponse,
+    MutationsLogResponse, RemotesLogRequest, RemotesLogResponse, StatusRequest,
+    StatusResponse,
 };
+use crate::telemetry;
 use crate::{
     GraphEdge, GraphNode, GraphNodeIndex, StoreError, SyncRemotesMessage,
 };
@@ -27,8 +42,13 @@ where
     I: GraphNodeIndex + From<N> + Unpin + 'static,
 {
     server_address: String,
+    graph: Addr<Graph<N, E, I>>,
     mutations_log: Addr<MutationsLog<N, E, I>>,
     remotes: Addr<Remotes<N, E, I>>,
+    started_at: Instant,
+    cluster_id: Option<String>,
+    /// Mode the graphs of the remotes have to run with.
+    mode: GraphMode,
 }
 
 impl<N, E, I> GraphServer<N, E, I>
@@ -37,27 +57,77 @@ where
     E: GraphEdge + Unpin + 'static,
     I: GraphNodeIndex + From<N> + Unpin + 'static,
 {
-    pub fn run(
+    /// Serves the `SyncGraph`, `GraphApi` and `grpc.health.v1.Health`
+    /// services, over mutual TLS if `tls` is set. They are reported as not
+    /// serving until the returned reporter is updated. With `auth` set,
+    /// `SyncGraph` and `GraphApi` calls have to present a token of the
+    /// cluster. Fails if `server_address` can't be bound. Metrics are
+    /// labeled with `server_url`.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn run(
         server_address: SocketAddr,
+        server_url: String,
+        graph: Addr<Graph<N, E, I>>,
         mutations_log: Addr<MutationsLog<N, E, I>>,
         remotes: Addr<Remotes<N, E, I>>,
-    ) -> Result<(), StoreError> {
+        started_at: Instant,
+        tls: Option<ServerTlsConfig>,
+        auth: Option<AuthConfig>,
+    ) -> Result<HealthReporter, StoreError> {
+        let mut builder = Server::builder();
+        if let Some(tls) = tls {
+            builder = builder
+                .tls_config(tls)
+                .map_err(|err| StoreError::ServerError(err.to_string()))?;
+        }
+
+        let (mut reporter, health_service) = health_reporter();
+        health::set_status::<N, E, I>(&mut reporter, ServingStatus::NotServing)
+            .await;
+
+        let GraphStats { mode, .. } = graph.send(GraphStatsQuery).await?;
+
+        let api_service = GraphApiServer::with_interceptor(
+            ApiServer::new(graph.clone(), mutations_log.clone(), server_url),
+            ServerAuth::new(auth.as_ref()),
+        );
+
         let sync_graph_server = Self {
             server_address: server_address.clone().to_string(),
+            graph,
             mutations_log,
             remotes,
+            started_at,
+            cluster_id: auth.as_ref().map(|auth| auth.cluster_id.clone()),
+            mode,
         };
-        let service = SyncGraphServer::new(sync_graph_server);
+        let service = SyncGraphServer::with_interceptor(
+            sync_graph_server,
+            ServerAuth::new(auth.as_ref()),
+        );
+
+        let listener =
+            TcpListener::bind(server_address).await.map_err(|err| {
+                log::error!(
+                    "Error while binding server to {server_address}. \
+Error: '{err}'"
+                );
+                StoreError::ServerError(err.to_string())
+            })?;
 
         tokio::spawn(async move {
-            Server::builder()
+            if let Err(err) = builder
+                .add_service(health_service)
                 .add_service(service)
-                .serve(server_address)
+                .add_service(api_service)
+                .serve_with_incoming(TcpListenerStream::new(listener))
                 .await
-                .unwrap()
+            {
+                log::error!("Error while serving gRPC. Error: '{err}'");
+            }
         });
 
-        Ok(())
+        Ok(reporter)
     }
 }
 
@@ -75,8 +145,29 @@ where
         let RemotesLogRequest {
             from_server,
             remotes_log,
+            cluster_id,
+            multigraph,
+            undirected,
         } = request.into_inner();
 
+        if let Some(own_cluster_id) = &self.cluster_id {
+            if &cluster_id != own_cluster_id {
+                return Err(Status::permission_denied(format!(
+                    "Remote {from_server:?} belongs to cluster {cluster_id:?}"
+                )));
+            }
+        }
+
+        let mode = GraphMode {
+            multigraph,
+            undirected,
+        };
+        if mode != self.mode {
+            return Err(Status::failed_precondition(format!(
+                "Remote {from_server:?} runs in graph mode {mode:?}"
+            )));
+        }
+
         // pass RemotesLogRequest to remotes
         match self
             .remotes
@@ -91,6 +182,9 @@ where
             Ok(flat_remotes_log) => Ok(Response::new(RemotesLogResponse {
                 from_server: self.server_address.clone(),
                 remotes_log: flat_remotes_log,
+                cluster_id: self.cluster_id.clone().unwrap_or_default(),
+                multigraph: self.mode.multigraph,
+                undirected: self.mode.undirected,
             })),
             Err(err) => {
                 let msg = format!(
@@ -104,9 +198,17 @@ where
 
     async fn sync_mutations_log(
         &self,
-        _request: Request<MutationsLogRequest>,
+        request: Request<MutationsLogRequest>,
     ) -> Result<Response<MutationsLogResponse>, Status> {
-        match self.mutations_log.send(MutationsLogQuery::full()).await {
+        let span = info_span!("sync_mutations_log");
+        span.set_parent(telemetry::extract(&request));
+
+        match self
+            .mutations_log
+            .send(MutationsLogQuery::full())
+            .instrument(span)
+            .await
+        {
             Ok(inner) => match inner {
                 Ok(mutations_log) => {
                     let mutations_log: Vec<u8> =
@@ -137,10 +239,17 @@ where
         &self,
         request: Request<GraphMutationRequest>,
     ) -> Result<Response<GraphMutationResponse>, Status> {
-        let graph_mutation_log_entry: MutationsLogMutation<N, E, I> = request
-            .into_inner()
-            .try_into()
-            .map_err(|err: StoreError| Status::internal(err.to_string()))?;
+        ServerAuth::require_write(&request)?;
+
+        let span = info_span!("graph_mutation");
+        span.set_parent(telemetry::extract(&request));
+
+        let mut graph_mutation_log_entry: MutationsLogMutation<N, E, I> =
+            request
+                .into_inner()
+                .try_into()
+                .map_err(|err: StoreError| Status::internal(err.to_string()))?;
+        graph_mutation_log_entry.span = span;
 
         match self.mutations_log.send(graph_mutation_log_entry).await {
             Ok(inner) => match inner {
@@ -162,4 +271,348 @@ where
             }
         }
     }
+
+    async fn get_status(
+        &self,
+        _request: Request<StatusRequest>,
+    ) -> Result<Response<StatusResponse>, Status> {
+        let status = DatabaseStatus::collect(
+            &self.graph,
+            &self.mutations_log,
+            &self.remotes,
+            self.started_at,
+        )
+        .await?;
+
+        Ok(Response::new(status.into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    use actix::Actor;
+    use futures_util::future::BoxFuture;
+    use opentelemetry::global;
+    use opentelemetry::sdk::export::trace::{
+        ExportResult, SpanData, SpanExporter,
+    };
+    use opentelemetry::sdk::propagation::TraceContextPropagator;
+    use opentelemetry::sdk::trace::TracerProvider;
+    use opentelemetry::trace::{TraceContextExt, TracerProvider as _};
+    use serde::{Deserialize, Serialize};
+    use tokio::sync::broadcast;
+    use tonic::codegen::InterceptedService;
+    use tonic::transport::Channel;
+    use tonic::Code;
+    use tracing::Span;
+    use tracing_subscriber::layer::SubscriberExt;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::auth::{ClientAuth, PeerRole};
+    use crate::graph_api::graph_api_client::GraphApiClient;
+    use crate::graph_api::{EmptyMessage, NodeMessage};
+    use crate::sync_graph::sync_graph_client::SyncGraphClient;
+    use crate::GraphMutation;
+
+    #[derive(
+        Debug,
+        Default,
+        Clone,
+        Copy,
+        Hash,
+        PartialEq,
+        Eq,
+        PartialOrd,
+        Ord,
+        Serialize,
+        Deserialize,
+    )]
+    struct Edge(usize);
+    impl GraphEdge for Edge {}
+
+    #[derive(
+        Debug,
+        Default,
+        Clone,
+        Copy,
+        Hash,
+        PartialEq,
+        Eq,
+        PartialOrd,
+        Ord,
+        Serialize,
+        Deserialize,
+    )]
+    struct Node(Uuid);
+    impl GraphNode for Node {}
+
+    #[derive(
+        Debug,
+        Default,
+        Clone,
+        Copy,
+        Hash,
+        PartialEq,
+        Eq,
+        PartialOrd,
+        Ord,
+        Serialize,
+        Deserialize,
+    )]
+    struct NodeId(Uuid);
+
+    impl GraphNodeIndex for NodeId {}
+
+    impl From<Node> for NodeId {
+        fn from(node: Node) -> Self {
+            Self(node.0)
+        }
+    }
+
+    /// Sends the spans closed on the server to a channel.
+    #[derive(Debug)]
+    struct ChannelExporter(mpsc::Sender<SpanData>);
+
+    impl SpanExporter for ChannelExporter {
+        fn export(
+            &mut self,
+            batch: Vec<SpanData>,
+        ) -> BoxFuture<'static, ExportResult> {
+            for span in batch {
+                // the test stops receiving once it found its spans
+                let _ = self.0.send(span);
+            }
+
+            Box::pin(std::future::ready(Ok(())))
+        }
+    }
+
+    const CLUSTER_ID: &str = "cluster";
+    const TOKEN: &str = "secret";
+    const READ_ONLY_TOKEN: &str = "read-only";
+
+    /// Serves the graph on `port` to the peers of `CLUSTER_ID`, accepting
+    /// `READ_ONLY_TOKEN` as read only.
+    async fn serve(
+        test_dir: &str,
+        port: u16,
+    ) -> Result<HealthReporter, StoreError> {
+        if std::path::Path::new(test_dir).is_dir() {
+            std::fs::remove_dir_all(test_dir).unwrap();
+        }
+
+        let graph = Graph::<Node, Edge, NodeId>::new(Some(test_dir.into()))
+            .unwrap()
+            .start();
+        let remotes = Remotes::new(1, None, None, GraphMode::default()).start();
+        let (events, _) = broadcast::channel(16);
+        let server_url = format!("http://127.0.0.1:{port}");
+        let mutations_log = MutationsLog::new(
+            graph.clone(),
+            remotes.clone(),
+            Some(test_dir.into()),
+            server_url.clone(),
+            events,
+        )
+        .await
+        .unwrap()
+        .start();
+
+        let mut auth = AuthConfig::new(CLUSTER_ID.into(), TOKEN.into());
+        auth.add_peer_token(READ_ONLY_TOKEN.into(), PeerRole::ReadOnly);
+
+        GraphServer::run(
+            SocketAddr::from(([127, 0, 0, 1], port)),
+            server_url,
+            graph,
+            mutations_log,
+            remotes,
+            Instant::now(),
+            None,
+            Some(auth),
+        )
+        .await
+    }
+
+    async fn setup(test_dir: &str, port: u16) {
+        serve(test_dir, port).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+
+    /// Clients presenting `token`, or no token at all.
+    async fn clients(
+        port: u16,
+        token: Option<&str>,
+    ) -> (
+        SyncGraphClient<InterceptedService<Channel, ClientAuth>>,
+        GraphApiClient<InterceptedService<Channel, ClientAuth>>,
+    ) {
+        let auth =
+            token.map(|token| AuthConfig::new(CLUSTER_ID.into(), token.into()));
+        let interceptor = ClientAuth::new(auth.as_ref()).unwrap();
+        let channel = Channel::from_shared(format!("http://127.0.0.1:{port}"))
+            .unwrap()
+            .connect()
+            .await
+            .unwrap();
+
+        (
+            SyncGraphClient::with_interceptor(
+                channel.clone(),
+                interceptor.clone(),
+            ),
+            GraphApiClient::with_interceptor(channel, interceptor),
+        )
+    }
+
+    #[actix_rt::test]
+    async fn test_unauthenticated_peer_rejected() {
+        let test_dir = "test-data/test_server_unauthenticated_peer_rejected";
+        setup(test_dir, 4050).await;
+
+        for token in [None, Some("invalid")] {
+            let (mut sync_client, mut api_client) = clients(4050, token).await;
+
+            let status = sync_client
+                .sync_mutations_log(MutationsLogRequest {})
+                .await
+                .unwrap_err();
+            assert_eq!(status.code(), Code::Unauthenticated);
+
+            let status =
+                api_client.get_nodes(EmptyMessage {}).await.unwrap_err();
+            assert_eq!(status.code(), Code::Unauthenticated);
+        }
+
+        let (mut sync_client, mut api_client) =
+            clients(4050, Some(TOKEN)).await;
+        assert!(sync_client
+            .sync_mutations_log(MutationsLogRequest {})
+            .await
+            .is_ok());
+        assert!(api_client.get_nodes(EmptyMessage {}).await.is_ok());
+
+        if std::path::Path::new(test_dir).is_dir() {
+            std::fs::remove_dir_all(test_dir).unwrap();
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_read_only_peer_denied_mutations() {
+        let test_dir = "test-data/test_server_read_only_peer_denied";
+        setup(test_dir, 4051).await;
+
+        let (mut sync_client, mut api_client) =
+            clients(4051, Some(READ_ONLY_TOKEN)).await;
+
+        let status = sync_client
+            .graph_mutation(GraphMutationRequest::default())
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::PermissionDenied);
+
+        let node = serde_json::to_string(&Node(Uuid::new_v4())).unwrap();
+        let status =
+            api_client.add_node(NodeMessage { node }).await.unwrap_err();
+        assert_eq!(status.code(), Code::PermissionDenied);
+
+        // reads are still granted
+        assert!(api_client.get_nodes(EmptyMessage {}).await.is_ok());
+
+        if std::path::Path::new(test_dir).is_dir() {
+            std::fs::remove_dir_all(test_dir).unwrap();
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_foreign_cluster_rejected() {
+        let test_dir = "test-data/test_server_foreign_cluster_rejected";
+        setup(test_dir, 4052).await;
+
+        let (mut sync_client, _) = clients(4052, Some(TOKEN)).await;
+
+        let status = sync_client
+            .sync_remotes(RemotesLogRequest {
+                from_server: "http://127.0.0.1:4053".to_string(),
+                cluster_id: "other".to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::PermissionDenied);
+
+        if std::path::Path::new(test_dir).is_dir() {
+            std::fs::remove_dir_all(test_dir).unwrap();
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_address_in_use_nok() {
+        let test_dir = "test-data/test_server_address_in_use_nok";
+        let _listener = std::net::TcpListener::bind("127.0.0.1:4054").unwrap();
+
+        assert!(matches!(
+            serve(test_dir, 4054).await,
+            Err(StoreError::ServerError(_))
+        ));
+
+        if std::path::Path::new(test_dir).is_dir() {
+            std::fs::remove_dir_all(test_dir).unwrap();
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_trace_context_propagated_ok() {
+        let test_dir = "test-data/test_server_trace_context_propagated_ok";
+
+        let (sender, exported) = mpsc::channel();
+        let provider = TracerProvider::builder()
+            .with_simple_exporter(ChannelExporter(sender))
+            .build();
+        let subscriber = tracing_subscriber::registry().with(
+            tracing_opentelemetry::layer().with_tracer(provider.tracer("test")),
+        );
+        // the server runs on the thread of the test
+        let _guard = tracing::subscriber::set_default(subscriber);
+        global::set_text_map_propagator(TraceContextPropagator::new());
+
+        setup(test_dir, 4055).await;
+        let (mut sync_client, _) = clients(4055, Some(TOKEN)).await;
+
+        let span = info_span!("client");
+        let trace_id = span.context().span().span_context().trace_id();
+
+        let mut request = Request::new(MutationsLogRequest {});
+        telemetry::inject(&span, &mut request);
+        sync_client.sync_mutations_log(request).await.unwrap();
+
+        let entry = MutationsLogMutation::<Node, Edge, NodeId> {
+            hash: "1".to_string(),
+            origin: "http://127.0.0.1:4056".to_string(),
+            mutation: GraphMutation::AddNode(Node(Uuid::new_v4())),
+            span: Span::none(),
+        };
+        let mut request =
+            Request::new(GraphMutationRequest::try_from(entry).unwrap());
+        telemetry::inject(&span, &mut request);
+        sync_client.graph_mutation(request).await.unwrap();
+
+        // the server spans continue the trace of the client
+        let mut traced = Vec::new();
+        while let Ok(span) = exported.recv_timeout(Duration::from_secs(1)) {
+            if span.span_context.trace_id() == trace_id {
+                traced.push(span.name.to_string());
+            }
+        }
+        assert!(traced.contains(&"sync_mutations_log".to_string()));
+        assert!(traced.contains(&"graph_mutation".to_string()));
+
+        if std::path::Path::new(test_dir).is_dir() {
+            std::fs::remove_dir_all(test_dir).unwrap();
+        }
+    }
 }
@@ -0,0 +1,95 @@
+use opentelemetry::propagation::{Extractor, Injector};
+use opentelemetry::sdk::propagation::TraceContextPropagator;
+use opentelemetry::sdk::{trace, Resource};
+use opentelemetry::{global, Context, KeyValue};
+use opentelemetry_otlp::WithExportConfig;
+use tonic::metadata::{
+    AsciiMetadataKey, AsciiMetadataValue, KeyAndValueRef, MetadataMap,
+};
+use tonic::Request;
+use tracing::Span;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use tracing_subscriber::layer::SubscriberExt;
+
+use crate::StoreError;
+
+const SERVICE_NAME: &str = "graph_db";
+
+/// Exports the spans of the database to the OTLP collector at `endpoint`
+/// and propagates their context to the remotes as W3C trace context.
+pub fn init(endpoint: &str) -> Result<(), StoreError> {
+    global::set_text_map_propagator(TraceContextPropagator::new());
+
+    let tracer = opentelemetry_otlp::new_pipeline()
+        .tracing()
+        .with_exporter(
+            opentelemetry_otlp::new_exporter()
+                .tonic()
+                .with_endpoint(endpoint),
+        )
+        .with_trace_config(trace::config().with_resource(Resource::new(vec![
+            KeyValue::new("service.name", SERVICE_NAME),
+        ])))
+        .install_batch(opentelemetry::runtime::TokioCurrentThread)
+        .map_err(|err| StoreError::ServerError(err.to_string()))?;
+
+    let subscriber = tracing_subscriber::registry()
+        .with(tracing_opentelemetry::layer().with_tracer(tracer));
+
+    // another database of the process may have installed it already
+    if let Err(err) = tracing::subscriber::set_global_default(subscriber) {
+        log::warn!("Tracing subscriber already set. Error: '{err}'");
+    }
+
+    Ok(())
+}
+
+/// Adds the trace context of `span` to the metadata of `request`.
+pub fn inject<T>(span: &Span, request: &mut Request<T>) {
+    let context = span.context();
+
+    global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(
+            &context,
+            &mut MetadataInjector(request.metadata_mut()),
+        )
+    });
+}
+
+/// Trace context of the caller sent along with `request`.
+pub fn extract<T>(request: &Request<T>) -> Context {
+    global::get_text_map_propagator(|propagator| {
+        propagator.extract(&MetadataExtractor(request.metadata()))
+    })
+}
+
+struct MetadataInjector<'a>(&'a mut MetadataMap);
+
+impl<'a> Injector for MetadataInjector<'a> {
+    fn set(&mut self, key: &str, value: String) {
+        if let (Ok(key), Ok(value)) = (
+            AsciiMetadataKey::from_bytes(key.as_bytes()),
+            value.parse::<AsciiMetadataValue>(),
+        ) {
+            self.0.insert(key, value);
+        }
+    }
+}
+
+struct MetadataExtractor<'a>(&'a MetadataMap);
+
+impl<'a> Extractor for MetadataExtractor<'a> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|value| value.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0
+            .iter()
+            .filter_map(|entry| match entry {
+                KeyAndValueRef::Ascii(key, _) => Some(key.as_str()),
+                KeyAndValueRef::Binary(_, _) => None,
+            })
+            .collect()
+    }
+}
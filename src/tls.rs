@@ -0,0 +1,98 @@
+use std::fs;
+
+use tonic::transport::{
+    Certificate, ClientTlsConfig, Identity, ServerTlsConfig,
+};
+
+use crate::StoreError;
+
+/// PEM files securing the connections between peers with mutual TLS. The
+/// server presents `cert_path` and only accepts clients with a certificate
+/// signed by a CA of `ca_path`; clients validate the servers against the
+/// same CA bundle and present the client certificate, or the server
+/// certificate if none is set.
+#[derive(Debug, Clone)]
+pub struct TlsConfig {
+    pub cert_path: String,
+    pub key_path: String,
+    pub ca_path: String,
+    pub client_cert_path: Option<String>,
+    pub client_key_path: Option<String>,
+    /// Name the certificates of the remotes are validated against instead
+    /// of the host of their url.
+    pub domain_name: Option<String>,
+}
+
+impl TlsConfig {
+    pub fn new(cert_path: String, key_path: String, ca_path: String) -> Self {
+        Self {
+            cert_path,
+            key_path,
+            ca_path,
+            client_cert_path: None,
+            client_key_path: None,
+            domain_name: None,
+        }
+    }
+
+    /// Presents `client_cert_path` instead of the server certificate when
+    /// connecting to remotes.
+    pub fn set_client_identity(
+        &mut self,
+        client_cert_path: String,
+        client_key_path: String,
+    ) {
+        self.client_cert_path = Some(client_cert_path);
+        self.client_key_path = Some(client_key_path);
+    }
+
+    /// Validates the certificates of the remotes against `domain_name`
+    /// instead of the host of their url. Remotes are addressed by IP, which
+    /// certificates can't be validated against.
+    pub fn set_domain_name(&mut self, domain_name: String) {
+        self.domain_name = Some(domain_name);
+    }
+
+    pub(crate) fn server_config(&self) -> Result<ServerTlsConfig, StoreError> {
+        Ok(ServerTlsConfig::new()
+            .identity(Identity::from_pem(
+                read_pem(&self.cert_path)?,
+                read_pem(&self.key_path)?,
+            ))
+            .client_ca_root(Certificate::from_pem(read_pem(&self.ca_path)?)))
+    }
+
+    pub(crate) fn client_config(&self) -> Result<ClientTlsConfig, StoreError> {
+        let (cert_path, key_path) =
+            match (&self.client_cert_path, &self.client_key_path) {
+                (Some(cert_path), Some(key_path)) => (cert_path, key_path),
+                _ => {
+                    log::warn!(
+                        "No client certificate set, presenting the server \
+certificate {:?} to remotes",
+                        self.cert_path
+                    );
+                    (&self.cert_path, &self.key_path)
+                }
+            };
+
+        let mut config = ClientTlsConfig::new()
+            .ca_certificate(Certificate::from_pem(read_pem(&self.ca_path)?))
+            .identity(Identity::from_pem(
+                read_pem(cert_path)?,
+                read_pem(key_path)?,
+            ));
+        if let Some(domain_name) = &self.domain_name {
+            config = config.domain_name(domain_name);
+        }
+
+        Ok(config)
+    }
+}
+
+fn read_pem(path: &str) -> Result<Vec<u8>, StoreError> {
+    fs::read(path).map_err(|err| {
+        log::error!("Error while reading PEM file {path:?}. Error: '{err}'");
+        StoreError::FileLoadError(err.to_string())
+    })
+}
@@ -1,3 +1,4 @@
+use graph_db::DeletionPolicy;
 use uuid::Uuid;
 
 use common::{Edge, Node};
@@ -28,8 +29,14 @@ async fn test_basic_single_process() {
     assert_eq!(edges.len(), 1);
     assert_eq!(nodes.len(), 2);
 
-    database.remove_node(node1).await.unwrap();
-    database.remove_node(node2).await.unwrap();
+    database
+        .remove_node(node1, DeletionPolicy::Detach)
+        .await
+        .unwrap();
+    database
+        .remove_node(node2, DeletionPolicy::Detach)
+        .await
+        .unwrap();
 
     let resulting_graph = database.get_graph().await.unwrap();
     let edges: Vec<_> = resulting_graph.edge_indices().into_iter().collect();
@@ -86,10 +93,22 @@ async fn test_basic_single_process() {
     assert_eq!(edges.len(), 0);
     assert_eq!(nodes.len(), 2);
 
-    database.remove_node(node1).await.unwrap();
-    database.remove_node(node2).await.unwrap();
-    database.remove_node(node3).await.unwrap();
-    database.remove_node(node4).await.unwrap();
+    database
+        .remove_node(node1, DeletionPolicy::Detach)
+        .await
+        .unwrap();
+    database
+        .remove_node(node2, DeletionPolicy::Detach)
+        .await
+        .unwrap();
+    database
+        .remove_node(node3, DeletionPolicy::Detach)
+        .await
+        .unwrap();
+    database
+        .remove_node(node4, DeletionPolicy::Detach)
+        .await
+        .unwrap();
     database.add_node(node1).await.unwrap();
     database.add_node(node2).await.unwrap();
     database.add_node(node3).await.unwrap();
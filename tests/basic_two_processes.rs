@@ -1,4 +1,5 @@
 use common::{Node, NodeId};
+use graph_db::DeletionPolicy;
 use uuid::Uuid;
 
 mod common;
@@ -51,7 +52,7 @@ async fn test_basic_two_processes() {
     assert_eq!(nodes1, nodes2);
 
     database1
-        .remove_node(NodeId(first_node_id.clone()))
+        .remove_node(NodeId(first_node_id.clone()), DeletionPolicy::Detach)
         .await
         .unwrap();
     let nodes1 = database1.get_nodes().await.unwrap();
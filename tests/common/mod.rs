@@ -77,6 +77,18 @@ pub async fn setup(
     port: u16,
     initial_remotes: Vec<String>,
 ) -> SimpleDatabase {
+    let database_config = config(store_path, port, initial_remotes);
+
+    GraphDatabase::run(database_config).await.unwrap()
+}
+
+/// Configuration of a database on a fresh `store_path`, to adjust before
+/// running it.
+pub fn config(
+    store_path: &'static str,
+    port: u16,
+    initial_remotes: Vec<String>,
+) -> DatabaseConfig {
     if std::path::Path::new(store_path).is_dir() {
         std::fs::remove_dir_all(store_path).unwrap();
     }
@@ -87,5 +99,5 @@ pub async fn setup(
     database_config.set_server_url(server_address);
     database_config.set_initial_remote_addresses(initial_remotes);
 
-    GraphDatabase::run(database_config).await.unwrap()
+    database_config
 }
@@ -0,0 +1,153 @@
+use std::time::Duration;
+
+use common::Edge;
+use graph_db::{
+    GraphDatabase, GraphNode, GraphNodeIndex, GraphSchema, IndexKey, StoreError,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+mod common;
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+struct User {
+    id: Uuid,
+    email: String,
+}
+
+impl User {
+    fn new(email: &str) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            email: email.to_string(),
+        }
+    }
+}
+
+impl GraphNode for User {
+    fn unique_keys(&self) -> Vec<(&'static str, IndexKey)> {
+        vec![("email", self.email.as_str().into())]
+    }
+}
+
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    Copy,
+    Hash,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Serialize,
+    Deserialize,
+)]
+struct UserId(Uuid);
+
+impl GraphNodeIndex for UserId {}
+
+impl From<User> for UserId {
+    fn from(user: User) -> Self {
+        Self(user.id)
+    }
+}
+
+type UserDatabase = GraphDatabase<User, Edge, UserId>;
+
+struct EmailSchema;
+
+impl GraphSchema<User, Edge> for EmailSchema {
+    fn validate_node(&self, user: &User) -> Result<(), String> {
+        if user.email.contains('@') {
+            Ok(())
+        } else {
+            Err(format!("invalid email {:?}", user.email))
+        }
+    }
+}
+
+#[actix_rt::test]
+async fn test_concurrent_unique_keys_converge_on_replicas() {
+    let root_test_path = "test-data/test_concurrent_unique_keys";
+
+    let test_dir1 = "test-data/test_concurrent_unique_keys/node1";
+    let config1 = common::config(test_dir1, 4020, vec![]);
+    let database1 = UserDatabase::run(config1).await.unwrap();
+
+    let test_dir2 = "test-data/test_concurrent_unique_keys/node2";
+    let initial_remotes2 = vec!["http://127.0.0.1:4020".to_string()];
+    let config2 = common::config(test_dir2, 4021, initial_remotes2);
+    let database2 = UserDatabase::run(config2).await.unwrap();
+
+    let emails: Vec<_> =
+        (0..5).map(|i| format!("user{i}@example.com")).collect();
+
+    for email in emails.iter() {
+        let (result1, result2) = tokio::join!(
+            database1.add_node(User::new(email)),
+            database2.add_node(User::new(email)),
+        );
+
+        // at least the add of the earlier mutation succeeds
+        assert!(result1.is_ok() || result2.is_ok());
+        for result in [result1, result2] {
+            if let Err(err) = result {
+                assert_eq!(err, StoreError::ConflictUniqueKey("email".into()));
+            }
+        }
+    }
+    tokio::time::sleep(Duration::from_millis(500)).await;
+
+    let mut users1 = database1.get_nodes().await.unwrap();
+    let mut users2 = database2.get_nodes().await.unwrap();
+    users1.sort_by(|a, b| a.email.cmp(&b.email));
+    users2.sort_by(|a, b| a.email.cmp(&b.email));
+
+    // both replicas keep the same user for each email
+    assert_eq!(users1.len(), emails.len());
+    assert_eq!(users1, users2);
+
+    if std::path::Path::new(root_test_path).is_dir() {
+        std::fs::remove_dir_all(root_test_path).unwrap();
+    }
+}
+
+#[actix_rt::test]
+async fn test_replicated_schema_violation_rejected() {
+    let root_test_path = "test-data/test_replicated_schema_violation";
+
+    // the first replica runs without the schema, e.g. an older deployment
+    let test_dir1 = "test-data/test_replicated_schema_violation/node1";
+    let config1 = common::config(test_dir1, 4022, vec![]);
+    let database1 = UserDatabase::run(config1).await.unwrap();
+
+    let test_dir2 = "test-data/test_replicated_schema_violation/node2";
+    let initial_remotes2 = vec!["http://127.0.0.1:4022".to_string()];
+    let config2 = common::config(test_dir2, 4023, initial_remotes2);
+    let database2 = UserDatabase::run_with_schema(config2, EmailSchema)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        database2.add_node(User::new("invalid")).await,
+        Err(StoreError::ConstraintViolation(
+            "invalid email \"invalid\"".to_string()
+        ))
+    );
+
+    let valid = database1
+        .add_node(User::new("a@example.com"))
+        .await
+        .unwrap();
+    let invalid = database1.add_node(User::new("invalid")).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(500)).await;
+
+    assert!(database1.has_node(invalid.clone().into()).await.unwrap());
+    assert!(database2.has_node(valid.into()).await.unwrap());
+    assert!(!database2.has_node(invalid.into()).await.unwrap());
+
+    if std::path::Path::new(root_test_path).is_dir() {
+        std::fs::remove_dir_all(root_test_path).unwrap();
+    }
+}
@@ -0,0 +1,32 @@
+#!/bin/sh
+# Regenerates the certificates of the TLS tests: `peer` is signed by the
+# trusted `ca`, `rogue` by `rogue-ca` which no peer trusts.
+set -e
+cd "$(dirname "$0")"
+
+cat > ext.cnf <<CNF
+subjectAltName = DNS:localhost, IP:127.0.0.1
+extendedKeyUsage = serverAuth, clientAuth
+CNF
+
+for ca in ca rogue-ca; do
+    openssl genpkey -algorithm EC -pkeyopt ec_paramgen_curve:P-256 \
+        -out "$ca.key"
+    openssl req -x509 -new -key "$ca.key" -subj "/CN=$ca" -days 36500 \
+        -addext "basicConstraints = critical, CA:TRUE" \
+        -addext "keyUsage = critical, keyCertSign, cRLSign" -out "$ca.pem"
+done
+
+for peer in peer:ca rogue:rogue-ca; do
+    name="${peer%%:*}"
+    ca="${peer##*:}"
+    openssl genpkey -algorithm EC -pkeyopt ec_paramgen_curve:P-256 \
+        -out "$name.key"
+    openssl req -new -key "$name.key" -subj "/CN=localhost" -out "$name.csr"
+    openssl x509 -req -in "$name.csr" -CA "$ca.pem" -CAkey "$ca.key" \
+        -CAcreateserial -days 36500 -extfile ext.cnf \
+        -out "$name.pem"
+    rm "$name.csr"
+done
+
+rm -f ext.cnf ./*.srl
@@ -0,0 +1,30 @@
+use std::time::Duration;
+
+use common::{Node, SimpleDatabase};
+use uuid::Uuid;
+
+mod common;
+
+#[actix_rt::test]
+async fn test_remote_in_other_graph_mode_not_synchronized() {
+    let root_test_path = "test-data/test_remote_in_other_graph_mode";
+
+    let test_dir1 = "test-data/test_remote_in_other_graph_mode/node1";
+    let mut config1 = common::config(test_dir1, 4010, vec![]);
+    config1.set_multigraph(true);
+    let database1 = SimpleDatabase::run(config1).await.unwrap();
+
+    let test_dir2 = "test-data/test_remote_in_other_graph_mode/node2";
+    let initial_remotes2 = vec!["http://127.0.0.1:4010".to_string()];
+    let database2 = common::setup(test_dir2, 4011, initial_remotes2).await;
+
+    database2.add_node(Node::new(Uuid::new_v4())).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(500)).await;
+
+    assert_eq!(database2.get_nodes().await.unwrap().len(), 1);
+    assert!(database1.get_nodes().await.unwrap().is_empty());
+
+    if std::path::Path::new(root_test_path).is_dir() {
+        std::fs::remove_dir_all(root_test_path).unwrap();
+    }
+}
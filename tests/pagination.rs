@@ -0,0 +1,46 @@
+use futures_util::StreamExt;
+use uuid::Uuid;
+
+use common::Node;
+
+mod common;
+
+const TOTAL_NODES: usize = 5;
+
+#[actix_rt::test]
+async fn test_pagination() {
+    let test_dir = "test-data/test_pagination";
+
+    let database = common::setup(test_dir, 4000, vec![]).await;
+
+    for _ in 0..TOTAL_NODES {
+        database.add_node(Node::new(Uuid::new_v4())).await.unwrap();
+    }
+
+    let first_page = database.get_nodes_page(None, 2).await.unwrap();
+    assert_eq!(first_page.len(), 2);
+
+    let after = first_page.last().map(|node| (*node).into());
+    let second_page = database.get_nodes_page(after, 10).await.unwrap();
+    assert_eq!(second_page.len(), TOTAL_NODES - 2);
+
+    for node in first_page.iter() {
+        assert!(!second_page.contains(node));
+    }
+
+    let mut streamed = Vec::new();
+    let mut stream = Box::pin(database.stream_nodes(2));
+
+    while let Some(node) = stream.next().await {
+        streamed.push(node.unwrap());
+    }
+
+    // nodes are streamed in key order
+    let mut nodes = database.get_nodes().await.unwrap();
+    nodes.sort();
+    assert_eq!(streamed, nodes);
+
+    if std::path::Path::new(test_dir).is_dir() {
+        std::fs::remove_dir_all(test_dir).unwrap();
+    }
+}
@@ -0,0 +1,36 @@
+use uuid::Uuid;
+
+use common::{Edge, Node};
+
+mod common;
+
+#[actix_rt::test]
+async fn test_status() {
+    let test_dir = "test-data/test_status";
+
+    let database = common::setup(test_dir, 4002, vec![]).await;
+
+    assert!(database.is_ready().await);
+
+    let node1 = Node::new(Uuid::new_v4());
+    let node2 = Node::new(Uuid::new_v4());
+
+    database.add_node(node1).await.unwrap();
+    database.add_node(node2).await.unwrap();
+    database.add_edge(node1, node2, Edge(1)).await.unwrap();
+
+    let status = database.status().await.unwrap();
+
+    assert_eq!(status.node_count, 2);
+    assert_eq!(status.edge_count, 1);
+    assert_eq!(status.mutations_log_length, 3);
+    assert!(status.last_mutation_hash.is_some());
+    assert!(status.remotes.is_empty());
+    assert!(status.pending_mutations.is_empty());
+    assert!(status.snapshot_size.is_some());
+    assert_eq!(status.version, env!("CARGO_PKG_VERSION"));
+
+    if std::path::Path::new(test_dir).is_dir() {
+        std::fs::remove_dir_all(test_dir).unwrap();
+    }
+}
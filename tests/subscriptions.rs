@@ -0,0 +1,185 @@
+use futures_util::StreamExt;
+use graph_db::{DeletionPolicy, MutationKind, StoreError, SubscriptionFilter};
+use uuid::Uuid;
+
+use common::{Edge, Node, NodeId};
+
+mod common;
+
+#[actix_rt::test]
+async fn test_subscriptions() {
+    let test_dir = "test-data/test_subscriptions";
+
+    let database = common::setup(test_dir, 4000, vec![]).await;
+
+    let node1 = Node::new(Uuid::new_v4());
+    let node2 = Node::new(Uuid::new_v4());
+
+    let mut all = Box::pin(
+        database
+            .subscribe(SubscriptionFilter::default())
+            .await
+            .unwrap(),
+    );
+    let mut edges = Box::pin(
+        database
+            .subscribe(SubscriptionFilter {
+                kinds: vec![MutationKind::AddEdge],
+                ..Default::default()
+            })
+            .await
+            .unwrap(),
+    );
+
+    database.add_node(node1).await.unwrap();
+    database.add_node(node2).await.unwrap();
+    database.add_edge(node1, node2, Edge(1)).await.unwrap();
+
+    let first = all.next().await.unwrap().unwrap();
+    assert_eq!(first.mutation.keys(), vec![NodeId::from(node1)]);
+    assert_eq!(first.origin, "http://127.0.0.1:4000");
+    assert!(first.result.is_ok());
+
+    let edge = edges.next().await.unwrap().unwrap();
+    assert_eq!(edge.mutation.kind(), MutationKind::AddEdge);
+
+    // resuming after the first mutation replays the remaining ones
+    let mut resumed = Box::pin(
+        database
+            .subscribe(SubscriptionFilter {
+                after: Some(first.cursor),
+                ..Default::default()
+            })
+            .await
+            .unwrap(),
+    );
+
+    let second = resumed.next().await.unwrap().unwrap();
+    assert_eq!(second.mutation.keys(), vec![NodeId::from(node2)]);
+    let third = resumed.next().await.unwrap().unwrap();
+    assert_eq!(third.cursor, edge.cursor);
+
+    if std::path::Path::new(test_dir).is_dir() {
+        std::fs::remove_dir_all(test_dir).unwrap();
+    }
+}
+
+#[actix_rt::test]
+async fn test_node_removal_published_with_edge_removals() {
+    let test_dir = "test-data/test_node_removal_published_with_edge_removals";
+
+    let database = common::setup(test_dir, 4004, vec![]).await;
+
+    let node1 = Node::new(Uuid::new_v4());
+    let node2 = Node::new(Uuid::new_v4());
+    let node3 = Node::new(Uuid::new_v4());
+
+    database.add_node(node1).await.unwrap();
+    database.add_node(node2).await.unwrap();
+    database.add_node(node3).await.unwrap();
+    database.add_edge(node1, node2, Edge(1)).await.unwrap();
+    database.add_edge(node3, node1, Edge(2)).await.unwrap();
+
+    let mut removals = Box::pin(
+        database
+            .subscribe(SubscriptionFilter {
+                kinds: vec![MutationKind::RemoveEdge, MutationKind::RemoveNode],
+                ..Default::default()
+            })
+            .await
+            .unwrap(),
+    );
+
+    // restricted removals fail before anything is logged
+    assert_eq!(
+        database.remove_node(node1, DeletionPolicy::Restrict).await,
+        Err(StoreError::NodeHasEdges)
+    );
+
+    let (node, mut edges) = database
+        .remove_node(node1, DeletionPolicy::Cascade)
+        .await
+        .unwrap();
+    edges.sort_by_key(|(_, _, edge)| edge.0);
+    assert_eq!(node, node1);
+    assert_eq!(
+        edges,
+        vec![
+            (node1.into(), node2.into(), Edge(1)),
+            (node3.into(), node1.into(), Edge(2)),
+        ]
+    );
+
+    let mut kinds = Vec::new();
+    for _ in 0..3 {
+        let event = removals.next().await.unwrap().unwrap();
+        assert!(event.result.is_ok());
+        kinds.push(event.mutation.kind());
+    }
+    assert_eq!(
+        kinds,
+        vec![
+            MutationKind::RemoveEdge,
+            MutationKind::RemoveEdge,
+            MutationKind::RemoveNode
+        ]
+    );
+    assert!(database.get_edges().await.unwrap().is_empty());
+
+    if std::path::Path::new(test_dir).is_dir() {
+        std::fs::remove_dir_all(test_dir).unwrap();
+    }
+}
+
+#[actix_rt::test]
+async fn test_edges_added_during_node_removal_logged() {
+    let test_dir = "test-data/test_edges_added_during_node_removal_logged";
+
+    let database = common::setup(test_dir, 4005, vec![]).await;
+
+    let mut edge_removals = Box::pin(
+        database
+            .subscribe(SubscriptionFilter {
+                kinds: vec![MutationKind::RemoveEdge],
+                ..Default::default()
+            })
+            .await
+            .unwrap(),
+    );
+
+    let mut added_edges = 0;
+    for i in 0..10 {
+        let node1 = Node::new(Uuid::new_v4());
+        let node2 = Node::new(Uuid::new_v4());
+        database.add_node(node1).await.unwrap();
+        database.add_node(node2).await.unwrap();
+
+        let (added, removed) = tokio::join!(
+            database.add_edge(node1, node2, Edge(i)),
+            database.remove_node(node1, DeletionPolicy::Detach),
+        );
+        removed.unwrap();
+        if added.is_ok() {
+            added_edges += 1;
+        }
+    }
+
+    // every edge added ahead of the removal of its node is removed by a
+    // logged mutation of its own
+    for _ in 0..added_edges {
+        let event = tokio::time::timeout(
+            std::time::Duration::from_secs(1),
+            edge_removals.next(),
+        )
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+        assert!(event.result.is_ok());
+    }
+    assert!(database.get_edges().await.unwrap().is_empty());
+
+    if std::path::Path::new(test_dir).is_dir() {
+        std::fs::remove_dir_all(test_dir).unwrap();
+    }
+}
@@ -0,0 +1,63 @@
+use std::time::Duration;
+
+use common::{Node, SimpleDatabase};
+use graph_db::{DatabaseConfig, TlsConfig};
+use uuid::Uuid;
+
+mod common;
+
+const FIXTURES: &str = "tests/fixtures/tls";
+
+/// Configuration of a database presenting the certificate `name` and
+/// trusting the CA the trusted peers are signed by.
+fn tls_config(
+    store_path: &'static str,
+    port: u16,
+    initial_remotes: Vec<String>,
+    name: &str,
+) -> DatabaseConfig {
+    let mut config = common::config(store_path, port, initial_remotes);
+    config.set_server_url(format!("https://127.0.0.1:{}", port));
+
+    let mut tls = TlsConfig::new(
+        format!("{FIXTURES}/{name}.pem"),
+        format!("{FIXTURES}/{name}.key"),
+        format!("{FIXTURES}/ca.pem"),
+    );
+    tls.set_domain_name("localhost".to_string());
+    config.set_tls(tls);
+
+    config
+}
+
+#[actix_rt::test]
+async fn test_untrusted_peer_rejected() {
+    let root_test_path = "test-data/test_untrusted_peer_rejected";
+
+    let test_dir1 = "test-data/test_untrusted_peer_rejected/node1";
+    let config1 = tls_config(test_dir1, 4040, vec![], "peer");
+    let database1 = SimpleDatabase::run(config1).await.unwrap();
+
+    // signed by a CA the first peer doesn't trust
+    let test_dir2 = "test-data/test_untrusted_peer_rejected/node2";
+    let initial_remotes = vec!["https://127.0.0.1:4040".to_string()];
+    let config2 = tls_config(test_dir2, 4041, initial_remotes.clone(), "rogue");
+    let database2 = SimpleDatabase::run(config2).await.unwrap();
+
+    let test_dir3 = "test-data/test_untrusted_peer_rejected/node3";
+    let config3 = tls_config(test_dir3, 4042, initial_remotes, "peer");
+    let database3 = SimpleDatabase::run(config3).await.unwrap();
+
+    let rogue_node = Node::new(Uuid::new_v4());
+    let trusted_node = Node::new(Uuid::new_v4());
+    database2.add_node(rogue_node).await.unwrap();
+    database3.add_node(trusted_node).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(500)).await;
+
+    assert!(!database1.has_node(rogue_node.into()).await.unwrap());
+    assert!(database1.has_node(trusted_node.into()).await.unwrap());
+
+    if std::path::Path::new(root_test_path).is_dir() {
+        std::fs::remove_dir_all(root_test_path).unwrap();
+    }
+}